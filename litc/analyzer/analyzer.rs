@@ -1,57 +1,1304 @@
+use std::cell::Cell;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use crate::ast::*;
-use crate::generate_plain_error;
+use crate::lexer::Span;
+use crate::lexer::FileId;
+use crate::lint::Lint;
+use crate::lint::LintConfig;
+use crate::lint::Level;
+use crate::lint::ALL_LINTS;
+use crate::lint::Linter;
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::codes;
+use crate::generate_error;
+use crate::utils::suggest::closest;
 
-pub fn analyze(program: &Program) {
-    let mut analyzer = Analyzer::new(&program.expr_arena);
+use super::patterns;
 
-    for func in &program.funcs {
-        analyzer.analyze_func(func);
+pub fn analyze(program: &mut Program, lints: &LintConfig) {
+    check_supported(program);
+    check_bound_attrs(program);
+
+    let mut defined: HashMap<&str, &Span> = HashMap::new();
+
+    for func in program.funcs.iter().chain(&program.templates.funcs) {
+        if let Some(receiver) = &func.receiver {
+            Diagnostic::error(format!("Function `{}` cannot take `self`", func.name))
+                .with_code(&codes::E0047)
+                .with_span(receiver)
+                .with_note("only methods, declared in a class or an `impl` block, have a receiver".to_string())
+                .abort();
+        }
+
+        if Builtin::from_name(func.base_name()).is_some() {
+            Diagnostic::error(format!("Function `{}` is a builtin and cannot be redefined", func.name))
+                .with_code(&codes::E0008)
+                .with_span(&func.span)
+                .abort();
+        }
+
+        if func.is_override {
+            Diagnostic::error(format!("Function `{}` cannot be marked `override`", func.name))
+                .with_code(&codes::E0054)
+                .with_span(&func.span)
+                .with_note("only methods that implement an interface method are `override`".to_string())
+                .abort();
+        }
+
+        if let Some(first) = defined.insert(&func.name, &func.span) {
+            Diagnostic::error(format!("Function `{}` is already defined", func.name))
+                .with_code(&codes::E0009)
+                .with_span(&func.span)
+                .with_label(first, format!("`{}` is first defined here", func.name))
+                .abort();
+        }
+    }
+
+    let main = match program.funcs.iter().find(|f| f.name == "main") {
+        Some(main) => main,
+        // the entry file is the first one loaded
+        None => Diagnostic::error("Missing entry point: function `main` is not defined".to_string())
+            .with_code(&codes::E0010)
+            .with_span(&Span::new(FileId::default(), 0, 0))
+            .with_note("a program starts by calling `fun main()` of its entry file".to_string())
+            .abort(),
+    };
+
+    check_entry_point(main);
+    check_const_exprs(program);
+
+    let root_file = main.span.file;
+
+    let linter = Linter::new(lints);
+    let funcs = program.signatures();
+    let classes = program.class_sigs();
+    let enums = program.enum_sigs();
+    let interfaces = program.interface_sigs();
+    let methods = program.method_sigs();
+    let consts = program.consts.iter().map(|c| (c.name.clone(), c._type.clone())).collect();
+    let implementations = program.implementations();
+    let mut probes = std::mem::take(&mut program.templates.probes);
+    let mut analyzer = Analyzer::new(
+        &program.expr_arena, &linter, funcs, classes, enums, interfaces, methods, consts, &program.templates, implementations,
+    );
+
+    analyzer.check_bounds();
+
+    for constant in &program.consts {
+        analyzer.check_type(&constant._type, &constant.span);
+
+        let expr_type = analyzer.infer_type(constant.expr_id);
+
+        if expr_type != constant._type {
+            Diagnostic::error(format!(
+                "Cannot assign {} value to constant `{}` of type `{}`",
+                expr_type, constant.name, constant._type
+            ))
+                .with_code(&codes::E0014)
+                .with_span(&program.expr_arena.get(constant.expr_id).span)
+                .abort();
+        }
+    }
+
+    let generic_methods = program.templates.methods.iter().map(|(owner, method)| (owner.clone(), method));
+    analyzer.check_methods(&program.methods().into_iter().chain(generic_methods).collect::<Vec<_>>());
+
+    for interface in &program.interfaces {
+        analyzer.check_interface(interface);
+    }
+
+    let callees = analyze_items(&mut analyzer, &mut program.funcs, &mut program.classes, &mut program.enums, &mut program.impls);
+
+    for probe in &mut probes {
+        analyze_probe(program, probe);
+    }
+
+    check_unused_funcs(&program.funcs, root_file, &callees, &linter);
+
+    linter.finish();
+}
+
+/// Checks the types, conformance and bodies of the given items, and returns
+/// the names of the functions that each of them calls.
+fn analyze_items(
+    analyzer: &mut Analyzer,
+    funcs: &mut [FuncDef],
+    classes: &mut [ClassDef],
+    enums: &mut [EnumDef],
+    impls: &mut [ImplDef],
+) -> HashMap<String, HashSet<String>> {
+    let mut callees = HashMap::new();
+
+    for class in classes.iter() {
+        analyzer.check_fields(class);
+    }
+
+    for _enum in enums.iter() {
+        analyzer.check_variants(_enum);
+    }
+
+    for block in impls.iter().flat_map(|i| &i.blocks) {
+        analyzer.check_type(&block.target, &block.span);
+    }
+
+    for class in classes.iter() {
+        analyzer.check_conformance(&Type::Named(class.name.clone(), Vec::new()), &class.interfaces, &class.methods, &class.span, false);
+    }
+
+    for _enum in enums.iter() {
+        analyzer.check_conformance(&Type::Named(_enum.name.clone(), Vec::new()), &_enum.interfaces, &_enum.methods, &_enum.span, false);
+    }
+
+    for _impl in impls.iter() {
+        for block in &_impl.blocks {
+            analyzer.check_conformance(&block.target, _impl.interface.as_slice(), &block.methods, &block.span, _impl.interface.is_some());
+        }
+    }
+
+    for func in funcs.iter_mut() {
+        callees.insert(func.name.clone(), analyzer.analyze_func(func));
+    }
+
+    // a call of a class counts as a call of each of its constructors
+    for class in classes.iter_mut() {
+        for ctor in &mut class.ctors {
+            let called = analyzer.analyze_ctor(&class.name, ctor);
+            callees.entry(class.name.clone()).or_insert_with(HashSet::new).extend(called);
+        }
+
+        let owner = Type::Named(class.name.clone(), Vec::new());
+
+        for method in &mut class.methods {
+            callees.insert(method_name(&class.name, &method.name), analyzer.analyze_method(&owner, method));
+        }
+    }
+
+    for _enum in enums.iter_mut() {
+        let owner = Type::Named(_enum.name.clone(), Vec::new());
+
+        for method in &mut _enum.methods {
+            callees.insert(method_name(&_enum.name, &method.name), analyzer.analyze_method(&owner, method));
+        }
+    }
+
+    for block in impls.iter_mut().flat_map(|i| &mut i.blocks) {
+        for method in &mut block.methods {
+            callees.insert(method_name(&block.target.to_string(), &method.name), analyzer.analyze_method(&block.target, method));
+        }
+    }
+
+    callees
+}
+
+/// Analyzes `probe` against the items of `program` and its own. Lints are
+/// all allowed, so that what they find in a template is reported once, for
+/// the instances that the program uses.
+fn analyze_probe(program: &Program, probe: &mut Probe) {
+    let mut lints = LintConfig::new();
+    lints.set(ALL_LINTS, Level::Allow);
+
+    let linter = Linter::new(&lints);
+    let mut funcs = program.signatures();
+    let mut classes = program.class_sigs();
+    let mut enums = program.enum_sigs();
+    let mut methods = program.method_sigs();
+    let mut implementations = program.implementations();
+
+    funcs.extend(probe.funcs.iter().map(|f| (f.name.clone(), f.sig())));
+    classes.extend(probe.classes.iter().map(|c| (c.name.clone(), c.sig())));
+    enums.extend(probe.enums.iter().map(|e| (e.name.clone(), e.sig())));
+
+    let types = probe.classes.iter().map(|c| (&c.name, &c.interfaces, &c.methods))
+        .chain(probe.enums.iter().map(|e| (&e.name, &e.interfaces, &e.methods)));
+
+    // a placeholder replaces any type of the same name
+    for (name, interfaces, defs) in types {
+        methods.insert(name.clone(), defs.iter().map(|m| (m.name.clone(), m.method_sig())).collect());
+        implementations.extend(interfaces.iter().map(|i| (name.clone(), i.to_string())));
+    }
+
+    for block in probe.impls.iter().flat_map(|i| &i.blocks) {
+        methods.entry(block.target.to_string()).or_default()
+            .extend(block.methods.iter().map(|m| (m.name.clone(), m.method_sig())));
+    }
+
+    let consts = program.consts.iter().map(|c| (c.name.clone(), c._type.clone())).collect();
+    let mut analyzer = Analyzer::new(
+        &program.expr_arena, &linter, funcs, classes, enums, program.interface_sigs(), methods, consts, &program.templates, implementations,
+    );
+
+    analyze_items(&mut analyzer, &mut probe.funcs, &mut probe.classes, &mut probe.enums, &mut probe.impls);
+}
+
+/// Reports a construct that parses, but that no later pass handles yet.
+fn not_supported(what: &str, span: &Span) -> ! {
+    Diagnostic::error(format!("{} not supported yet", what))
+        .with_code(&codes::E0040)
+        .with_span(span)
+        .abort()
+}
+
+/// Rejects the items that only the parser knows about so far.
+fn check_supported(program: &Program) {
+    let templates = &program.templates;
+
+    for class in program.classes.iter().chain(&templates.classes) {
+        if let Some(generic) = class.ctors.iter().flat_map(|c| &c.generics).next() {
+            not_supported("Generic constructors are", &generic.span);
+        }
+    }
+
+    for interface in &program.interfaces {
+        if let Some(generic) = interface.generics.first() {
+            not_supported("Generic interfaces are", &generic.span);
+        }
+
+        if let Some(generic) = interface.methods.iter().flat_map(|m| &m.generics).next() {
+            not_supported("Generic functions are", &generic.span);
+        }
+    }
+
+    if let Some(block) = program.llvm.first() {
+        not_supported("`llvm` blocks are", &block.span);
+    }
+
+    let funcs = program.funcs.iter()
+        .chain(program.methods().into_iter().map(|(_, m)| m))
+        .chain(&templates.funcs)
+        .chain(templates.methods.iter().map(|(_, m)| m))
+        .chain(templates.classes.iter().flat_map(|c| &c.methods))
+        .chain(templates.enums.iter().flat_map(|e| &e.methods));
+
+    for func in funcs {
+        if let Some(attr) = func.attrs.iter().find(|a| matches!(a.kind, AttrKind::Word(_))) {
+            not_supported("Attributes are", &attr.span);
+        }
+
+        if let Some(param) = func.params.iter().find(|p| p.variadic) {
+            not_supported("Variadic parameters are", &param.span);
+        }
+    }
+}
+
+/// Checks that `#[T : Interface]` is only put on methods of generic classes
+/// and enums, and names one of their generic parameters. The resolver has
+/// already dropped these attributes from the instances.
+fn check_bound_attrs(program: &Program) {
+    let templates = &program.templates;
+    let bound_attr = |func: &FuncDef| func.attrs.iter()
+        .find_map(|attr| match &attr.kind {
+            AttrKind::Bound(param, _) => Some((param.clone(), attr.span.clone())),
+            AttrKind::Word(_) => None,
+        });
+
+    let unconditional = program.funcs.iter()
+        .chain(program.methods().into_iter().map(|(_, m)| m))
+        .chain(&templates.funcs)
+        .chain(templates.methods.iter().map(|(_, m)| m));
+
+    for func in unconditional {
+        if let Some((_, span)) = bound_attr(func) {
+            Diagnostic::error(format!("Function `{}` cannot have a bound attribute", func.name))
+                .with_code(&codes::E0058)
+                .with_span(&span)
+                .with_note("only methods of generic classes and enums can be conditional on a type argument".to_string())
+                .abort();
+        }
+    }
+
+    let conditional = templates.classes.iter().map(|c| (&c.name, &c.generics, &c.methods))
+        .chain(templates.enums.iter().map(|e| (&e.name, &e.generics, &e.methods)));
+
+    for (owner, generics, methods) in conditional {
+        for (param, span) in methods.iter().filter_map(bound_attr) {
+            if !generics.iter().any(|g| g.name == param) {
+                let names = generics.iter().map(|g| format!("`{}`", g.name)).collect::<Vec<_>>();
+
+                Diagnostic::error(format!("`{}` is not a generic parameter of `{}`", param, owner))
+                    .with_code(&codes::E0058)
+                    .with_span(&span)
+                    .with_note(format!("the generic parameters of `{}` are {}", owner, names.join(", ")))
+                    .abort();
+            }
+        }
+    }
+}
+
+/// How static method `name` of `owner` is called: by the name of a class or
+/// enum, or else through a generic parameter that `owner` is given for.
+fn static_call(owner: &Type, name: &str) -> String {
+    match owner {
+        Type::Named(..) => format!("`{}::{}(..)`", owner, name),
+        _ => format!("`T::{}(..)` where `T` is `{}`", name, owner),
+    }
+}
+
+/// Signature of method `name` as it would be declared, e.g. `fun len(): unt`.
+fn sig_to_string(name: &str, sig: &FuncSig) -> String {
+    let params = sig.params.iter().map(Type::to_string).collect::<Vec<_>>().join(", ");
+
+    match &sig.ret_type {
+        Some(ret_type) => format!("fun {}({}): {}", name, params, ret_type),
+        None => format!("fun {}({})", name, params),
+    }
+}
+
+/// Reports every function of the entry file that cannot be reached from
+/// `main` through calls. Imported modules may define more than one program
+/// needs, so their functions are left alone.
+fn check_unused_funcs(funcs: &[FuncDef], root_file: FileId, callees: &HashMap<String, HashSet<String>>, linter: &Linter) {
+    let mut used = HashSet::from(["main"]);
+    let mut pending = vec!["main"];
+
+    while let Some(name) = pending.pop() {
+        for callee in callees.get(name).into_iter().flatten() {
+            if used.insert(callee) {
+                pending.push(callee);
+            }
+        }
+    }
+
+    for func in funcs.iter().filter(|f| f.span.file == root_file) {
+        if !used.contains(func.name.as_str()) {
+            linter.report(
+                Lint::UnusedFunctions,
+                Diagnostic::warning(format!("Function `{}` is never used", func.name)).with_span(&func.span)
+            );
+        }
+    }
+}
+
+/// Checks that every constant is initialized with a constant expression and
+/// that no constant depends on itself, so each one can be substituted where
+/// it is used.
+fn check_const_exprs(program: &Program) {
+    let arena = &program.expr_arena;
+    let mut refs: HashMap<&str, Vec<(&str, &Span)>> = HashMap::new();
+
+    for constant in &program.consts {
+        let mut pending = vec![constant.expr_id];
+        let entry = refs.entry(constant.name.as_str()).or_default();
+
+        while let Some(id) = pending.pop() {
+            let node = arena.get(id);
+
+            match &node.expr {
+                Expr::Lit(_) | Expr::Unary(..) | Expr::Binary(..) | Expr::Cast(..) => {}
+                Expr::Const(name) => entry.push((name.as_str(), &node.span)),
+                _ => {
+                    Diagnostic::error(format!("Constant `{}` must be initialized with a constant expression", constant.name))
+                        .with_code(&codes::E0036)
+                        .with_span(&node.span)
+                        .with_note("only literals, other constants, casts and operators are allowed".to_string())
+                        .abort();
+                }
+            }
+
+            pending.extend(node.expr.children());
+        }
+    }
+
+    // depth-first search; a constant met again while still on the stack
+    // closes a cycle
+    fn visit<'a>(name: &'a str, refs: &HashMap<&'a str, Vec<(&'a str, &'a Span)>>, stack: &mut Vec<&'a str>, done: &mut HashSet<&'a str>) {
+        if done.contains(name) {
+            return;
+        }
+
+        stack.push(name);
+
+        for &(next, span) in refs.get(name).into_iter().flatten() {
+            if let Some(start) = stack.iter().position(|n| *n == next) {
+                let cycle = stack[start..].iter().chain([&next]).map(|n| format!("`{}`", n)).collect::<Vec<_>>();
+
+                Diagnostic::error(format!("Constant `{}` depends on itself: {}", next, cycle.join(" -> ")))
+                    .with_code(&codes::E0036)
+                    .with_span(span)
+                    .abort();
+            }
+
+            visit(next, refs, stack, done);
+        }
+
+        stack.pop();
+        done.insert(name);
+    }
+
+    let mut done = HashSet::new();
+
+    for constant in &program.consts {
+        visit(&constant.name, &refs, &mut Vec::new(), &mut done);
+    }
+}
+
+fn check_entry_point(main: &FuncDef) {
+    if let Some(ret_type) = &main.ret_type && *ret_type != Type::Int {
+        Diagnostic::error(format!("Function `main` must return `int` or nothing, but returns `{}`", ret_type))
+            .with_code(&codes::E0011)
+            .with_span(&main.span)
+            .abort();
+    }
+
+    let args_type = Type::Array(Box::new(Type::Str));
+
+    match main.params.as_slice() {
+        [] => {}
+        [param] if param._type == args_type => {}
+        params => Diagnostic::error("Function `main` must take no parameters or a single `str[]` parameter".to_string())
+            .with_code(&codes::E0011)
+            .with_span(params.first().map_or(&main.span, |p| &p.span))
+            .abort(),
+    }
+}
+
+/// A parameter or local variable, tracked so unused ones can be reported.
+struct Binding {
+    kind: &'static str,
+    name: String,
+    _type: Type,
+    span: Span,
+    used: Cell<bool>,
+}
+
+struct Analyzer<'a> {
+    arena: &'a ExprArena,
+    linter: &'a Linter<'a>,
+    bindings: RefCell<Vec<Binding>>,
+    declared: RefCell<HashMap<String, usize>>,
+    funcs: HashMap<String, FuncSig>,
+    classes: HashMap<String, ClassSig>,
+    enums: HashMap<String, EnumSig>,
+    interfaces: HashMap<String, InterfaceSig>,
+    methods: HashMap<String, HashMap<String, MethodSig>>,
+    consts: HashMap<String, Type>,
+    templates: &'a Templates,
+    // (type, interface) pairs of every implementation, for checking bounds
+    implementations: HashSet<(String, String)>,
+    callees: RefCell<HashSet<String>>,
+    ret_type: Option<Type>,
+    // the type whose constructor or method is analyzed, which may use its
+    // private fields and methods
+    class: Option<String>,
+}
+
+impl<'a> Analyzer<'a> {
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        expr_arena: &'a ExprArena,
+        linter: &'a Linter<'a>,
+        funcs: HashMap<String, FuncSig>,
+        classes: HashMap<String, ClassSig>,
+        enums: HashMap<String, EnumSig>,
+        interfaces: HashMap<String, InterfaceSig>,
+        methods: HashMap<String, HashMap<String, MethodSig>>,
+        consts: HashMap<String, Type>,
+        templates: &'a Templates,
+        implementations: HashSet<(String, String)>,
+    ) -> Analyzer<'a> {
+        Analyzer {
+            arena: expr_arena,
+            linter,
+            bindings: RefCell::new(Vec::new()),
+            declared: RefCell::new(HashMap::new()),
+            funcs,
+            classes,
+            enums,
+            interfaces,
+            methods,
+            consts,
+            templates,
+            implementations,
+            callees: RefCell::new(HashSet::new()),
+            ret_type: None,
+            class: None,
+        }
+    }
+
+    /// Reports a type that names nothing the program defines.
+    fn check_type(&self, _type: &Type, span: &Span) {
+        match _type {
+            Type::Array(elem) => self.check_type(elem, span),
+
+            Type::Named(name, args) if self.template_generics(name).is_some() => {
+                for arg in args {
+                    self.check_type(arg, span);
+                }
+
+                self.check_type_args(&format!("Type `{}`", name), self.template_generics(name).unwrap(), args, span);
+            }
+
+            Type::Named(name, args) if self.is_defined_type(name) && !args.is_empty() => {
+                self.check_type_args(&format!("Type `{}`", name), &[], args, span);
+            }
+
+            Type::Named(name, _) if self.is_defined_type(name) => {}
+
+            Type::Named(name, _) if self.interfaces.contains_key(name) => not_supported("Interface types are", span),
+
+            Type::Named(name, _) => {
+                let known = Type::PRIMITIVES.into_iter()
+                    .chain(self.classes.keys().map(String::as_str))
+                    .chain(self.enums.keys().map(String::as_str))
+                    .chain(self.templates.classes.iter().map(|c| c.name.as_str()))
+                    .chain(self.templates.enums.iter().map(|e| e.name.as_str()));
+
+                let mut diag = Diagnostic::error(format!("Unknown type: `{}`", name))
+                    .with_code(&codes::E0006)
+                    .with_span(span);
+
+                // `span` covers the whole item, not the type, so there is
+                // nothing the suggestion could replace
+                if let Some(known) = closest(name, known) {
+                    diag = diag.with_note(format!("did you mean `{}`?", known));
+                }
+
+                diag.abort();
+            }
+
+            Type::Ptr(_) => not_supported("Pointer types are", span),
+            Type::Fallible(_) => not_supported("Fallible types are", span),
+            Type::Lambda(..) => not_supported("Lambda types are", span),
+
+            _ => {}
+        }
+    }
+
+    /// The generic parameters of `name`, if it is a generic class or enum.
+    fn template_generics(&self, name: &str) -> Option<&'a [GenericParam]> {
+        let templates = self.templates;
+
+        templates.class(name).map(|c| c.generics.as_slice())
+            .or_else(|| templates._enum(name).map(|e| e.generics.as_slice()))
+    }
+
+    /// Checks that every bound of a generic parameter or of a conditional
+    /// method names an interface.
+    fn check_bounds(&self) {
+        let templates = self.templates;
+        let generics = templates.funcs.iter().flat_map(|f| &f.generics)
+            .chain(templates.classes.iter().flat_map(|c| c.generics.iter().chain(c.methods.iter().flat_map(|m| &m.generics))))
+            .chain(templates.enums.iter().flat_map(|e| e.generics.iter().chain(e.methods.iter().flat_map(|m| &m.generics))))
+            .chain(templates.methods.iter().flat_map(|(_, m)| &m.generics));
+
+        for generic in generics {
+            for bound in &generic.bounds {
+                self.interface_sig(bound, &generic.span);
+            }
+        }
+
+        let conditional = templates.classes.iter().flat_map(|c| &c.methods)
+            .chain(templates.enums.iter().flat_map(|e| &e.methods))
+            .flat_map(|m| &m.attrs);
+
+        for attr in conditional {
+            if let AttrKind::Bound(_, bounds) = &attr.kind {
+                for bound in bounds {
+                    self.interface_sig(bound, &attr.span);
+                }
+            }
+        }
+    }
+
+    /// Checks `args`, the type arguments given to `what`, against its
+    /// `generics`: one for each parameter, implementing its bounds. The
+    /// resolver instantiates every use whose arguments fit, so this only
+    /// reports why one did not.
+    fn check_type_args(&self, what: &str, generics: &[GenericParam], args: &[Type], span: &Span) {
+        if generics.len() != args.len() {
+            let mut diag = Diagnostic::error(format!(
+                "{} takes {} type argument(s), but {} were supplied", what, generics.len(), args.len()
+            ))
+                .with_code(&codes::E0056)
+                .with_span(span);
+
+            if !generics.is_empty() {
+                let names = generics.iter().map(|g| g.name.as_str()).collect::<Vec<_>>();
+                diag = diag.with_label(&generics[0].span, format!("expects `<{}>`", names.join(", ")));
+            }
+
+            diag.abort();
+        }
+
+        for (generic, arg) in generics.iter().zip(args) {
+            let unmet = generic.bounds.iter()
+                .find(|bound| !self.implementations.contains(&(arg.to_string(), bound.to_string())));
+
+            if let Some(bound) = unmet {
+                let mut diag = Diagnostic::error(format!("Type `{}` does not implement interface `{}`", arg, bound))
+                    .with_code(&codes::E0055)
+                    .with_span(span)
+                    .with_label(&generic.span, format!("`{}` must implement `{}`", generic.name, bound));
+
+                // an instance that lacks a conditional method of the interface
+                if let Some(cause) = self.unmet_method_of(arg, bound) {
+                    diag = diag.with_note(format!(
+                        "`{}` implements `{}` only where `{}` implements `{}`, which `{}` does not",
+                        arg, bound, cause.param, cause.bound, cause.arg
+                    ));
+                }
+
+                diag.abort();
+            }
+        }
+    }
+
+    /// The unmet bound that left a method of `interface` out of `owner`.
+    fn unmet_method_of(&self, owner: &Type, interface: &Type) -> Option<&'a UnmetBound> {
+        let sig = self.interfaces.get(&interface.to_string())?;
+        let templates = self.templates;
+
+        templates.unmet.iter().find(|u| u.owner == *owner && sig.method(&u.method).is_some())
+    }
+
+    /// Reports a use of generic `what` whose type arguments were neither
+    /// given nor inferred.
+    fn cannot_infer(&self, what: &str, generics: &[GenericParam], span: &Span, help: String) -> ! {
+        let names = generics.iter().map(|g| format!("`{}`", g.name)).collect::<Vec<_>>();
+
+        Diagnostic::error(format!("{} needs type arguments that cannot be inferred", what))
+            .with_code(&codes::E0057)
+            .with_span(span)
+            .with_label(&generics[0].span, format!("{} declared here", names.join(", ")))
+            .with_note(help)
+            .abort()
+    }
+
+    /// Whether `name` is a class or an enum of the program.
+    fn is_defined_type(&self, name: &str) -> bool {
+        self.classes.contains_key(name) || self.enums.contains_key(name)
+    }
+
+    /// Checks the field list of `class`: every name once, every type known.
+    fn check_fields(&self, class: &ClassDef) {
+        for (i, field) in class.fields.iter().enumerate() {
+            self.check_type(&field._type, &field.span);
+
+            if let Some(first) = class.fields[..i].iter().find(|f| f.name == field.name) {
+                Diagnostic::error(format!("Field `{}` is already declared in class `{}`", field.name, class.name))
+                    .with_code(&codes::E0041)
+                    .with_span(&field.span)
+                    .with_label(&first.span, format!("`{}` is first declared here", field.name))
+                    .abort();
+            }
+        }
+    }
+
+    /// Checks the variants of `_enum`: every name once, every field type
+    /// known.
+    fn check_variants(&self, _enum: &EnumDef) {
+        for (i, variant) in _enum.variants.iter().enumerate() {
+            for field in &variant.fields {
+                self.check_type(field, &variant.span);
+            }
+
+            if let Some(first) = _enum.variants[..i].iter().find(|v| v.name == variant.name) {
+                Diagnostic::error(format!("Variant `{}` is already declared in enum `{}`", variant.name, _enum.name))
+                    .with_code(&codes::E0048)
+                    .with_span(&variant.span)
+                    .with_label(&first.span, format!("`{}` is first declared here", variant.name))
+                    .abort();
+            }
+        }
+    }
+
+    /// Checks that no type has two methods of the same name.
+    fn check_methods(&self, methods: &[(Type, &FuncDef)]) {
+        for (i, (owner, method)) in methods.iter().enumerate() {
+            if let Some((_, first)) = methods[..i].iter().find(|(o, m)| o == owner && m.name == method.name) {
+                Diagnostic::error(format!("Method `{}` is already defined for type `{}`", method.name, owner))
+                    .with_code(&codes::E0045)
+                    .with_span(&method.span)
+                    .with_label(&first.span, format!("`{}` is first defined here", method.name))
+                    .abort();
+            }
+        }
+    }
+
+    /// Checks that no method of `interface` is declared twice.
+    fn check_interface(&self, interface: &InterfaceDef) {
+        for (i, method) in interface.methods.iter().enumerate() {
+            if let Some(first) = interface.methods[..i].iter().find(|m| m.name == method.name) {
+                Diagnostic::error(format!("Method `{}` is already declared in interface `{}`", method.name, interface.name))
+                    .with_code(&codes::E0045)
+                    .with_span(&method.span)
+                    .with_label(&first.span, format!("`{}` is first declared here", method.name))
+                    .abort();
+            }
+        }
+    }
+
+    /// Checks that `methods` of `owner` implement each of `interfaces`:
+    /// every method an interface declares is there, marked `override`, with
+    /// the declared signature once the interface's name is read as `owner`.
+    /// In `impl Interface { ... }`, which is `exclusive`, the methods must
+    /// all belong to the interface.
+    fn check_conformance(&self, owner: &Type, interfaces: &[Type], methods: &[FuncDef], span: &Span, exclusive: bool) {
+        let interfaces = interfaces.iter()
+            .map(|interface| (interface.to_string(), self.interface_sig(interface, span)))
+            .collect::<Vec<_>>();
+
+        for method in methods {
+            let declared = interfaces.iter()
+                .find_map(|(name, sig)| sig.method(&method.name).map(|decl| (name, decl)));
+
+            let Some((interface, decl)) = declared else {
+                if exclusive {
+                    let (interface, sig) = &interfaces[0];
+                    let mut diag = Diagnostic::error(format!("Method `{}` is not a member of interface `{}`", method.name, interface))
+                        .with_code(&codes::E0053)
+                        .with_span(&method.span);
+
+                    if let Some(closest) = closest(&method.name, sig.methods.iter().map(|m| m.name.as_str())) {
+                        diag = diag.with_note(format!("a method with a similar name exists: `{}`", closest));
+                    }
+
+                    diag.abort();
+                }
+
+                // implementing an interface that a conditional method left
+                // out of the instance
+                if method.is_override && !self.templates.unmet.iter().any(|u| u.owner == *owner) {
+                    let note = match interfaces.is_empty() {
+                        true => format!("`{}` implements no interface here", owner),
+                        false => {
+                            let names = interfaces.iter().map(|(name, _)| format!("`{}`", name)).collect::<Vec<_>>();
+                            format!("`{}` implements {} here", owner, names.join(", "))
+                        }
+                    };
+
+                    Diagnostic::error(format!("Method `{}` is marked `override`, but implements no interface method", method.name))
+                        .with_code(&codes::E0054)
+                        .with_span(&method.span)
+                        .with_note(note)
+                        .abort();
+                }
+
+                continue;
+            };
+
+            if !method.is_override {
+                Diagnostic::error(format!("Method `{}` implements interface `{}` and must be marked `override`", method.name, interface))
+                    .with_code(&codes::E0054)
+                    .with_span(&method.span)
+                    .with_label(&decl.span, format!("`{}` is declared here", method.name))
+                    .abort();
+            }
+
+            if let (None, Some(receiver)) = (&decl.receiver, &method.receiver) {
+                Diagnostic::error(format!("Method `{}` of `{}` cannot take `self`", method.name, owner))
+                    .with_code(&codes::E0054)
+                    .with_span(receiver)
+                    .with_label(&decl.span, format!("`{}` is declared without `self` here", method.name))
+                    .with_note(format!("it is called through its type, e.g. {}", static_call(owner, &method.name)))
+                    .abort();
+            }
+
+            let subst = HashMap::from([(interface.clone(), owner.clone())]);
+            let expected = decl.sig();
+            let expected = FuncSig {
+                params: expected.params.iter().map(|p| p.substitute(&subst)).collect(),
+                ret_type: expected.ret_type.map(|r| r.substitute(&subst)),
+            };
+            let found = method.sig();
+
+            if expected.params != found.params || expected.ret_type != found.ret_type {
+                Diagnostic::error(format!("Method `{}` of `{}` does not match its declaration in interface `{}`", method.name, owner, interface))
+                    .with_code(&codes::E0054)
+                    .with_span(&method.span)
+                    .with_label(&decl.span, format!("`{}` is declared here", method.name))
+                    .with_note(format!(
+                        "expected `{}`, found `{}`",
+                        sig_to_string(&method.name, &expected), sig_to_string(&method.name, &found)
+                    ))
+                    .abort();
+            }
+        }
+
+        for (interface, sig) in &interfaces {
+            if let Some(decl) = sig.methods.iter().find(|decl| !methods.iter().any(|m| m.name == decl.name)) {
+                Diagnostic::error(format!("Type `{}` does not implement method `{}` of interface `{}`", owner, decl.name, interface))
+                    .with_code(&codes::E0053)
+                    .with_span(span)
+                    .with_label(&decl.span, format!("`{}` is declared here", decl.name))
+                    .abort();
+            }
+        }
+    }
+
+    /// The interface that `_type`, written after `:` or `impl`, names.
+    fn interface_sig(&self, _type: &Type, span: &Span) -> &InterfaceSig {
+        match _type {
+            Type::Named(name, args) if self.interfaces.contains_key(name) => {
+                if !args.is_empty() {
+                    not_supported("Type arguments are", span);
+                }
+
+                &self.interfaces[name]
+            }
+
+            _ => {
+                let name = _type.to_string();
+
+                Diagnostic::error(format!("`{}` is not an interface", name))
+                    .with_code(&codes::E0053)
+                    .with_span(span)
+                    .with_suggestion(span, closest(&name, self.interfaces.keys().map(String::as_str)))
+                    .abort()
+            }
+        }
+    }
+
+    /// Analyzes `func` and returns the names of the functions it calls.
+    fn analyze_func(&mut self, func: &mut FuncDef) -> HashSet<String> {
+        let name = func.name.clone();
+        self.analyze_fn(func, &name, None)
+    }
+
+    /// Analyzes method `method` of `owner`, in which `self` is the receiver
+    /// unless the method is static, and returns the names of the functions
+    /// it calls.
+    fn analyze_method(&mut self, owner: &Type, method: &mut FuncDef) -> HashSet<String> {
+        let name = method_name(&owner.to_string(), &method.name);
+        let receiver = (!method.is_static).then_some(owner);
+
+        self.class = Some(owner.to_string());
+        let called = self.analyze_fn(method, &name, receiver);
+        self.class = None;
+
+        called
+    }
+
+    fn analyze_fn(&mut self, func: &mut FuncDef, name: &str, receiver: Option<&Type>) -> HashSet<String> {
+        self.bindings.borrow_mut().clear();
+        self.declared.borrow_mut().clear();
+        self.ret_type = func.ret_type.clone();
+
+        if let Some(ret_type) = &func.ret_type {
+            self.check_type(ret_type, &func.span);
+        }
+
+        if let Some(owner) = receiver {
+            self.declare("Parameter", "self", owner, func.receiver.as_ref().unwrap_or(&func.span));
+            self.bindings.borrow()[0].used.set(true);
+        }
+
+        self.declare_params(&func.params, name);
+
+        let reachable_len = self.analyze_block(&func.body, name);
+
+        if let Some(end) = reachable_len {
+            func.body.mut_stmts().truncate(end);
+        } else if let Some(ret_type) = &self.ret_type {
+            let kind = if receiver.is_some() { "Method" } else { "Function" };
+            generate_error!(&func.span, E0013, "{} `{}` must return a value of type `{}`", kind, name, ret_type);
+        }
+
+        self.report_unused_bindings();
+        self.callees.take()
+    }
+
+    /// Analyzes constructor `ctor` of `class`, in which `self` is the object
+    /// being built, and returns the names of the functions it calls.
+    fn analyze_ctor(&mut self, class: &str, ctor: &mut CtorDef) -> HashSet<String> {
+        self.bindings.borrow_mut().clear();
+        self.declared.borrow_mut().clear();
+        self.ret_type = None;
+        self.class = Some(class.to_string());
+
+        self.declare("Parameter", "self", &Type::Named(class.to_string(), Vec::new()), &ctor.span);
+        self.bindings.borrow()[0].used.set(true);
+        self.declare_params(&ctor.params, class);
+
+        if let Some(end) = self.analyze_block(&ctor.body, class) {
+            ctor.body.mut_stmts().truncate(end);
+        }
+
+        self.report_unused_bindings();
+        self.class = None;
+        self.callees.take()
+    }
+
+    fn declare_params(&mut self, params: &[Param], fn_name: &str) {
+        for param in params {
+            self.check_type(&param._type, &param.span);
+
+            if let Some(&first) = self.declared.borrow().get(&param.name) {
+                Diagnostic::error(format!("Parameter `{}` is already declared in function `{}`", param.name, fn_name))
+                    .with_code(&codes::E0012)
+                    .with_span(&param.span)
+                    .with_label(&self.bindings.borrow()[first].span, format!("`{}` is first declared here", param.name))
+                    .abort();
+            }
+
+            self.declare("Parameter", &param.name, &param._type, &param.span);
+        }
+    }
+
+    fn report_unused_bindings(&self) {
+        for binding in self.bindings.borrow().iter() {
+            if !binding.used.get() && !binding.name.starts_with('_') {
+                self.linter.report(
+                    Lint::UnusedVariables,
+                    Diagnostic::warning(format!("{} `{}` is never used", binding.kind, binding.name))
+                        .with_span(&binding.span)
+                );
+            }
+        }
+    }
+
+    fn declare(&self, kind: &'static str, name: &str, _type: &Type, span: &Span) {
+        let mut bindings = self.bindings.borrow_mut();
+
+        self.declared.borrow_mut().insert(name.to_string(), bindings.len());
+        bindings.push(Binding {
+            kind,
+            name: name.to_string(),
+            _type: _type.clone(),
+            span: span.clone(),
+            used: Cell::new(false),
+        });
+    }
+
+    /// Analyzes every statement of `block` and returns the number of
+    /// statements up to and including the first diverging one, or `None`
+    /// if control can reach the end of the block. Statements past that point
+    /// are reported as unreachable and must not be emitted.
+    fn analyze_block(&mut self, block: &Block, fn_name: &str) -> Option<usize> {
+        let mut diverged: Option<(usize, &'static str)> = None;
+        let mut reported = false;
+
+        for (i, node) in block.stmts().iter().enumerate() {
+            if let Some((end, after)) = diverged && !reported {
+                self.linter.report(
+                    Lint::UnreachableCode,
+                    Diagnostic::warning(format!("Code after `{}` is never executed", after))
+                        .with_span(&node.span)
+                        .with_label(&block.stmts()[end - 1].span, format!("any code following this `{}` is unreachable", after))
+                );
+                reported = true;
+            }
+
+            self.analyze_stmt(node, fn_name);
+
+            if diverged.is_none() && let Some(kind) = self.diverges(&node.stmt) {
+                diverged = Some((i + 1, kind));
+            }
+        }
+
+        diverged.map(|(end, _)| end)
+    }
+
+    /// Returns the keyword that makes `stmt` diverge, if it never completes.
+    /// A `switch` diverges when every one of its arms does.
+    fn diverges(&self, stmt: &Stmt) -> Option<&'static str> {
+        match stmt {
+            Stmt::Unreachable(_) => Some("unreachable"),
+            Stmt::Return(_) => Some("return"),
+            Stmt::Expr(expr_id) => self.diverging_call(*expr_id),
+            Stmt::Switch(_, cases) => {
+                let every_arm = !cases.is_empty() && cases.iter()
+                    .all(|case| case.body.stmts().iter().any(|node| self.diverges(&node.stmt).is_some()));
+
+                every_arm.then_some("switch")
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the builtin that expression `id` calls if the call never
+    /// returns: `panic(..)` or `assert(false, ..)`.
+    fn diverging_call(&self, id: ExprId) -> Option<&'static str> {
+        match &self.arena.get(id).expr {
+            Expr::Call(name, _, args) => match Builtin::from_name(name) {
+                Some(Builtin::Panic) => Some("panic"),
+                Some(Builtin::Assert)
+                    if matches!(self.arena.get(args[0]).expr, Expr::Lit(Lit::Bool(false)))
+                => Some("assert"),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn analyze_stmt(&mut self, node: &StmtNode, fn_name: &str) {
+        match &node.stmt {
+            Stmt::VarDecl(v) => {
+                self.check_type(&v._type, &node.span);
+
+                let expr_type = self.infer_type(v.expr_id);
+
+                if expr_type != v._type {
+                    generate_error!(
+                        &self.arena.get(v.expr_id).span, E0014, "Cannot assign {} value to variable `{}` of type `{}`",
+                        expr_type, v.name, v._type
+                    );
+                }
+
+                if let Some(&shadowed) = self.declared.borrow().get(&v.name) {
+                    let shadowed = &self.bindings.borrow()[shadowed];
+
+                    self.linter.report(
+                        Lint::Shadowing,
+                        Diagnostic::warning(format!("Variable `{}` shadows an earlier {}", v.name, shadowed.kind.to_lowercase()))
+                            .with_span(&node.span)
+                            .with_label(&shadowed.span, format!("`{}` is first declared here", v.name))
+                    );
+                }
+
+                self.declare("Variable", &v.name, &v._type, &node.span);
+            }
+            Stmt::Println(expr_id) => {
+                let expr_type = self.infer_type(*expr_id);
+
+                if let Type::Array(_) | Type::Named(..) | Type::Void = expr_type {
+                    generate_error!(&self.arena.get(*expr_id).span, E0015, "Cannot print value of type `{}`", expr_type);
+                }
+            }
+            Stmt::Unreachable(Some(expr_id)) => {
+                let expr_type = self.infer_type(*expr_id);
+
+                if expr_type != Type::Str {
+                    generate_error!(
+                        &self.arena.get(*expr_id).span, E0016,
+                        "Message of `unreachable` must be of type `str`, but got `{}`", expr_type
+                    );
+                }
+            }
+            Stmt::Unreachable(None) => {}
+            Stmt::Return(expr_id) => {
+                self.analyze_return(*expr_id, fn_name, &node.span);
+            }
+            Stmt::Expr(expr_id) => {
+                self.infer_type(*expr_id);
+            }
+            Stmt::Assign(target, op, value) => {
+                self.analyze_assign(*target, op.as_ref(), *value, &node.span);
+            }
+            // the names bound by a pattern are in scope in its block only
+            Stmt::Switch(scrutinee, cases) => {
+                let scrutinee_ty = self.infer_type(*scrutinee);
+                let patterns = cases.iter().map(|case| &case.pattern).collect::<Vec<_>>();
+
+                self.check_switch(&scrutinee_ty, &patterns, &node.span);
+
+                for case in cases {
+                    let outer = self.declare_bindings(&case.pattern);
+                    self.analyze_block(&case.body, fn_name);
+                    *self.declared.borrow_mut() = outer;
+                }
+            }
+            Stmt::If(..) => not_supported("`if` is", &node.span),
+            Stmt::While(..) => not_supported("`while` is", &node.span),
+            Stmt::For(..) => not_supported("`for` is", &node.span),
+            Stmt::Llvm(..) => not_supported("`llvm` blocks are", &node.span),
+        }
     }
-}
 
-struct Analyzer<'a> {
-    arena: &'a ExprArena,
-    declared: HashMap<String, Type>,
-}
+    /// Checks that every pattern of a switch on a value of `_type` can match
+    /// it, reports the arms that cannot be reached and rejects the switch
+    /// unless every value is matched.
+    fn check_switch(&self, _type: &Type, patterns: &[&Pattern], span: &Span) {
+        for pattern in patterns {
+            self.check_pattern(pattern, _type);
 
-impl<'a> Analyzer<'a> {
-    fn new(expr_arena: &'a ExprArena) -> Analyzer<'a> {
-        Analyzer {
-            arena: expr_arena,
-            declared: HashMap::new(),
+            let mut bound = HashSet::new();
+
+            for (name, _, span) in pattern.bindings() {
+                if !bound.insert(name) {
+                    Diagnostic::error(format!("Name `{}` is bound more than once in the same pattern", name))
+                        .with_code(&codes::E0050)
+                        .with_span(span)
+                        .abort();
+                }
+            }
+        }
+
+        let coverage = patterns::check_arms(patterns, _type, &self.enums);
+
+        for arm in coverage.unreachable {
+            self.linter.report(
+                Lint::UnreachablePatterns,
+                Diagnostic::warning(format!("Pattern `{}` is unreachable", patterns[arm]))
+                    .with_span(patterns[arm].span())
+                    .with_note("the arms above it already match every value it matches".to_string())
+            );
+        }
+
+        if let Some(missing) = coverage.missing {
+            Diagnostic::error(format!("Non-exhaustive `switch`: `{}` is not matched", missing))
+                .with_code(&codes::E0052)
+                .with_span(span)
+                .with_note("add an arm for it, or end with `_` to match every remaining value".to_string())
+                .abort();
         }
     }
-    fn analyze_func(&mut self, func: &FuncDef) {
-        self.declared.clear();
 
-        for stmt in func.body.stmts() {
-            match stmt {
-                Stmt::VarDecl(v) => {
-                    if self.declared.contains_key(&v.name) {
-                        generate_plain_error!("Variable `{}` is already declared", v.name);
+    /// Checks that `pattern` can match a value of `_type`. The resolver has
+    /// already turned every pattern that can into its resolved form.
+    fn check_pattern(&self, pattern: &Pattern, _type: &Type) {
+        let mismatch = |what: String| -> ! {
+            Diagnostic::error(format!("Pattern `{}` cannot match a value of type `{}`", pattern, _type))
+                .with_code(&codes::E0050)
+                .with_span(pattern.span())
+                .with_note(what)
+                .abort()
+        };
+
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(..) => {}
+
+            Pattern::Lit(lit, _) => {
+                let lit_ty = match lit {
+                    Lit::Unt(_) => Type::Unt,
+                    Lit::Int(_) => Type::Int,
+                    Lit::Float(_) => Type::Float,
+                    Lit::Bool(_) => Type::Bool,
+                    Lit::Char(_) => Type::Char,
+                    Lit::Str(_) => Type::Str,
+                };
+
+                if lit_ty != *_type {
+                    mismatch(format!("the pattern is of type `{}`", lit_ty));
+                }
+            }
+
+            Pattern::Name(path, _) | Pattern::Variant(path, _, _) => {
+                let enum_sig = match _type {
+                    Type::Named(name, _) => self.enums.get(name).map(|sig| (name, sig)),
+                    _ => None,
+                };
+
+                let (_enum, variant) = match path.rsplit_once("::") {
+                    Some((_enum, variant)) => (Some(_enum), variant),
+                    None => (None, path.as_str()),
+                };
+
+                let Some((name, sig)) = enum_sig.filter(|(name, _)| _enum.is_none_or(|e| e == *name)) else {
+                    match _enum {
+                        Some(_enum) => mismatch(format!("the pattern is a variant of `{}`", _enum)),
+                        None => mismatch("only values of enums have variants".to_string()),
                     }
+                };
 
-                    let expr_type = self.infer_type(v.expr_id);
+                let Some((_, def)) = sig.variant(variant) else {
+                    let mut diag = Diagnostic::error(format!("Type `{}` has no variant `{}`", name, variant))
+                        .with_code(&codes::E0049)
+                        .with_span(pattern.span());
 
-                    if expr_type != v._type {
-                        generate_plain_error!(
-                            "Cannot assign {} value to variable `{}` of type `{}`",
-                            expr_type, v.name, v._type
-                        );
+                    if let Some(closest) = closest(variant, sig.variants.iter().map(|v| v.name.as_str())) {
+                        diag = diag.with_note(format!("a variant with a similar name exists: `{}`", closest));
                     }
 
-                    self.declared.insert(v.name.clone(), v._type.clone());
+                    diag.abort()
+                };
+
+                let fields = match pattern {
+                    Pattern::Variant(_, fields, _) => fields.as_slice(),
+                    _ => &[],
+                };
+
+                if fields.len() != def.fields.len() {
+                    Diagnostic::error(format!(
+                        "Variant `{}::{}` has {} field(s), but the pattern has {}",
+                        name, variant, def.fields.len(), fields.len(),
+                    ))
+                        .with_code(&codes::E0050)
+                        .with_span(pattern.span())
+                        .abort();
                 }
-                Stmt::Println(expr_id) => {
-                    self.infer_type(*expr_id);
+
+                for (field, field_ty) in fields.iter().zip(&def.fields) {
+                    self.check_pattern(field, field_ty);
+                }
+            }
+        }
+    }
+
+    /// Declares the names bound by `pattern` and returns the variables that
+    /// were in scope before, to restore once the arm is analyzed.
+    fn declare_bindings(&self, pattern: &Pattern) -> HashMap<String, usize> {
+        let outer = self.declared.borrow().clone();
+
+        for (name, _type, span) in pattern.bindings() {
+            self.declare("Binding", name, _type, span);
+        }
+
+        outer
+    }
+
+    fn analyze_return(&self, expr_id: Option<ExprId>, fn_name: &str, span: &Span) {
+        let expr_type = expr_id.map(|id| self.infer_type(id));
+
+        match (&self.ret_type, expr_type) {
+            (None, None) => {}
+
+            (Some(ret_type), Some(expr_type)) => {
+                if *ret_type != expr_type {
+                    generate_error!(
+                        span, E0017, "Cannot return {} value from function `{}` of type `{}`",
+                        expr_type, fn_name, ret_type
+                    );
                 }
-                _ => {}
             }
+
+            (Some(ret_type), None) => {
+                generate_error!(span, E0013, "Function `{}` must return a value of type `{}`", fn_name, ret_type);
+            }
+
+            (None, Some(_)) => {
+                generate_error!(span, E0018, "Function `{}` has no return type, but returns a value", fn_name);
+            }
+        }
+    }
+
+    /// Checks `target = value`, or `target op= value` when `op` is given.
+    /// Variables, fields of classes and elements of arrays can be assigned.
+    fn analyze_assign(&self, target: ExprId, op: Option<&BinaryOp>, value: ExprId, span: &Span) {
+        let target_node = self.arena.get(target);
+        let target_ty = self.infer_type(target);
+
+        let place = match &target_node.expr {
+            Expr::Var(name) => format!("variable `{}`", name),
+
+            Expr::Field(expr, name) => match self.infer_type(*expr) {
+                Type::Named(..) => format!("field `{}`", name),
+                other => self.report_invalid_target(format!("field `{}` of type `{}`", name, other), &target_node.span),
+            },
+
+            Expr::Index(expr, _) => match self.infer_type(*expr) {
+                Type::Array(_) => "array element".to_string(),
+                other => self.report_invalid_target(format!("an element of type `{}`", other), &target_node.span),
+            },
+
+            Expr::Const(name) => self.report_invalid_target(format!("constant `{}`", name), &target_node.span),
+
+            _ => self.report_invalid_target("this expression".to_string(), &target_node.span),
+        };
+
+        let value_ty = match op {
+            Some(op) => self.infer_binary(op, target, value, span),
+            None => self.infer_type(value),
+        };
+
+        if value_ty != target_ty {
+            Diagnostic::error(format!("Cannot assign {} value to {} of type `{}`", value_ty, place, target_ty))
+                .with_code(&codes::E0043)
+                .with_span(&self.arena.get(value).span)
+                .abort();
         }
     }
 
+    fn report_invalid_target(&self, what: String, span: &Span) -> ! {
+        Diagnostic::error(format!("Cannot assign to {}", what))
+            .with_code(&codes::E0044)
+            .with_span(span)
+            .with_note("only variables, fields of classes and array elements can be assigned".to_string())
+            .abort()
+    }
+
     fn infer_type(&self, id: ExprId) -> Type {
         let expr_node = self.arena.get(id);
         let expr = &expr_node.expr;
@@ -66,60 +1313,22 @@ impl<'a> Analyzer<'a> {
             Expr::Lit(Str(_)) => Type::Str,
 
             Expr::Var(name) => {
-                self.declared.get(name).unwrap_or_else(|| {
-                    generate_plain_error!("Variable `{}` is not declared", name)
-                }).clone()
-            }
-
-            Expr::Binary (op, left, right) => {
-                let left_ty = self.infer_type(*left);
-                let right_ty = self.infer_type(*right);
-
-                if left_ty != right_ty {
-                    generate_plain_error!(
-                        "Cannot apply operator `{op}` for types: `{left}` and `{right}`",
-                        op = op, left = left_ty, right = right_ty
-                    )
-                }
-
-                if op.is_comparison() {
-                    Type::Bool
-
-                } else if
-                    op.is_arranging()
-                    && left_ty.is_num_type()
-                    && right_ty.is_num_type()
-                {
-                    Type::Bool
-
-                } else if
-                    op.is_arithmetic()
-                    && left_ty.is_num_type()
-                    && right_ty.is_num_type()
-                {
-                    left_ty
+                let index = *self.declared.borrow().get(name).unwrap_or_else(|| {
+                    Diagnostic::error(format!("Variable `{}` is not declared", name))
+                        .with_code(&codes::E0019)
+                        .with_span(&expr_node.span)
+                        .with_suggestion(&expr_node.span, closest(name, self.declared.borrow().keys().map(String::as_str)))
+                        .abort()
+                });
 
-                } else if
-                    op.is_logical()
-                    && left_ty.is_logical_type()
-                    && right_ty.is_logical_type()
-                {
-                    left_ty
+                let binding = &self.bindings.borrow()[index];
+                binding.used.set(true);
+                binding._type.clone()
+            }
 
-                } else if
-                    op.is_bitwise()
-                    && left_ty.is_integer_type()
-                    && right_ty.is_integer_type()
-                {
-                    left_ty
+            Expr::Const(name) => self.consts[name].clone(),
 
-                } else {
-                    generate_plain_error!(
-                        "Cannot apply operator `{op}` for types: `{left}` and `{right}`",
-                        op = op, left = left_ty, right = right_ty
-                    )
-                }
-            }
+            Expr::Binary (op, left, right) => self.infer_binary(op, *left, *right, &expr_node.span),
 
             Expr::Unary (op, expr) => {
                 let expr_ty = self.infer_type(*expr);
@@ -129,32 +1338,578 @@ impl<'a> Analyzer<'a> {
                 } else if let UnaryOp::Not = op && expr_ty.is_logical_type() {
                     expr_ty
                 } else {
-                    generate_plain_error!(
-                        "Cannot apply unary operator `{op}` for type: `{_type}`",
+                    generate_error!(
+                        &expr_node.span, E0021, "Cannot apply unary operator `{op}` for type: `{_type}`",
                         op = op, _type = expr_ty
                     )
                 }
             }
 
             Expr::Cast (to, expr) => {
+                self.check_type(to, &expr_node.span);
+
                 let expr_ty = self.infer_type(*expr);
 
                 if !expr_ty.is_num_type() && expr_ty != Type::Char {
-                    generate_plain_error!("Cannot cast {} to type `{}`", expr_ty, to);
+                    generate_error!(&expr_node.span, E0022, "Cannot cast {} to type `{}`", expr_ty, to);
                 }
 
                 if (expr_ty == Type::Char && *to == Type::Float) ||
                     (expr_ty == Type::Float && *to == Type::Char)
                 {
-                    generate_plain_error!("Cannot cast {} to type `{}`", expr_ty, to);
+                    generate_error!(&expr_node.span, E0022, "Cannot cast {} to type `{}`", expr_ty, to);
                 }
 
                 if !to.is_num_type() && *to != Type::Char {
-                    generate_plain_error!("Cannot cast anything to type `{}`", to);
+                    generate_error!(&expr_node.span, E0022, "Cannot cast anything to type `{}`", to);
+                }
+
+                if expr_ty == *to {
+                    self.linter.report(
+                        Lint::UnnecessaryCast,
+                        Diagnostic::warning(format!("Unnecessary cast: value is already of type `{}`", to))
+                            .with_span(&expr_node.span)
+                    );
                 }
 
                 to.clone()
             }
+
+            Expr::Field (expr, name) => {
+                let expr_ty = self.infer_type(*expr);
+
+                if (expr_ty == Type::Str || matches!(expr_ty, Type::Array(_))) && name == "len" {
+                    return Type::Unt;
+                }
+
+                let class = match &expr_ty {
+                    Type::Named(class, _) if self.classes.contains_key(class) => class,
+                    _ => generate_error!(&expr_node.span, E0023, "Type `{}` has no field `{}`", expr_ty, name),
+                };
+
+                let sig = &self.classes[class];
+
+                let Some((_, field)) = sig.field(name) else {
+                    let name_span = Span::new(expr_node.span.file, expr_node.span.end - name.chars().count(), expr_node.span.end);
+
+                    Diagnostic::error(format!("Type `{}` has no field `{}`", expr_ty, name))
+                        .with_code(&codes::E0023)
+                        .with_span(&expr_node.span)
+                        .with_suggestion(&name_span, closest(name, sig.fields.iter().map(|f| f.name.as_str())))
+                        .abort()
+                };
+
+                if !field.is_pub && self.class.as_ref() != Some(class) {
+                    Diagnostic::error(format!("Field `{}` of class `{}` is private", name, class))
+                        .with_code(&codes::E0037)
+                        .with_span(&expr_node.span)
+                        .with_label(&field.span, format!("`{}` is declared here without `pub`", name))
+                        .abort();
+                }
+
+                field._type.clone()
+            }
+
+            Expr::Index (expr, index) => {
+                let expr_ty = self.infer_type(*expr);
+                let index_ty = self.infer_type(*index);
+
+                if !index_ty.is_integer_type() {
+                    generate_error!(&self.arena.get(*index).span, E0024, "Index must be of type `unt` or `int`, but got `{}`", index_ty);
+                }
+
+                match expr_ty {
+                    Type::Str => Type::Char,
+                    Type::Array(elem) => *elem,
+                    _ => generate_error!(&expr_node.span, E0025, "Cannot index into value of type `{}`", expr_ty),
+                }
+            }
+
+            Expr::Call (name, type_args, args) if self.templates.func(name).is_some() => {
+                let template = self.templates.func(name).unwrap();
+                let what = format!("Function `{}`", name);
+
+                self.check_arity(&what, template.params.len(), args, &expr_node.span);
+                self.check_generic_use(&what, &template.generics, type_args, &expr_node.span, || {
+                    format!("give them explicitly, e.g. `{}<..>(..)`", name)
+                })
+            }
+
+            Expr::Call (name, type_args, args) if self.template_generics(name).is_some() => {
+                let template = self.templates.class(name).unwrap();
+                let what = format!("Class `{}`", name);
+
+                if type_args.is_empty() && !template.ctors.iter().any(|c| c.params.len() == args.len()) {
+                    let mut diag = Diagnostic::error(format!("No constructor of `{}` takes {} argument(s)", name, args.len()))
+                        .with_code(&codes::E0042)
+                        .with_span(&expr_node.span);
+
+                    for ctor in &template.ctors {
+                        let params = ctor.params.iter().map(|p| p._type.to_string()).collect::<Vec<_>>();
+                        diag = diag.with_note(format!("candidate: `{}({})`", name, params.join(", ")));
+                    }
+
+                    diag.abort();
+                }
+
+                self.check_generic_use(&what, &template.generics, type_args, &expr_node.span, || {
+                    format!("give them explicitly, e.g. `{}<..>(..)`", name)
+                })
+            }
+
+            Expr::Call (name, type_args, _)
+                if !type_args.is_empty() && (self.funcs.contains_key(name) || self.classes.contains_key(name))
+            => {
+                let kind = if self.classes.contains_key(name) { "Class" } else { "Function" };
+                self.check_type_args(&format!("{} `{}`", kind, name), &[], type_args, &expr_node.span);
+                unreachable!()
+            }
+
+            Expr::Call (name, _, args) if self.classes.contains_key(name) => {
+                let arg_types = args.iter().map(|arg| self.infer_type(*arg).to_string()).collect::<Vec<_>>();
+                let mut diag = Diagnostic::error(format!("No constructor of `{}` takes ({})", name, arg_types.join(", ")))
+                    .with_code(&codes::E0042)
+                    .with_span(&expr_node.span);
+
+                for ctor in &self.classes[name].ctors {
+                    let params = ctor.params.iter().map(Type::to_string).collect::<Vec<_>>();
+                    diag = diag.with_note(format!("candidate: `{}({})`", name, params.join(", ")));
+                }
+
+                diag.abort()
+            }
+
+            Expr::Call (name, _, _) if self.enums.contains_key(name) => {
+                let mut diag = Diagnostic::error(format!("Enum `{}` cannot be called", name))
+                    .with_code(&codes::E0042)
+                    .with_span(&expr_node.span);
+
+                if let Some(variant) = self.enums[name].variants.first() {
+                    diag = diag.with_note(format!("construct one of its variants instead, e.g. `{}::{}`", name, variant.name));
+                }
+
+                diag.abort()
+            }
+
+            // the variant fits no expected type, so its fields are all that
+            // could determine the type arguments
+            Expr::Variant (_enum, name, args) if self.template_generics(_enum).is_some() => {
+                let template = self.templates._enum(_enum).unwrap();
+                let what = format!("Enum `{}`", _enum);
+
+                let Some(variant) = template.variants.iter().find(|v| v.name == *name) else {
+                    let mut diag = Diagnostic::error(format!("Type `{}` has no variant `{}`", _enum, name))
+                        .with_code(&codes::E0049)
+                        .with_span(&expr_node.span);
+
+                    if let Some(closest) = closest(name, template.variants.iter().map(|v| v.name.as_str())) {
+                        diag = diag.with_note(format!("a variant with a similar name exists: `{}`", closest));
+                    }
+
+                    diag.abort()
+                };
+
+                self.check_arity(&format!("Variant `{}::{}`", _enum, name), variant.fields.len(), args, &expr_node.span);
+
+                let inferred = template.generics.iter()
+                    .map(|g| {
+                        let field = variant.fields.iter().position(|f| *f == Type::Named(g.name.clone(), Vec::new()))?;
+                        Some(self.infer_type(args[field]))
+                    })
+                    .collect::<Option<Vec<_>>>();
+
+                if let Some(type_args) = inferred {
+                    self.check_type_args(&what, &template.generics, &type_args, &expr_node.span);
+                }
+
+                self.cannot_infer(&what, &template.generics, &expr_node.span, format!(
+                    "use the variant where a `{}<..>` is expected, e.g. as the value of a variable of that type", _enum
+                ))
+            }
+
+            Expr::Variant (_enum, name, args) => {
+                let Some((_, variant)) = self.enums.get(_enum).and_then(|e| e.variant(name)) else {
+                    let variants = self.enums.get(_enum).into_iter().flat_map(|e| e.variants.iter().map(|v| v.name.as_str()));
+
+                    let mut diag = Diagnostic::error(format!("Type `{}` has no variant `{}`", _enum, name))
+                        .with_code(&codes::E0049)
+                        .with_span(&expr_node.span);
+
+                    if let Some(closest) = closest(name, variants) {
+                        diag = diag.with_note(format!("a variant with a similar name exists: `{}`", closest));
+                    }
+
+                    diag.abort()
+                };
+
+                let sig = FuncSig { params: variant.fields.clone(), ret_type: None };
+                let qualified = format!("{}::{}", _enum, name);
+                self.check_args(&format!("Variant `{}`", qualified), &qualified, &sig, args, &expr_node.span);
+
+                Type::Named(_enum.clone(), Vec::new())
+            }
+
+            Expr::Construct (name, ctor, args) => {
+                self.callees.borrow_mut().insert(name.clone());
+                self.check_args(&format!("Constructor of `{}`", name), name, &self.classes[name].ctors[*ctor], args, &expr_node.span);
+
+                Type::Named(name.clone(), Vec::new())
+            }
+
+            Expr::Call (name, _, args) => {
+                if let Some(builtin) = Builtin::from_name(name) {
+                    return self.infer_builtin_call(builtin, name, args, &expr_node.span);
+                }
+
+                self.callees.borrow_mut().insert(name.clone());
+
+                let sig = self.funcs.get(name).unwrap_or_else(|| {
+                    let builtins = Builtin::ALL.iter().map(|b| b.name()).chain(["println"]);
+                    let name_span = Span::new(expr_node.span.file, expr_node.span.start, expr_node.span.start + name.chars().count());
+
+                    Diagnostic::error(format!("Function `{}` is not defined", name))
+                        .with_code(&codes::E0026)
+                        .with_span(&name_span)
+                        .with_suggestion(&name_span, closest(name, self.funcs.keys().map(String::as_str).chain(builtins)))
+                        .abort()
+                });
+
+                self.check_args(&format!("Function `{}`", name), name, sig, args, &expr_node.span);
+
+                sig.ret_type.clone().unwrap_or(Type::Void)
+            }
+
+            Expr::MethodCall (receiver, name, type_args, args) => {
+                let receiver_ty = self.infer_type(*receiver);
+                let methods = self.methods.get(&receiver_ty.to_string());
+                let what = format!("Method `{}`", method_name(&receiver_ty.to_string(), name));
+
+                if let Some(template) = self.templates.method(&receiver_ty, name) {
+                    self.check_arity(&what, template.params.len(), args, &expr_node.span);
+
+                    self.check_generic_use(&what, &template.generics, type_args, &expr_node.span, || {
+                        format!("give them explicitly, e.g. `.{}<..>(..)`", name)
+                    });
+                }
+
+                if methods.is_some_and(|m| m.contains_key(name)) {
+                    self.check_type_args(&what, &[], type_args, &expr_node.span);
+                }
+
+                if let Some(unmet) = self.templates.unmet(&receiver_ty, name) {
+                    Diagnostic::error(format!("Method `{}` is not available for `{}`", name, receiver_ty))
+                        .with_code(&codes::E0055)
+                        .with_span(&expr_node.span)
+                        .with_label(&unmet.span, format!("`{}` exists only where `{}` implements `{}`", name, unmet.param, unmet.bound))
+                        .with_note(format!("`{}` does not implement `{}`", unmet.arg, unmet.bound))
+                        .abort();
+                }
+
+                if methods.and_then(|m| m.get(name)).is_some_and(|m| m.is_static) {
+                    Diagnostic::error(format!("Method `{}` of `{}` takes no `self`", name, receiver_ty))
+                        .with_code(&codes::E0046)
+                        .with_span(&expr_node.span)
+                        .with_note(format!("call it through its type, e.g. {}", static_call(&receiver_ty, name)))
+                        .abort();
+                }
+
+                let mut diag = Diagnostic::error(format!("Type `{}` has no method `{}`", receiver_ty, name))
+                    .with_code(&codes::E0046)
+                    .with_span(&expr_node.span);
+
+                if let Some(closest) = closest(name, methods.into_iter().flat_map(|m| m.keys().map(String::as_str))) {
+                    diag = diag.with_note(format!("a method with a similar name exists: `{}`", closest));
+                }
+
+                diag.abort()
+            }
+
+            Expr::Invoke (owner, name, receiver, args) => {
+                self.infer_type(*receiver);
+
+                let method = &self.methods[owner][name];
+                let qualified = method_name(owner, name);
+
+                if !method.is_pub && self.class.as_ref() != Some(owner) {
+                    Diagnostic::error(format!("Method `{}` of `{}` is private", name, owner))
+                        .with_code(&codes::E0037)
+                        .with_span(&expr_node.span)
+                        .with_label(&method.span, format!("`{}` is declared here without `pub`", name))
+                        .abort();
+                }
+
+                self.callees.borrow_mut().insert(qualified.clone());
+                self.check_args(&format!("Method `{}`", qualified), &qualified, &method.sig, args, &expr_node.span);
+
+                method.sig.ret_type.clone().unwrap_or(Type::Void)
+            }
+
+            Expr::Associated (owner, name, args) => {
+                let Some(method) = self.methods.get(owner).and_then(|m| m.get(name)).filter(|m| m.is_static) else {
+                    Diagnostic::error(format!("Type `{}` has no method `{}` without `self`", owner, name))
+                        .with_code(&codes::E0046)
+                        .with_span(&expr_node.span)
+                        .abort();
+                };
+
+                let qualified = method_name(owner, name);
+
+                self.callees.borrow_mut().insert(qualified.clone());
+                self.check_args(&format!("Method `{}`", qualified), &qualified, &method.sig, args, &expr_node.span);
+
+                method.sig.ret_type.clone().unwrap_or(Type::Void)
+            }
+
+            // arms that `panic` produce no value and fit any type
+            Expr::Switch (scrutinee, arms) => {
+                let scrutinee_ty = self.infer_type(*scrutinee);
+                let patterns = arms.iter().map(|arm| &arm.pattern).collect::<Vec<_>>();
+
+                self.check_switch(&scrutinee_ty, &patterns, &expr_node.span);
+
+                let mut switch_ty: Option<(Type, &Span)> = None;
+
+                for arm in arms {
+                    let outer = self.declare_bindings(&arm.pattern);
+                    let arm_ty = self.infer_type(arm.expr);
+                    *self.declared.borrow_mut() = outer;
+
+                    if self.diverging_call(arm.expr).is_some() {
+                        continue;
+                    }
+
+                    let arm_span = &self.arena.get(arm.expr).span;
+
+                    match &switch_ty {
+                        None => switch_ty = Some((arm_ty, arm_span)),
+
+                        Some((first_ty, first_span)) if *first_ty != arm_ty => {
+                            Diagnostic::error(format!("Arms of `switch` have different types: `{}` and `{}`", first_ty, arm_ty))
+                                .with_code(&codes::E0051)
+                                .with_span(arm_span)
+                                .with_label(first_span, format!("the first arm is of type `{}`", first_ty))
+                                .abort();
+                        }
+
+                        Some(_) => {}
+                    }
+                }
+
+                switch_ty.map(|(_type, _)| _type).unwrap_or(Type::Void)
+            }
+
+            Expr::Null => not_supported("`null` is", &expr_node.span),
+        }
+    }
+
+    fn infer_binary(&self, op: &BinaryOp, left: ExprId, right: ExprId, span: &Span) -> Type {
+        let left_ty = self.infer_type(left);
+        let right_ty = self.infer_type(right);
+
+        if left_ty != right_ty {
+            generate_error!(
+                span, E0020, "Cannot apply operator `{op}` for types: `{left}` and `{right}`",
+                op = op, left = left_ty, right = right_ty
+            )
+        }
+
+        if let Type::Array(_) | Type::Named(..) | Type::Void = left_ty {
+            generate_error!(
+                span, E0020, "Cannot apply operator `{op}` for types: `{left}` and `{right}`",
+                op = op, left = left_ty, right = right_ty
+            )
+        }
+
+        if left_ty == Type::Unt && (op.is_comparison() || op.is_arranging()) {
+            self.check_unt_comparison(op, left, right, span);
+        }
+
+        if op.is_comparison() {
+            Type::Bool
+
+        } else if
+            op.is_arranging()
+            && left_ty.is_num_type()
+            && right_ty.is_num_type()
+        {
+            Type::Bool
+
+        } else if
+            op.is_arithmetic()
+            && left_ty.is_num_type()
+            && right_ty.is_num_type()
+        {
+            left_ty
+
+        } else if let BinaryOp::Add = op && left_ty == Type::Str {
+            Type::Str
+
+        } else if
+            op.is_logical()
+            && left_ty.is_logical_type()
+            && right_ty.is_logical_type()
+        {
+            left_ty
+
+        } else if
+            op.is_bitwise()
+            && left_ty.is_integer_type()
+            && right_ty.is_integer_type()
+        {
+            left_ty
+
+        } else {
+            generate_error!(
+                span, E0020, "Cannot apply operator `{op}` for types: `{left}` and `{right}`",
+                op = op, left = left_ty, right = right_ty
+            )
+        }
+    }
+
+    /// Checks the arguments of a call to `name` against `sig`. `callee`
+    /// describes what is called, e.g. "Function `f`".
+    fn check_args(&self, callee: &str, name: &str, sig: &FuncSig, args: &[ExprId], span: &Span) {
+        self.check_arity(callee, sig.params.len(), args, span);
+
+        for (arg, param_ty) in args.iter().zip(&sig.params) {
+            let arg_ty = self.infer_type(*arg);
+
+            if arg_ty != *param_ty {
+                generate_error!(
+                    &self.arena.get(*arg).span, E0028, "Cannot pass {} value to parameter of type `{}` in call to `{}`",
+                    arg_ty, param_ty, name
+                );
+            }
+        }
+    }
+
+    fn check_arity(&self, callee: &str, expected: usize, args: &[ExprId], span: &Span) {
+        if args.len() != expected {
+            generate_error!(
+                span, E0027, "{} takes {} argument(s), but {} were supplied",
+                callee, expected, args.len()
+            );
+        }
+    }
+
+    /// Reports why a use of generic `what`, with `type_args` given or
+    /// inferred, was left uninstantiated.
+    fn check_generic_use(
+        &self,
+        what: &str,
+        generics: &[GenericParam],
+        type_args: &[Type],
+        span: &Span,
+        help: impl FnOnce() -> String,
+    ) -> ! {
+        if type_args.is_empty() {
+            self.cannot_infer(what, generics, span, help());
+        }
+
+        for arg in type_args {
+            self.check_type(arg, span);
+        }
+
+        self.check_type_args(what, generics, type_args, span);
+        unreachable!("`{}` fits and is instantiated by the resolver", what)
+    }
+
+    fn infer_builtin_call(&self, builtin: Builtin, name: &str, args: &[ExprId], span: &Span) -> Type {
+        let arg_types: Vec<Type> = args.iter().map(|arg| self.infer_type(*arg)).collect();
+
+        match (builtin, arg_types.as_slice()) {
+            (Builtin::Panic, [Type::Str]) => Type::Void,
+            (Builtin::Assert, [Type::Bool] | [Type::Bool, Type::Str]) => {
+                self.check_condition("assert", args[0]);
+                Type::Void
+            }
+
+            (Builtin::Panic, _) => generate_error!(span, E0029, "Builtin `panic` expects a single `str` message"),
+            (Builtin::Assert, _) => generate_error!(span, E0029, "Builtin `assert` expects a `bool` condition and an optional `str` message"),
+
+            (_, [left, right]) if left == right && left.is_integer_type() => left.clone(),
+
+            _ => generate_error!(span, E0029, "Builtin `{}` expects two integer arguments of the same type", name),
+        }
+    }
+
+    /// Reports a condition whose value is known at compile time. A literal
+    /// `false` is left alone, since `assert(false)` is a deliberate abort.
+    fn check_condition(&self, keyword: &str, cond: ExprId) {
+        if let Expr::Lit(Lit::Bool(false)) = self.arena.get(cond).expr {
+            return;
+        }
+
+        if let Some(value) = self.const_bool(cond) {
+            self.linter.report(
+                Lint::ConstantCondition,
+                Diagnostic::warning(format!("Condition of `{}` is always {}", keyword, value))
+                    .with_span(&self.arena.get(cond).span)
+            );
+        }
+    }
+
+    /// Evaluates a condition built only from literals.
+    fn const_bool(&self, id: ExprId) -> Option<bool> {
+        match &self.arena.get(id).expr {
+            Expr::Lit(Lit::Bool(b)) => Some(*b),
+
+            Expr::Unary(UnaryOp::Not, expr) => self.const_bool(*expr).map(|b| !b),
+
+            Expr::Binary(op, left, right) if op.is_logical() => {
+                let (left, right) = (self.const_bool(*left)?, self.const_bool(*right)?);
+
+                match op {
+                    BinaryOp::And => Some(left & right),
+                    BinaryOp::Or => Some(left | right),
+                    _ => Some(left ^ right),
+                }
+            }
+
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.const_int(*left)?, self.const_int(*right)?);
+
+                match op {
+                    BinaryOp::EqEq => Some(left == right),
+                    BinaryOp::NotEq => Some(left != right),
+                    BinaryOp::Gt => Some(left > right),
+                    BinaryOp::Lt => Some(left < right),
+                    BinaryOp::GtEq => Some(left >= right),
+                    BinaryOp::LtEq => Some(left <= right),
+                    _ => None,
+                }
+            }
+
+            _ => None,
         }
     }
-}
\ No newline at end of file
+
+    fn const_int(&self, id: ExprId) -> Option<i128> {
+        match &self.arena.get(id).expr {
+            Expr::Lit(Lit::Unt(u)) => Some(*u as i128),
+            Expr::Lit(Lit::Int(i)) => Some(*i as i128),
+            Expr::Unary(UnaryOp::Minus, expr) => self.const_int(*expr).map(|i| -i),
+            _ => None,
+        }
+    }
+
+    /// Reports comparisons of an `unt` against zero that cannot fail or
+    /// cannot succeed, such as `u >= 0` or `0 > u`.
+    fn check_unt_comparison(&self, op: &BinaryOp, left: ExprId, right: ExprId, span: &Span) {
+        let is_zero = |id: ExprId| matches!(self.arena.get(id).expr, Expr::Lit(Lit::Unt(0)));
+
+        let always = match op {
+            BinaryOp::GtEq if is_zero(right) => true,
+            BinaryOp::Lt if is_zero(right) => false,
+            BinaryOp::LtEq if is_zero(left) => true,
+            BinaryOp::Gt if is_zero(left) => false,
+            _ => return,
+        };
+
+        self.linter.report(
+            Lint::UntComparison,
+            Diagnostic::warning(format!("Comparison is always {} because `unt` is never negative", always))
+                .with_span(span)
+        );
+    }
+}
@@ -1,3 +1,4 @@
 mod analyzer;
+mod patterns;
 
-pub use analyzer::analyze;
\ No newline at end of file
+pub use analyzer::analyze;
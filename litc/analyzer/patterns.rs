@@ -0,0 +1,202 @@
+// Exhaustiveness and reachability of `switch` arms.
+//
+// Both questions reduce to whether a pattern is *useful* after some rows of
+// patterns: whether some value matches it but none of the rows. An arm is
+// unreachable when its pattern is not useful after the arms above it, and a
+// switch is exhaustive when `_` is not useful after all of its arms. The
+// check follows Maranget, "Warnings for pattern matching" (2007), and
+// returns a value that proves usefulness, which is what a non-exhaustive
+// switch reports as missing.
+
+use std::collections::HashMap;
+
+use crate::ast::*;
+
+/// What a switch on a value of some type leaves unmatched.
+pub struct Coverage {
+    pub unreachable: Vec<usize>,   // the arms that no value reaches
+    pub missing: Option<String>,   // a value that no arm matches, e.g. `Some(false)`
+}
+
+/// Checks the arms of a switch on a value of `_type`, whose `patterns` are
+/// resolved and match that type.
+pub fn check_arms(patterns: &[&Pattern], _type: &Type, enums: &HashMap<String, EnumSig>) -> Coverage {
+    let matcher = Matcher { enums };
+    let types = [_type.clone()];
+
+    let mut rows: Vec<Vec<Pat>> = Vec::new();
+    let mut unreachable = Vec::new();
+
+    for (i, pattern) in patterns.iter().enumerate() {
+        let row = vec![matcher.lower(pattern, _type)];
+
+        if matcher.useful(&rows, &row, &types).is_none() {
+            unreachable.push(i);
+        }
+
+        rows.push(row);
+    }
+
+    let missing = matcher.useful(&rows, &[Pat::Any], &types).map(|mut witness| witness.remove(0));
+
+    Coverage { unreachable, missing }
+}
+
+/// A pattern reduced to what matching depends on.
+#[derive(Clone)]
+enum Pat {
+    Any,                   // `_` or a binding
+    Ctor(Ctor, Vec<Pat>),  // a literal, or a variant with its fields
+}
+
+#[derive(Clone, PartialEq)]
+enum Ctor {
+    Bool(bool),
+    Variant(usize),
+    Lit(String),           // any other literal, by its spelling
+}
+
+struct Matcher<'a> {
+    enums: &'a HashMap<String, EnumSig>,
+}
+
+impl Matcher<'_> {
+    fn lower(&self, pattern: &Pattern, _type: &Type) -> Pat {
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(..) => Pat::Any,
+            Pattern::Lit(Lit::Bool(b), _) => Pat::Ctor(Ctor::Bool(*b), Vec::new()),
+            Pattern::Lit(..) => Pat::Ctor(Ctor::Lit(pattern.to_string()), Vec::new()),
+
+            Pattern::Variant(name, fields, _) => {
+                let (_, variant) = name.rsplit_once("::").unwrap();
+                let (tag, def) = self.enum_of(_type).variant(variant).unwrap();
+                let fields = fields.iter().zip(&def.fields).map(|(field, ty)| self.lower(field, ty)).collect();
+
+                Pat::Ctor(Ctor::Variant(tag), fields)
+            }
+
+            Pattern::Name(..) => unreachable!(),
+        }
+    }
+
+    fn enum_of(&self, _type: &Type) -> &EnumSig {
+        match _type {
+            Type::Named(name, _) => &self.enums[name],
+            _ => unreachable!(),
+        }
+    }
+
+    /// Every constructor of `_type`, or `None` when there are too many to
+    /// list, as for numbers and strings.
+    fn ctors(&self, _type: &Type) -> Option<Vec<Ctor>> {
+        match _type {
+            Type::Bool => Some(vec![Ctor::Bool(true), Ctor::Bool(false)]),
+            Type::Named(name, _) if self.enums.contains_key(name) => {
+                Some((0..self.enums[name].variants.len()).map(Ctor::Variant).collect())
+            }
+            _ => None,
+        }
+    }
+
+    /// Types of the fields of `ctor`, a constructor of `_type`.
+    fn fields(&self, ctor: &Ctor, _type: &Type) -> Vec<Type> {
+        match ctor {
+            Ctor::Variant(tag) => self.enum_of(_type).variants[*tag].fields.clone(),
+            Ctor::Bool(_) | Ctor::Lit(_) => Vec::new(),
+        }
+    }
+
+    fn show(&self, ctor: &Ctor, _type: &Type, fields: &[String]) -> String {
+        match ctor {
+            Ctor::Bool(b) => b.to_string(),
+            Ctor::Lit(lit) => lit.clone(),
+            Ctor::Variant(tag) => {
+                let name = &self.enum_of(_type).variants[*tag].name;
+
+                match fields.is_empty() {
+                    true => name.clone(),
+                    false => format!("{}({})", name, fields.join(", ")),
+                }
+            }
+        }
+    }
+
+    /// Returns values, one per column, that match `row` but none of
+    /// `rows`, or `None` if there are none. Columns have `types`.
+    fn useful(&self, rows: &[Vec<Pat>], row: &[Pat], types: &[Type]) -> Option<Vec<String>> {
+        let Some((head, rest)) = row.split_first() else {
+            return rows.is_empty().then(Vec::new);
+        };
+
+        let (_type, rest_types) = types.split_first().unwrap();
+
+        if let Pat::Ctor(ctor, fields) = head {
+            let row = fields.iter().chain(rest).cloned().collect::<Vec<_>>();
+            return self.useful_ctor(rows, ctor, &row, _type, rest_types);
+        }
+
+        let used = rows.iter()
+            .filter_map(|r| match &r[0] {
+                Pat::Ctor(ctor, _) => Some(ctor),
+                Pat::Any => None,
+            })
+            .collect::<Vec<_>>();
+
+        let all = self.ctors(_type);
+
+        // every constructor appears in the rows, so `_` is useful only
+        // where one of them is
+        if let Some(all) = &all && all.iter().all(|ctor| used.contains(&ctor)) {
+            return all.iter().find_map(|ctor| {
+                let arity = self.fields(ctor, _type).len();
+                let row = std::iter::repeat_n(Pat::Any, arity).chain(rest.iter().cloned()).collect::<Vec<_>>();
+
+                self.useful_ctor(rows, ctor, &row, _type, rest_types)
+            });
+        }
+
+        // otherwise some value is matched by no constructor in the rows,
+        // and only the rows that start with `_` can match it
+        let default = rows.iter()
+            .filter(|r| matches!(r[0], Pat::Any))
+            .map(|r| r[1..].to_vec())
+            .collect::<Vec<_>>();
+
+        let mut witness = self.useful(&default, rest, rest_types)?;
+
+        let head = match all.and_then(|all| all.into_iter().find(|ctor| !used.contains(&ctor))) {
+            Some(ctor) if !used.is_empty() => {
+                let fields = vec!["_".to_string(); self.fields(&ctor, _type).len()];
+                self.show(&ctor, _type, &fields)
+            }
+            _ => "_".to_string(),
+        };
+
+        witness.insert(0, head);
+        Some(witness)
+    }
+
+    /// [`Matcher::useful`] for a row that starts with `ctor`, whose fields
+    /// are already spread into `row`.
+    fn useful_ctor(&self, rows: &[Vec<Pat>], ctor: &Ctor, row: &[Pat], _type: &Type, rest_types: &[Type]) -> Option<Vec<String>> {
+        let field_types = self.fields(ctor, _type);
+        let arity = field_types.len();
+
+        let rows = rows.iter()
+            .filter_map(|r| match &r[0] {
+                Pat::Any => Some(std::iter::repeat_n(Pat::Any, arity).chain(r[1..].iter().cloned()).collect()),
+                Pat::Ctor(c, fields) if c == ctor => Some(fields.iter().chain(&r[1..]).cloned().collect()),
+                Pat::Ctor(..) => None,
+            })
+            .collect::<Vec<Vec<Pat>>>();
+
+        let types = field_types.into_iter().chain(rest_types.iter().cloned()).collect::<Vec<_>>();
+        let mut witness = self.useful(&rows, row, &types)?;
+        let rest = witness.split_off(arity);
+
+        let mut result = vec![self.show(ctor, _type, &witness)];
+        result.extend(rest);
+
+        Some(result)
+    }
+}
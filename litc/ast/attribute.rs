@@ -0,0 +1,15 @@
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `#[...]` in front of a function or method.
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub kind: AttrKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum AttrKind {
+    Word(String),             // e.g. `#[inline]`
+    Bound(String, Vec<Type>), // `#[T : Formatter]`, the method exists only where `T` implements `Formatter`
+}
@@ -1,20 +1,20 @@
-use super::stmt::Stmt;
+use super::stmt_node::StmtNode;
 
 #[derive(Debug)]
 pub struct Block {
-    stmts: Vec<Stmt>,
+    stmts: Vec<StmtNode>,
 }
 
 impl Block {
-    pub fn new(stmts: Vec<Stmt>) -> Block {
+    pub fn new(stmts: Vec<StmtNode>) -> Block {
         Block { stmts }
     }
     
-    pub fn stmts(&self) -> &Vec<Stmt> {
+    pub fn stmts(&self) -> &Vec<StmtNode> {
         &self.stmts
     }
     
-    pub fn mut_stmts(&mut self) -> &mut Vec<Stmt> {
+    pub fn mut_stmts(&mut self) -> &mut Vec<StmtNode> {
         &mut self.stmts
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,65 @@
+use super::bin_op::BinaryOp;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+    Panic,        // `panic(msg)`
+    Assert,       // `assert(cond)` or `assert(cond, msg)`
+
+    // integer arithmetic that always wraps around on overflow
+    WrappingAdd,  // `wrapping_add(a, b)`
+    WrappingSub,  // `wrapping_sub(a, b)`
+    WrappingMul,  // `wrapping_mul(a, b)`
+
+    // integer arithmetic that always panics on overflow or division by zero
+    CheckedAdd,   // `checked_add(a, b)`
+    CheckedSub,   // `checked_sub(a, b)`
+    CheckedMul,   // `checked_mul(a, b)`
+    CheckedDiv,   // `checked_div(a, b)`
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 9] = [
+        Builtin::Panic,
+        Builtin::Assert,
+        Builtin::WrappingAdd,
+        Builtin::WrappingSub,
+        Builtin::WrappingMul,
+        Builtin::CheckedAdd,
+        Builtin::CheckedSub,
+        Builtin::CheckedMul,
+        Builtin::CheckedDiv,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Builtin::Panic => "panic",
+            Builtin::Assert => "assert",
+            Builtin::WrappingAdd => "wrapping_add",
+            Builtin::WrappingSub => "wrapping_sub",
+            Builtin::WrappingMul => "wrapping_mul",
+            Builtin::CheckedAdd => "checked_add",
+            Builtin::CheckedSub => "checked_sub",
+            Builtin::CheckedMul => "checked_mul",
+            Builtin::CheckedDiv => "checked_div",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        Builtin::ALL.into_iter().find(|builtin| builtin.name() == name)
+    }
+
+    /// The operator performed by an arithmetic builtin.
+    pub fn arith_op(&self) -> Option<BinaryOp> {
+        match self {
+            Builtin::WrappingAdd | Builtin::CheckedAdd => Some(BinaryOp::Add),
+            Builtin::WrappingSub | Builtin::CheckedSub => Some(BinaryOp::Sub),
+            Builtin::WrappingMul | Builtin::CheckedMul => Some(BinaryOp::Mul),
+            Builtin::CheckedDiv => Some(BinaryOp::Div),
+            Builtin::Panic | Builtin::Assert => None,
+        }
+    }
+
+    pub fn is_checked(&self) -> bool {
+        matches!(self, Builtin::CheckedAdd | Builtin::CheckedSub | Builtin::CheckedMul | Builtin::CheckedDiv)
+    }
+}
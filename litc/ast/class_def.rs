@@ -0,0 +1,69 @@
+use super::block::Block;
+use super::func_def::FuncDef;
+use super::func_def::FuncSig;
+use super::generic_param::GenericParam;
+use super::param::Param;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `class Name<T> : Interface { fields, constructors and methods }`.
+#[derive(Debug)]
+pub struct ClassDef {
+    pub name: String,
+    pub generics: Vec<GenericParam>,
+    pub interfaces: Vec<Type>,
+    pub fields: Vec<FieldDef>,
+    pub ctors: Vec<CtorDef>,
+    pub methods: Vec<FuncDef>,
+    pub is_pub: bool,
+    pub span: Span,
+}
+
+impl ClassDef {
+    pub fn sig(&self) -> ClassSig {
+        let _type = Type::Named(self.name.clone(), Vec::new());
+
+        ClassSig {
+            fields: self.fields.clone(),
+            ctors: self.ctors.iter()
+                .map(|c| FuncSig {
+                    params: c.params.iter().map(|p| p._type.clone()).collect(),
+                    ret_type: Some(_type.clone()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `unt capacity;` inside a class.
+#[derive(Debug, Clone)]
+pub struct FieldDef {
+    pub _type: Type,
+    pub name: String,
+    pub is_pub: bool,
+    pub span: Span,
+}
+
+/// `Name(params) { ... }` inside class `Name`, called as `Name(args)`.
+#[derive(Debug)]
+pub struct CtorDef {
+    pub generics: Vec<GenericParam>,
+    pub params: Vec<Param>,
+    pub body: Block,
+    pub span: Span,
+}
+
+/// What a class looks like from the outside: its fields in layout order and
+/// the signatures of its constructors.
+#[derive(Debug, Clone)]
+pub struct ClassSig {
+    pub fields: Vec<FieldDef>,
+    pub ctors: Vec<FuncSig>,
+}
+
+impl ClassSig {
+    /// The position of field `name` in the layout, and its definition.
+    pub fn field(&self, name: &str) -> Option<(usize, &FieldDef)> {
+        self.fields.iter().enumerate().find(|(_, f)| f.name == name)
+    }
+}
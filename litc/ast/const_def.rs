@@ -0,0 +1,13 @@
+use super::expr_arena::ExprId;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `const int answer = 42;`, a named value that is evaluated where it is used.
+#[derive(Debug)]
+pub struct ConstDef {
+    pub name: String,
+    pub _type: Type,
+    pub expr_id: ExprId,
+    pub is_pub: bool,
+    pub span: Span,
+}
@@ -0,0 +1,46 @@
+use super::func_def::FuncDef;
+use super::generic_param::GenericParam;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `enum Name<T> : Interface { variants, methods }`.
+#[derive(Debug)]
+pub struct EnumDef {
+    pub name: String,
+    pub generics: Vec<GenericParam>,
+    pub interfaces: Vec<Type>,
+    pub variants: Vec<VariantDef>,
+    pub methods: Vec<FuncDef>,
+    pub is_pub: bool,
+    pub span: Span,
+}
+
+impl EnumDef {
+    pub fn sig(&self) -> EnumSig {
+        EnumSig {
+            variants: self.variants.clone(),
+        }
+    }
+}
+
+/// `Some(T)` or `Null` inside an enum.
+#[derive(Debug, Clone)]
+pub struct VariantDef {
+    pub name: String,
+    pub fields: Vec<Type>,
+    pub span: Span,
+}
+
+/// What an enum looks like from the outside: its variants, whose position
+/// is the tag of a value.
+#[derive(Debug, Clone)]
+pub struct EnumSig {
+    pub variants: Vec<VariantDef>,
+}
+
+impl EnumSig {
+    /// The tag of variant `name`, and its definition.
+    pub fn variant(&self, name: &str) -> Option<(usize, &VariantDef)> {
+        self.variants.iter().enumerate().find(|(_, v)| v.name == name)
+    }
+}
@@ -3,14 +3,44 @@ use super::ty::Type;
 use super::un_op::UnaryOp;
 use super::expr_arena::ExprId;
 use super::lit::Lit;
+use super::pattern::SwitchArm;
 
 #[derive(Debug, Clone)]
 pub enum Expr {
     Lit(Lit), // literal
     Var(String), // variable
+    Const(String), // constant, by its qualified name, e.g. `Math::pi`
     
     Binary(BinaryOp, ExprId, ExprId), // binary action, e.g. `2 + 2 * 2`
     Unary(UnaryOp, ExprId), // unary action, e.g. `-a` where `a` is a variable
 
     Cast(Type, ExprId), // cast action, e.g. `a as float` where variable `a` is of type `unt`
-}
\ No newline at end of file
+
+    Field(ExprId, String), // field access, e.g. `s.len` where variable `s` is of type `str`
+    Index(ExprId, ExprId), // index access, e.g. `s[0]` where variable `s` is of type `str`
+
+    Call(String, Vec<Type>, Vec<ExprId>), // function call with optional type arguments, e.g. `panic("message")` or `Vec<T>(8)`
+    MethodCall(ExprId, String, Vec<Type>, Vec<ExprId>), // method call, e.g. `s.parse<int>()`
+    Construct(String, usize, Vec<ExprId>), // call of a class by its qualified name, with the index of the constructor it resolved to
+    Invoke(String, String, ExprId, Vec<ExprId>), // method call resolved to a method of the receiver's type, by the type's name
+    Associated(String, String, Vec<ExprId>), // call of a method without a receiver, by its type's name, e.g. `int::parse(s)` for `T::parse(s)`
+    Variant(String, String, Vec<ExprId>), // variant of an enum by the enum's qualified name, e.g. `Shape::Circle(1.0)` or `Color::Red`
+
+    Switch(ExprId, Vec<SwitchArm>), // e.g. `switch b { true => 1, false => 0 }`
+
+    Null, // `null`, the empty pointer
+}
+
+impl Expr {
+    /// Direct subexpressions, in evaluation order.
+    pub fn children(&self) -> Vec<ExprId> {
+        match self {
+            Expr::Lit(_) | Expr::Var(_) | Expr::Const(_) | Expr::Null => Vec::new(),
+            Expr::Binary(_, left, right) | Expr::Index(left, right) => vec![*left, *right],
+            Expr::Unary(_, expr) | Expr::Cast(_, expr) | Expr::Field(expr, _) => vec![*expr],
+            Expr::Call(_, _, args) | Expr::Construct(_, _, args) | Expr::Variant(_, _, args) | Expr::Associated(_, _, args) => args.clone(),
+            Expr::MethodCall(receiver, _, _, args) | Expr::Invoke(_, _, receiver, args) => [*receiver].into_iter().chain(args.iter().copied()).collect(),
+            Expr::Switch(scrutinee, arms) => [*scrutinee].into_iter().chain(arms.iter().map(|arm| arm.expr)).collect(),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use super::expr_node::ExprNode;
 
 pub type ExprId = usize;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ExprArena {
     nodes: Vec<ExprNode>,
 }
@@ -0,0 +1,11 @@
+use super::block::Block;
+use super::expr_arena::ExprId;
+use super::stmt_node::StmtNode;
+
+#[derive(Debug)]
+pub enum ForLoop {
+    // `for el in arr { }` or `for i, el in arr { }`
+    Each { index: Option<String>, elem: String, iter: ExprId, body: Block },
+    // `for unt i = 0, i < n, i++ { }`
+    Counted { init: Box<StmtNode>, cond: ExprId, step: Box<StmtNode>, body: Block },
+}
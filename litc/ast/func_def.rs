@@ -1,7 +1,70 @@
+use super::attribute::Attribute;
 use super::block::Block;
+use super::generic_param::GenericParam;
+use super::param::Param;
+use super::ty::Type;
+use crate::lexer::Span;
 
 #[derive(Debug)]
 pub struct FuncDef {
     pub name: String,
+    pub generics: Vec<GenericParam>,
+    pub attrs: Vec<Attribute>,
+    // `self` written as the first parameter; methods have a receiver
+    // whether or not it is written
+    pub receiver: Option<Span>,
+    pub params: Vec<Param>,
+    pub ret_type: Option<Type>,
     pub body: Block,
-}
\ No newline at end of file
+    pub is_pub: bool,
+    pub is_override: bool,
+    // implements an interface method declared without `self`, so it has no
+    // receiver and is called through its type, e.g. `T::parse(s)`
+    pub is_static: bool,
+    pub span: Span,
+}
+
+impl FuncDef {
+    /// The name without its module path, e.g. `f` for `a::b::f`.
+    pub fn base_name(&self) -> &str {
+        self.name.rsplit("::").next().unwrap_or(&self.name)
+    }
+
+    pub fn sig(&self) -> FuncSig {
+        FuncSig {
+            params: self.params.iter().map(|p| p._type.clone()).collect(),
+            ret_type: self.ret_type.clone(),
+        }
+    }
+
+    /// An `override` is as visible as the interface method it implements.
+    pub fn method_sig(&self) -> MethodSig {
+        MethodSig {
+            sig: self.sig(),
+            is_pub: self.is_pub || self.is_override,
+            is_static: self.is_static,
+            span: self.span.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FuncSig {
+    pub params: Vec<Type>,
+    pub ret_type: Option<Type>,
+}
+
+/// What callers of a method see. The receiver is not among the parameters.
+#[derive(Debug, Clone)]
+pub struct MethodSig {
+    pub sig: FuncSig,
+    pub is_pub: bool,
+    pub is_static: bool,
+    pub span: Span,
+}
+
+/// Name of method `name` of type `owner` in diagnostics and in the call
+/// graph, e.g. `a::Point.len`.
+pub fn method_name(owner: &str, name: &str) -> String {
+    format!("{}.{}", owner, name)
+}
@@ -0,0 +1,10 @@
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `T` or `T : Formatter` in `<...>` after `fun` or a type name.
+#[derive(Debug, Clone)]
+pub struct GenericParam {
+    pub name: String,
+    pub bounds: Vec<Type>,
+    pub span: Span,
+}
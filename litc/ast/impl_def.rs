@@ -0,0 +1,21 @@
+use super::func_def::FuncDef;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `impl $str { methods }`, which adds methods to a type, or
+/// `impl Interface { $unt { methods } ... }`, which implements an interface
+/// for each of the listed types.
+#[derive(Debug)]
+pub struct ImplDef {
+    pub interface: Option<Type>,
+    pub blocks: Vec<ImplBlock>,
+    pub span: Span,
+}
+
+/// The methods for one type, `$unt { ... }`.
+#[derive(Debug)]
+pub struct ImplBlock {
+    pub target: Type,
+    pub methods: Vec<FuncDef>,
+    pub span: Span,
+}
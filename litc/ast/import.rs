@@ -0,0 +1,21 @@
+use crate::lexer::Span;
+
+/// `import a::b::item;` brings `item` of module `a::b` into scope.
+#[derive(Debug)]
+pub struct Import {
+    pub path: Vec<String>,
+    pub span: Span,
+    pub item_span: Span,
+}
+
+impl Import {
+    /// Path of the module the item is imported from.
+    pub fn module(&self) -> &[String] {
+        &self.path[..self.path.len() - 1]
+    }
+
+    /// Name of the imported item.
+    pub fn item(&self) -> &str {
+        &self.path[self.path.len() - 1]
+    }
+}
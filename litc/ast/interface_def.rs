@@ -0,0 +1,57 @@
+use super::func_def::FuncSig;
+use super::generic_param::GenericParam;
+use super::param::Param;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// `interface Name { fun method(self): str; }`.
+#[derive(Debug)]
+pub struct InterfaceDef {
+    pub name: String,
+    pub generics: Vec<GenericParam>,
+    pub methods: Vec<FuncDecl>,
+    pub is_pub: bool,
+    pub span: Span,
+}
+
+impl InterfaceDef {
+    pub fn sig(&self) -> InterfaceSig {
+        InterfaceSig {
+            methods: self.methods.clone(),
+        }
+    }
+}
+
+/// A method of an interface, which has no body.
+#[derive(Debug, Clone)]
+pub struct FuncDecl {
+    pub name: String,
+    pub generics: Vec<GenericParam>,
+    pub receiver: Option<Span>,
+    pub params: Vec<Param>,
+    pub ret_type: Option<Type>,
+    pub span: Span,
+}
+
+impl FuncDecl {
+    pub fn sig(&self) -> FuncSig {
+        FuncSig {
+            params: self.params.iter().map(|p| p._type.clone()).collect(),
+            ret_type: self.ret_type.clone(),
+        }
+    }
+}
+
+/// What a type must provide to implement an interface. In the signatures
+/// of its methods, the interface's own name stands for the implementing
+/// type, as in `fun parse(str s): !FromStr`.
+#[derive(Debug, Clone)]
+pub struct InterfaceSig {
+    pub methods: Vec<FuncDecl>,
+}
+
+impl InterfaceSig {
+    pub fn method(&self, name: &str) -> Option<&FuncDecl> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
@@ -0,0 +1,9 @@
+use crate::lexer::Span;
+
+/// `llvm { ... }`, LLVM IR that is emitted as it is, except that `$name`
+/// stands for the Lit variable `name`.
+#[derive(Debug, Clone)]
+pub struct LlvmBlock {
+    pub code: String,
+    pub span: Span,
+}
@@ -1,7 +1,9 @@
 mod program;
 mod func_def;
+mod param;
 mod expr_arena;
 mod stmt;
+mod stmt_node;
 mod var_decl;
 mod expr;
 mod bin_op;
@@ -10,17 +12,64 @@ mod lit;
 mod un_op;
 mod expr_node;
 mod block;
+mod builtin;
+mod import;
+mod const_def;
+mod module_def;
+mod generic_param;
+mod attribute;
+mod class_def;
+mod enum_def;
+mod interface_def;
+mod impl_def;
+mod llvm_block;
+mod pattern;
+mod for_loop;
+mod templates;
 
 pub use program::Program;
 pub use func_def::FuncDef;
+pub use func_def::FuncSig;
+pub use func_def::MethodSig;
+pub use func_def::method_name;
+pub use param::Param;
 pub use block::Block;
 pub use expr_arena::ExprArena;
 pub use expr_arena::ExprId;
 pub use stmt::Stmt;
+pub use stmt_node::StmtNode;
 pub use var_decl::VarDecl;
 pub use expr_node::ExprNode;
 pub use expr::Expr;
 pub use bin_op::BinaryOp;
 pub use un_op::UnaryOp;
 pub use ty::Type;
-pub use lit::Lit;
\ No newline at end of file
+pub use lit::Lit;
+pub use builtin::Builtin;
+pub use import::Import;
+pub use const_def::ConstDef;
+pub use module_def::ModuleDef;
+pub use generic_param::GenericParam;
+pub use attribute::Attribute;
+pub use attribute::AttrKind;
+pub use class_def::ClassDef;
+pub use class_def::FieldDef;
+pub use class_def::CtorDef;
+pub use class_def::ClassSig;
+pub use enum_def::EnumDef;
+pub use enum_def::VariantDef;
+pub use enum_def::EnumSig;
+pub use interface_def::InterfaceDef;
+pub use interface_def::FuncDecl;
+pub use interface_def::InterfaceSig;
+pub use impl_def::ImplDef;
+pub use impl_def::ImplBlock;
+pub use llvm_block::LlvmBlock;
+pub use pattern::Pattern;
+pub use pattern::SwitchArm;
+pub use pattern::SwitchCase;
+pub use for_loop::ForLoop;
+pub use templates::Templates;
+pub use templates::Probe;
+pub use templates::UnmetBound;
+pub use templates::instance_name;
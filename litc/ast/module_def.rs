@@ -0,0 +1,24 @@
+use super::class_def::ClassDef;
+use super::const_def::ConstDef;
+use super::enum_def::EnumDef;
+use super::func_def::FuncDef;
+use super::impl_def::ImplDef;
+use super::interface_def::InterfaceDef;
+use super::llvm_block::LlvmBlock;
+use crate::lexer::Span;
+
+/// `module Name { ... }`, a namespace for the items inside it.
+#[derive(Debug)]
+pub struct ModuleDef {
+    pub name: String,
+    pub funcs: Vec<FuncDef>,
+    pub consts: Vec<ConstDef>,
+    pub modules: Vec<ModuleDef>,
+    pub classes: Vec<ClassDef>,
+    pub enums: Vec<EnumDef>,
+    pub interfaces: Vec<InterfaceDef>,
+    pub impls: Vec<ImplDef>,
+    pub llvm: Vec<LlvmBlock>,
+    pub is_pub: bool,
+    pub span: Span,
+}
@@ -0,0 +1,11 @@
+use super::ty::Type;
+use crate::lexer::Span;
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub _type: Type,
+    pub name: String,
+    // `T... elems`, which takes the remaining arguments as an array
+    pub variadic: bool,
+    pub span: Span,
+}
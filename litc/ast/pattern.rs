@@ -0,0 +1,79 @@
+use std::fmt;
+
+use super::block::Block;
+use super::expr_arena::ExprId;
+use super::lit::Lit;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// The left-hand side of a `switch` arm.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Lit(Lit, Span),                    // e.g. `true` or `"yes"`
+    Wildcard(Span),                    // `_`
+    Name(String, Span),                // a unit variant such as `Null`, or else a binding such as `other`
+    Variant(String, Vec<Pattern>, Span), // e.g. `Some(v)`, or `Null` once resolved; the name is `Enum::Variant` once resolved
+    Binding(String, Type, Span),       // a name that binds the matched value, once resolved
+}
+
+impl Pattern {
+    /// Every plain name in the pattern. Whether one binds a value or names
+    /// a unit variant is only known once the type of the scrutinee is.
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            Pattern::Name(name, _) | Pattern::Binding(name, ..) if !name.contains("::") => vec![name.clone()],
+            Pattern::Variant(_, fields, _) => fields.iter().flat_map(Pattern::names).collect(),
+            Pattern::Name(..) | Pattern::Binding(..) | Pattern::Lit(..) | Pattern::Wildcard(_) => Vec::new(),
+        }
+    }
+
+    /// Every name bound by the resolved pattern, with its type and span.
+    pub fn bindings(&self) -> Vec<(&str, &Type, &Span)> {
+        match self {
+            Pattern::Binding(name, _type, span) => vec![(name.as_str(), _type, span)],
+            Pattern::Variant(_, fields, _) => fields.iter().flat_map(Pattern::bindings).collect(),
+            Pattern::Lit(..) | Pattern::Wildcard(_) | Pattern::Name(..) => Vec::new(),
+        }
+    }
+
+    pub fn span(&self) -> &Span {
+        match self {
+            Pattern::Lit(_, span) | Pattern::Wildcard(span) | Pattern::Name(_, span)
+            | Pattern::Variant(_, _, span) | Pattern::Binding(_, _, span) => span,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Pattern::Lit(Lit::Unt(u), _) => write!(f, "{}", u),
+            Pattern::Lit(Lit::Int(i), _) => write!(f, "{}", i),
+            Pattern::Lit(Lit::Float(fl), _) => write!(f, "{:?}", fl),
+            Pattern::Lit(Lit::Bool(b), _) => write!(f, "{}", b),
+            Pattern::Lit(Lit::Char(c), _) => write!(f, "{:?}", c),
+            Pattern::Lit(Lit::Str(s), _) => write!(f, "{:?}", s),
+            Pattern::Wildcard(_) => write!(f, "_"),
+            Pattern::Name(name, _) | Pattern::Binding(name, ..) => write!(f, "{}", name),
+            Pattern::Variant(name, fields, _) if fields.is_empty() => write!(f, "{}", name),
+            Pattern::Variant(name, fields, _) => {
+                let fields = fields.iter().map(Pattern::to_string).collect::<Vec<_>>();
+                write!(f, "{}({})", name, fields.join(", "))
+            }
+        }
+    }
+}
+
+/// `pattern => expression` inside `switch`.
+#[derive(Debug, Clone)]
+pub struct SwitchArm {
+    pub pattern: Pattern,
+    pub expr: ExprId,
+}
+
+/// `pattern => { statements }` inside a `switch` statement.
+#[derive(Debug)]
+pub struct SwitchCase {
+    pub pattern: Pattern,
+    pub body: Block,
+}
@@ -1,8 +1,112 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
 use super::func_def::FuncDef;
+use super::func_def::FuncSig;
+use super::func_def::MethodSig;
 use super::expr_arena::ExprArena;
+use super::import::Import;
+use super::const_def::ConstDef;
+use super::module_def::ModuleDef;
+use super::class_def::ClassDef;
+use super::class_def::ClassSig;
+use super::enum_def::EnumDef;
+use super::enum_def::EnumSig;
+use super::interface_def::InterfaceDef;
+use super::interface_def::InterfaceSig;
+use super::impl_def::ImplDef;
+use super::llvm_block::LlvmBlock;
+use super::templates::Templates;
+use super::ty::Type;
+use crate::lexer::Span;
 
 #[derive(Debug)]
 pub struct Program {
+    pub imports: Vec<Import>,
     pub funcs: Vec<FuncDef>,
+    pub consts: Vec<ConstDef>,
+    pub modules: Vec<ModuleDef>,
+    pub classes: Vec<ClassDef>,
+    pub enums: Vec<EnumDef>,
+    pub interfaces: Vec<InterfaceDef>,
+    pub impls: Vec<ImplDef>,
+    pub llvm: Vec<LlvmBlock>,
+    // `#![no_std]`, which leaves the prelude out of the program
+    pub no_std: Option<Span>,
+    pub templates: Templates,
     pub expr_arena: ExprArena,
+}
+
+impl Program {
+    pub fn signatures(&self) -> HashMap<String, FuncSig> {
+        self.funcs.iter()
+            .map(|f| (f.name.clone(), f.sig()))
+            .collect()
+    }
+
+    pub fn class_sigs(&self) -> HashMap<String, ClassSig> {
+        self.classes.iter()
+            .map(|c| (c.name.clone(), c.sig()))
+            .collect()
+    }
+
+    pub fn enum_sigs(&self) -> HashMap<String, EnumSig> {
+        self.enums.iter()
+            .map(|e| (e.name.clone(), e.sig()))
+            .collect()
+    }
+
+    pub fn interface_sigs(&self) -> HashMap<String, InterfaceSig> {
+        self.interfaces.iter()
+            .map(|i| (i.name.clone(), i.sig()))
+            .collect()
+    }
+
+    /// Every type that implements an interface, through an `impl` block or
+    /// the interface list of its class or enum, paired with the interface.
+    pub fn implementations(&self) -> HashSet<(String, String)> {
+        let impls = self.impls.iter()
+            .filter_map(|i| i.interface.as_ref().map(|interface| (interface, &i.blocks)))
+            .flat_map(|(interface, blocks)| blocks.iter().map(|b| (b.target.to_string(), interface.to_string())));
+
+        let classes = self.classes.iter()
+            .flat_map(|c| c.interfaces.iter().map(|i| (c.name.clone(), i.to_string())));
+
+        let enums = self.enums.iter()
+            .flat_map(|e| e.interfaces.iter().map(|i| (e.name.clone(), i.to_string())));
+
+        impls.chain(classes).chain(enums).collect()
+    }
+
+    /// Every method with the type it belongs to, from classes and enums
+    /// first and then from `impl` blocks.
+    pub fn methods(&self) -> Vec<(Type, &FuncDef)> {
+        let class_methods = self.classes.iter()
+            .flat_map(|c| c.methods.iter().map(|m| (Type::Named(c.name.clone(), Vec::new()), m)));
+
+        let enum_methods = self.enums.iter()
+            .flat_map(|e| e.methods.iter().map(|m| (Type::Named(e.name.clone(), Vec::new()), m)));
+
+        let impl_methods = self.impls.iter()
+            .flat_map(|i| &i.blocks)
+            .flat_map(|b| b.methods.iter().map(|m| (b.target.clone(), m)));
+
+        class_methods.chain(enum_methods).chain(impl_methods).collect()
+    }
+
+    /// Signatures of the methods of each type, keyed by the type's name and
+    /// then by method name. Of two methods with the same name, the first
+    /// one is kept.
+    pub fn method_sigs(&self) -> HashMap<String, HashMap<String, MethodSig>> {
+        let mut sigs: HashMap<String, HashMap<String, MethodSig>> = HashMap::new();
+
+        for (owner, method) in self.methods() {
+            sigs.entry(owner.to_string())
+                .or_default()
+                .entry(method.name.clone())
+                .or_insert_with(|| method.method_sig());
+        }
+
+        sigs
+    }
 }
\ No newline at end of file
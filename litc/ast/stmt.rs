@@ -1,9 +1,21 @@
+use super::bin_op::BinaryOp;
+use super::block::Block;
 use super::expr_arena::ExprId;
+use super::for_loop::ForLoop;
+use super::pattern::SwitchCase;
 use super::var_decl::VarDecl;
 
 #[derive(Debug)]
 pub enum Stmt {
-    VarDecl(VarDecl),       // `<type> <name> = <expression>`
-    Println(ExprId),        // `println()`
-    Unreachable,            // `unreachable`
-}
\ No newline at end of file
+    VarDecl(VarDecl),             // `<type> <name> = <expression>`
+    Println(ExprId),              // `println()`
+    Unreachable(Option<ExprId>),  // `unreachable` or `unreachable(<message>)`
+    Return(Option<ExprId>),       // `return <expression>`
+    Expr(ExprId),                 // `<expression>;`, e.g. `assert(x > 0);`
+    Assign(ExprId, Option<BinaryOp>, ExprId), // `<place> = <expression>` or `<place> += <expression>`; `i++` is `i += 1`
+    If(ExprId, Block, Option<Block>), // `if <condition> { } else { }`, where `else if` is an `if` alone in the `else` block
+    While(ExprId, Block),         // `while <condition> { }`
+    For(ForLoop),                 // `for <element> in <array> { }` or `for <init>, <condition>, <step> { }`
+    Switch(ExprId, Vec<SwitchCase>), // `switch <expression> { <pattern> => { }, ... }`
+    Llvm(String),                 // `llvm { <IR> }`
+}
@@ -0,0 +1,15 @@
+use super::Stmt;
+use crate::lexer::Span;
+
+#[derive(Debug)]
+pub struct StmtNode {
+    pub stmt: Stmt,
+    pub span: Span,
+}
+
+impl StmtNode {
+    #[inline]
+    pub fn new(stmt: Stmt, span: Span) -> StmtNode {
+        StmtNode { stmt, span }
+    }
+}
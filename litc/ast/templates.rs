@@ -0,0 +1,80 @@
+use super::class_def::ClassDef;
+use super::enum_def::EnumDef;
+use super::func_def::FuncDef;
+use super::impl_def::ImplDef;
+use super::ty::Type;
+use crate::lexer::Span;
+
+/// The generic functions, types and methods of a program. The resolver sets
+/// them aside, since only their instances are analyzed and emitted: one copy
+/// for each list of type arguments they are used with, named the way the
+/// type would be written, e.g. `id<int>` or `Vec<str>`. Each template is
+/// also checked once on its own, through a probe.
+#[derive(Debug, Default)]
+pub struct Templates {
+    pub funcs: Vec<FuncDef>,
+    pub classes: Vec<ClassDef>,
+    pub enums: Vec<EnumDef>,
+    // generic methods, with the type they belong to
+    pub methods: Vec<(Type, FuncDef)>,
+    // conditional methods left out of instances whose type arguments do not
+    // meet their bounds
+    pub unmet: Vec<UnmetBound>,
+    pub probes: Vec<Probe>,
+}
+
+/// A template instantiated for its own generic parameters, each of which is
+/// read as a class with just the methods of its bounds. Analyzing it checks
+/// the template for every type argument at once, whether or not the program
+/// uses it. Probes are never emitted.
+#[derive(Debug, Default)]
+pub struct Probe {
+    pub funcs: Vec<FuncDef>,
+    // the classes standing for the generic parameters, then the instances
+    // of generic classes that the probe makes
+    pub classes: Vec<ClassDef>,
+    pub enums: Vec<EnumDef>,
+    pub impls: Vec<ImplDef>,
+}
+
+/// Method `method` of instance `owner`, declared with `#[T : Formatter]`,
+/// which `owner` lacks since its type argument `arg` for `param` does not
+/// implement `bound`.
+#[derive(Debug)]
+pub struct UnmetBound {
+    pub owner: Type,
+    pub method: String,
+    pub param: String,
+    pub arg: Type,
+    pub bound: Type,
+    // the attribute that declares the bound
+    pub span: Span,
+}
+
+impl Templates {
+    pub fn func(&self, name: &str) -> Option<&FuncDef> {
+        self.funcs.iter().find(|f| f.name == name)
+    }
+
+    pub fn class(&self, name: &str) -> Option<&ClassDef> {
+        self.classes.iter().find(|c| c.name == name)
+    }
+
+    pub fn _enum(&self, name: &str) -> Option<&EnumDef> {
+        self.enums.iter().find(|e| e.name == name)
+    }
+
+    pub fn method(&self, owner: &Type, name: &str) -> Option<&FuncDef> {
+        self.methods.iter().find(|(o, m)| o == owner && m.name == name).map(|(_, m)| m)
+    }
+
+    /// The unmet bound that left method `name` out of instance `owner`.
+    pub fn unmet(&self, owner: &Type, name: &str) -> Option<&UnmetBound> {
+        self.unmet.iter().find(|u| u.owner == *owner && u.method == name)
+    }
+}
+
+/// Name of the instance of generic item `name` for `args`, e.g. `Vec<str>`.
+pub fn instance_name(name: &str, args: &[Type]) -> String {
+    Type::Named(name.to_string(), args.to_vec()).to_string()
+}
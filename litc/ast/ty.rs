@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -8,9 +9,18 @@ pub enum Type {
     Bool,     // boolean
     Char,     // character
     Str,      // string
+    Array(Box<Type>), // array of elements, e.g. `str[]`
+    Named(String, Vec<Type>), // class, enum, interface or generic parameter, e.g. `Vec<T>`
+    Ptr(Box<Type>),   // raw pointer for `llvm` blocks, e.g. `i8*`
+    Fallible(Box<Type>), // value or error, e.g. `!int`
+    Lambda(Vec<Type>, Box<Type>), // function value, e.g. `lambda: (int) => str`
+    Void,     // no value, e.g. result of a function without return type
 }
 
 impl Type {
+    /// Keywords naming the primitive types.
+    pub const PRIMITIVES: [&'static str; 6] = ["unt", "int", "float", "bool", "char", "str"];
+
     pub fn is_num_type(&self) -> bool {
         match self {
             Type::Unt |
@@ -30,6 +40,22 @@ impl Type {
         }
     }
     
+    /// Replaces the names in `subst`, such as generic parameters, with
+    /// the types they stand for.
+    pub fn substitute(&self, subst: &HashMap<String, Type>) -> Type {
+        match self {
+            Type::Named(name, args) if args.is_empty() && subst.contains_key(name) => subst[name].clone(),
+            Type::Named(name, args) => Type::Named(name.clone(), args.iter().map(|a| a.substitute(subst)).collect()),
+            Type::Array(inner) => Type::Array(Box::new(inner.substitute(subst))),
+            Type::Ptr(inner) => Type::Ptr(Box::new(inner.substitute(subst))),
+            Type::Fallible(inner) => Type::Fallible(Box::new(inner.substitute(subst))),
+            Type::Lambda(params, ret) => {
+                Type::Lambda(params.iter().map(|p| p.substitute(subst)).collect(), Box::new(ret.substitute(subst)))
+            }
+            _ => self.clone(),
+        }
+    }
+
     pub fn is_logical_type(&self) -> bool {
         match self {
             Type::Unt |
@@ -50,6 +76,17 @@ impl fmt::Display for Type {
             Type::Bool => write!(f, "bool"),
             Type::Char => write!(f, "char"),
             Type::Str => write!(f, "str"),
+            Type::Array(elem) => write!(f, "{}[]", elem),
+            Type::Named(name, args) if args.is_empty() => write!(f, "{}", name),
+            Type::Named(name, args) => write!(f, "{}<{}>", name, join(args)),
+            Type::Ptr(inner) => write!(f, "{}*", inner),
+            Type::Fallible(inner) => write!(f, "!{}", inner),
+            Type::Lambda(params, ret) => write!(f, "lambda: ({}) => {}", join(params), ret),
+            Type::Void => write!(f, "void"),
         }
     }
-}
\ No newline at end of file
+}
+
+fn join(types: &[Type]) -> String {
+    types.iter().map(Type::to_string).collect::<Vec<_>>().join(", ")
+}
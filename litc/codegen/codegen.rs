@@ -1,12 +1,17 @@
-use std::collections::HashMap;
+use std::iter;
 
-use super::utils;
+use super::runtime;
 use super::LlvmType;
 use super::FuncCtx;
+use super::Globals;
 use super::EmitState;
+use super::StrPool;
+use super::mangle;
 use crate::ast::*;
+use crate::lexer::SourceMap;
+use crate::lexer::Span;
 
-pub fn generate(program: Program) -> String {
+pub fn generate(program: Program, sources: &SourceMap, checks: bool) -> String {
     let mut out = String::new();
 
     out.push_str("\
@@ -15,7 +20,6 @@ pub fn generate(program: Program) -> String {
         declare i32 @puts(i8* nocapture)\n\
         declare i32 @printf(i8*, ...)\n\
         declare void @exit(i32)\n\n\
-        @unreachable_msg = private unnamed_addr constant [29 x i8] c\"Entered to unreachable code\\0A\\00\"\n\
         @fmt.u64 = private unnamed_addr constant [6 x i8] c\"%llu\\0A\\00\"\n\
         @fmt.i64 = private unnamed_addr constant [6 x i8] c\"%lld\\0A\\00\"\n\
         @fmt.f64 = private unnamed_addr constant [4 x i8] c\"%g\\0A\\00\"\n\
@@ -23,73 +27,380 @@ pub fn generate(program: Program) -> String {
         @bool.false = private unnamed_addr constant [6 x i8] c\"false\\00\"\n\n\
     ");
 
+    out.push_str(runtime::DECLARATIONS);
+    out.push_str(runtime::STR_CONCAT);
+    out.push_str(runtime::ABORT);
+
+    let pool = StrPool::build(&program, sources, checks);
+    pool.emit(&mut out);
+
+    for class in &program.classes {
+        emit_class_type(&mut out, class);
+    }
+
+    for _enum in &program.enums {
+        emit_enum_type(&mut out, _enum);
+    }
+
+    let globals = Globals {
+        funcs: program.signatures(),
+        classes: program.class_sigs(),
+        enums: program.enum_sigs(),
+        methods: program.method_sigs(),
+        consts: program.consts.iter().map(|c| (c.name.clone(), c.expr_id)).collect(),
+        sources,
+        checks,
+    };
+
     for func in &program.funcs {
-        emit_func(&mut out, func, &program.expr_arena);
+        let ctx = FuncCtx::build(func, &program.expr_arena, &globals);
+        emit_func(&mut out, func, &program.expr_arena, &pool, &ctx);
+    }
+
+    for class in &program.classes {
+        for (index, ctor) in class.ctors.iter().enumerate() {
+            let ctx = FuncCtx::build_ctor(&class.name, ctor, &program.expr_arena, &globals);
+            emit_ctor(&mut out, &class.name, index, ctor, &program.expr_arena, &pool, &ctx);
+        }
+    }
+
+    for (owner, method) in program.methods() {
+        let ctx = FuncCtx::build_method(&owner, method, &program.expr_arena, &globals);
+        emit_method(&mut out, &owner, method, &program.expr_arena, &pool, &ctx);
     }
 
     out
 }
 
-fn emit_func(out: &mut String, func: &FuncDef, expr_arena: &ExprArena) {
-    let ctx = FuncCtx::build(func, expr_arena);
+/// Declares the struct that objects of `class` point to, one member per
+/// field in declaration order.
+fn emit_class_type(out: &mut String, class: &ClassDef) {
+    let fields = class.fields.iter()
+        .map(|f| LlvmType::from(&f._type).get_alloca_type())
+        .collect::<Vec<_>>()
+        .join(", ");
 
-    for (i, s) in ctx.get_string_consts().iter().enumerate() {
-        let b = s.len() + 1;
-        out.push_str(&format!(
-           "@str.{fn_name}.{i} = private unnamed_addr constant [{b} x i8] c\"{esc}\\00\"\n",
-            fn_name = func.name, i = i, b = b, esc = utils::escape_llvm(s),
-        ));
+    out.push_str(&format!("{} = type {{ {} }}\n\n", mangle::object(&class.name), fields));
+}
+
+/// Declares the struct that values of `_enum` point to: the tag, which is
+/// the index of the variant, and a payload big enough for the fields of any
+/// variant. Each variant reads its payload as a struct of its fields.
+fn emit_enum_type(out: &mut String, _enum: &EnumDef) {
+    out.push_str(&format!(
+        "{} = type {{ i64, [{} x i64] }}\n\n",
+        mangle::object(&_enum.name), payload_words(&_enum.variants),
+    ));
+}
+
+/// Size of the payload of an enum with `variants`, in 8-byte words.
+fn payload_words(variants: &[VariantDef]) -> usize {
+    variants.iter()
+        .map(|v| v.fields.iter().map(|f| LlvmType::from(f).size_in_words()).sum::<usize>())
+        .max()
+        .unwrap_or(0)
+}
+
+fn emit_func(out: &mut String, func: &FuncDef, expr_arena: &ExprArena, pool: &StrPool, ctx: &FuncCtx) {
+    let mut state = EmitState::new();
+
+    if ctx.is_entry_point() {
+        emit_entry_signature(out, func, &mut state);
+    } else {
+        emit_signature(out, func);
+    }
+
+    emit_block(out, &func.body, expr_arena, pool, ctx, &mut state);
+
+    if !state.is_terminated() {
+        emit_return(out, expr_arena, None, pool, ctx, &mut state);
+    }
+
+    out.push_str("}\n\n");
+}
+
+/// A constructor allocates a zeroed object, runs its body with `self`
+/// pointing at the object and returns it.
+#[allow(clippy::too_many_arguments)]
+fn emit_ctor(
+    out: &mut String,
+    class: &str,
+    index: usize,
+    ctor: &CtorDef,
+    expr_arena: &ExprArena,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+) {
+    let mut state = EmitState::new();
+    let _type = LlvmType::Object(class.to_string()).get_alloca_type();
+
+    out.push_str(&format!("define {} {}({}) {{\n", _type, mangle::ctor(class, index), emit_param_list(&ctor.params)));
+    emit_params(out, &ctor.params);
+
+    let object = emit_alloc(out, class, &mut state);
+
+    out.push_str(&format!("  \
+           {name} = alloca {_type}\n  \
+           store {_type} {object}, {_type}* {name}\n\
+        ",
+        _type = _type, object = object, name = mangle::local("self"),
+    ));
+
+    emit_block(out, &ctor.body, expr_arena, pool, ctx, &mut state);
+
+    if !state.is_terminated() {
+        emit_return(out, expr_arena, None, pool, ctx, &mut state);
     }
-    out.push('\n');
 
-    out.push_str(&format!("define i32 @{}() {{\n", func.name));
+    out.push_str("}\n\n");
+}
 
+/// A method is a function that takes its receiver as the first argument,
+/// unless it is static.
+fn emit_method(out: &mut String, owner: &Type, method: &FuncDef, expr_arena: &ExprArena, pool: &StrPool, ctx: &FuncCtx) {
     let mut state = EmitState::new();
 
-    for stmt in func.body.stmts() {
-        match stmt {
-            Stmt::VarDecl(v) => emit_vardecl(out, v, expr_arena, &func.name, &ctx, &mut state),
-            Stmt::Println(expr_id) => emit_println(out, expr_arena, *expr_id, &func.name, &ctx, &mut state),
-            Stmt::Unreachable => emit_unreachable(out, &mut state),
+    let ret_type = match &method.ret_type {
+        Some(ty) => LlvmType::from(ty).get_alloca_type(),
+        None => "void".to_string(),
+    };
+
+    let receiver = Param { _type: owner.clone(), name: "self".to_string(), variadic: false, span: method.span.clone() };
+    let params: Vec<Param> = iter::once(receiver).filter(|_| !method.is_static).chain(method.params.iter().cloned()).collect();
+
+    out.push_str(&format!(
+        "define {} {}({}) {{\n",
+        ret_type, mangle::method(&owner.to_string(), &method.name), emit_param_list(&params),
+    ));
+    emit_params(out, &params);
+
+    emit_block(out, &method.body, expr_arena, pool, ctx, &mut state);
+
+    if !state.is_terminated() {
+        emit_return(out, expr_arena, None, pool, ctx, &mut state);
+    }
+
+    out.push_str("}\n\n");
+}
+
+/// Allocates a zeroed object of class or enum `name` and returns the pointer
+/// to it.
+fn emit_alloc(out: &mut String, name: &str, state: &mut EmitState) -> String {
+    let _struct = mangle::object(name);
+    let end = state.next_reg();
+    let size = state.next_reg();
+    let mem = state.next_reg();
+    let object = state.next_reg();
+
+    out.push_str(&format!("  \
+           %r{end} = getelementptr {_struct}, {_struct}* null, i32 1\n  \
+           %r{size} = ptrtoint {_struct}* %r{end} to i64\n  \
+           %r{mem} = call i8* @calloc(i64 1, i64 %r{size})\n  \
+           %r{object} = bitcast i8* %r{mem} to {_struct}*\n\
+        ",
+        end = end, size = size, mem = mem, object = object, _struct = _struct,
+    ));
+
+    format!("%r{}", object)
+}
+
+fn emit_block(out: &mut String, block: &Block, expr_arena: &ExprArena, pool: &StrPool, ctx: &FuncCtx, state: &mut EmitState) {
+    for node in block.stmts() {
+        // the analyzer drops what follows a diverging statement only from
+        // the outermost block, not from the arms of a `switch`
+        if state.is_terminated() {
+            break;
+        }
+
+        match &node.stmt {
+            Stmt::VarDecl(v) => emit_vardecl(out, v, expr_arena, pool, ctx, state),
+            Stmt::Println(expr_id) => emit_println(out, expr_arena, *expr_id, pool, ctx, state),
+            Stmt::Unreachable(msg) => emit_unreachable(out, expr_arena, *msg, &node.span, pool, ctx, state),
+            Stmt::Return(expr_id) => emit_return(out, expr_arena, *expr_id, pool, ctx, state),
+            Stmt::Expr(expr_id) => {
+                emit_expr(out, expr_arena, *expr_id, pool, ctx, state);
+            }
+            Stmt::Assign(target, op, value) => {
+                emit_assign(out, expr_arena, *target, op.as_ref(), *value, &node.span, pool, ctx, state);
+            }
+            Stmt::Switch(scrutinee, cases) => {
+                let patterns = cases.iter().map(|case| &case.pattern).collect::<Vec<_>>();
+
+                emit_switch(out, expr_arena, *scrutinee, &patterns, pool, ctx, state, &mut |out, arm, state| {
+                    emit_block(out, &cases[arm].body, expr_arena, pool, ctx, state);
+                });
+            }
+            Stmt::If(..) | Stmt::While(..) | Stmt::For(..) | Stmt::Llvm(_) => unreachable!(),
         }
     }
+}
 
-    out.push_str("  \
-            ret i32 0\n\
-        }\n\
-    ");
+fn emit_signature(out: &mut String, func: &FuncDef) {
+    let ret_type = match &func.ret_type {
+        Some(ty) => LlvmType::from(ty).get_alloca_type(),
+        None => "void".to_string(),
+    };
+
+    out.push_str(&format!("define {} {}({}) {{\n", ret_type, mangle::func(&func.name), emit_param_list(&func.params)));
+    emit_params(out, &func.params);
 }
 
-fn emit_unreachable(out: &mut String, state: &mut EmitState) {
-    let reg = state.next_reg();
+fn emit_param_list(params: &[Param]) -> String {
+    params.iter()
+        .map(|p| format!("{} {}", LlvmType::from(&p._type).get_alloca_type(), mangle::param(&p.name)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Copies every parameter into a stack slot, where it can be reassigned.
+fn emit_params(out: &mut String, params: &[Param]) {
+    for param in params {
+        let _type = LlvmType::from(&param._type).get_alloca_type();
+
+        out.push_str(&format!("  \
+               {name} = alloca {_type}\n  \
+               store {_type} {param}, {_type}* {name}\n\
+            ",
+            name = mangle::local(&param.name), _type = _type, param = mangle::param(&param.name),
+        ));
+    }
+}
+
+/// `main` keeps the C signature: `main()` or `main(str[] args)`, where `args`
+/// wraps `argc`/`argv` (including the program name) into a `str[]`.
+fn emit_entry_signature(out: &mut String, func: &FuncDef, state: &mut EmitState) {
+    let Some(param) = func.params.first() else {
+        out.push_str(&format!("define i32 {}() {{\n", mangle::func(&func.name)));
+        return;
+    };
+
+    let _type = LlvmType::from(&param._type).get_alloca_type();
+    let len = state.next_reg();
+    let partial = state.next_reg();
+    let args = state.next_reg();
+
+    out.push_str(&format!("\
+           define i32 {fn_name}(i32 %argc, i8** %argv) {{\n  \
+           %r{len} = sext i32 %argc to i64\n  \
+           %r{partial} = insertvalue {_type} undef, i64 %r{len}, 0\n  \
+           %r{args} = insertvalue {_type} %r{partial}, i8** %argv, 1\n  \
+           {name} = alloca {_type}\n  \
+           store {_type} %r{args}, {_type}* {name}\n\
+        ",
+        fn_name = mangle::func(&func.name), len = len, partial = partial, args = args,
+        _type = _type, name = mangle::local(&param.name),
+    ));
+}
+
+fn emit_return(
+    out: &mut String,
+    arena: &ExprArena,
+    expr_id: Option<ExprId>,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) {
+    // the returned value may open blocks of its own, e.g. for overflow checks
+    let Some(expr_id) = expr_id else {
+        if let Some(class) = ctx.ctor_of() {
+            let _type = LlvmType::Object(class.to_string()).get_alloca_type();
+            let reg = state.next_reg();
+
+            out.push_str(&format!("  \
+                   %r{reg} = load {_type}, {_type}* {name}\n  \
+                   ret {_type} %r{reg}\n\
+                ",
+                reg = reg, _type = _type, name = mangle::local("self"),
+            ));
+        } else if ctx.is_entry_point() {
+            out.push_str("  ret i32 0\n");
+        } else {
+            out.push_str("  ret void\n");
+        }
+        state.terminate();
+        return;
+    };
+
+    let (val, _type) = emit_expr(out, arena, expr_id, pool, ctx, state);
+
+    if ctx.is_entry_point() {
+        let reg = state.next_reg();
+
+        out.push_str(&format!("  \
+               %r{reg} = trunc i64 {val} to i32\n  \
+               ret i32 %r{reg}\n\
+            ",
+            reg = reg, val = val,
+        ));
+        state.terminate();
+        return;
+    }
+
+    out.push_str(&format!("  ret {} {}\n", _type.get_alloca_type(), val));
+    state.terminate();
+}
+
+fn emit_unreachable(
+    out: &mut String,
+    arena: &ExprArena,
+    msg: Option<ExprId>,
+    span: &Span,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) {
+    let msg = match msg {
+        Some(expr_id) => {
+            let prefix = emit_str(out, runtime::UNREACHABLE_MSG_PREFIX, pool, state);
+            let (value, _) = emit_expr(out, arena, expr_id, pool, ctx, state);
+            let reg = state.next_reg();
+
+            out.push_str(&format!(
+                "  %r{reg} = call i8* @lit.str.concat(i8* {prefix}, i8* {value})\n",
+                reg = reg, prefix = prefix, value = value,
+            ));
+
+            format!("%r{}", reg)
+        }
+        None => emit_str(out, runtime::UNREACHABLE_MSG, pool, state),
+    };
+
+    emit_abort(out, &msg, span, pool, ctx, state);
+}
+
+fn emit_abort(out: &mut String, msg: &str, span: &Span, pool: &StrPool, ctx: &FuncCtx, state: &mut EmitState) {
+    let source = ctx.get_source(span);
+    let file = emit_str(out, &source.name, pool, state);
+    let (line, col) = source.line_col(span);
+
     out.push_str(&format!("  \
-            %r{reg} = getelementptr inbounds [29 x i8], [29 x i8]* @unreachable_msg, i32 0, i32 0\n  \
-            call i32 @puts(i8* %r{reg})\n  \
-            call void @exit(i32 1)\n  \
+            call void @lit.abort(i8* {file}, i64 {line}, i64 {col}, i8* {msg})\n  \
             unreachable\n\
         ",
-        reg = reg,
+        file = file, line = line, col = col, msg = msg,
     ));
+
+    state.terminate();
 }
 
 fn emit_vardecl(
     out: &mut String,
     v: &VarDecl,
     arena: &ExprArena,
-    fn_name: &str,
+    pool: &StrPool,
     ctx: &FuncCtx,
     state: &mut EmitState,
 ) {
-    let llvm_type = infer_llvm_type(arena, v.expr_id, ctx.get_var_types());
+    let llvm_type = infer_llvm_type(arena, v.expr_id, ctx);
     let alloca_type = llvm_type.get_alloca_type();
 
-    let val = emit_expr(out, arena, v.expr_id, fn_name, ctx, state).0;
+    let val = emit_expr(out, arena, v.expr_id, pool, ctx, state).0;
 
     out.push_str(&format!("  \
-           %{name} = alloca {_type}\n  \
-           store {_type} {val}, {_type}* %{name}\n\
+           {name} = alloca {_type}\n  \
+           store {_type} {val}, {_type}* {name}\n\
         ",
-        name = v.name, _type = alloca_type, val = val,
+        name = ctx.get_decl(v).0, _type = alloca_type, val = val,
     ));
 }
 
@@ -97,12 +408,12 @@ fn emit_println(
     out: &mut String,
     arena: &ExprArena,
     expr_id: ExprId,
-    fn_name: &str,
+    pool: &StrPool,
     ctx: &FuncCtx,
     state: &mut EmitState
 ) {
-    let _type = infer_llvm_type(arena, expr_id, ctx.get_var_types());
-    let val = emit_expr(out, arena, expr_id, fn_name, ctx, state).0;
+    let _type = infer_llvm_type(arena, expr_id, ctx);
+    let val = emit_expr(out, arena, expr_id, pool, ctx, state).0;
 
     match _type {
         LlvmType::I64Unsigned => {
@@ -171,6 +482,8 @@ fn emit_println(
                 val = val
             ));
         }
+
+        LlvmType::Array(_) | LlvmType::Object(_) | LlvmType::Void => unreachable!(),
     }
 }
 
@@ -178,7 +491,7 @@ fn emit_expr(
     out: &mut String,
     arena: &ExprArena,
     id: ExprId,
-    fn_name: &str,
+    pool: &StrPool,
     ctx: &FuncCtx,
     state: &mut EmitState
 ) -> (String, LlvmType) {
@@ -192,42 +505,39 @@ fn emit_expr(
         Expr::Lit(Float(f)) => (format!("{:.6e}", f), LlvmType::Double),
         Expr::Lit(Bool(b)) => ((*b as i32).to_string(), LlvmType::I1),
         Expr::Lit(Char(c)) => ((*c as i32).to_string(), LlvmType::Char),
-        Expr::Lit(Str(s)) => {
-            let b = s.len() + 1;
-            let si = state.next_str_idx();
-            let reg = state.next_reg();
-
-            out.push_str(&format!(
-                "  %r{reg} = getelementptr inbounds [{b} x i8], [{b} x i8]* @str.{fn_name}.{si}, i32 0, i32 0\n",
-                reg = reg, b = b, fn_name = fn_name, si = si,
-            ));
-            (format!("%r{}", reg), LlvmType::I8Ptr)
-        }
+        Expr::Lit(Str(s)) => (emit_str(out, s, pool, state), LlvmType::I8Ptr),
 
-        Expr::Var(name) => {
-            let _type = infer_llvm_type(arena, id, ctx.get_var_types());
+        Expr::Var(_) => {
+            let _type = infer_llvm_type(arena, id, ctx);
             let llvm_type = _type.get_alloca_type();
             let reg = state.next_reg();
 
             out.push_str(&format!(
-                "  %r{reg} = load {_type}, {_type}* %{name}\n",
-                reg = reg, _type = llvm_type, name = name,
+                "  %r{reg} = load {_type}, {_type}* {name}\n",
+                reg = reg, _type = llvm_type, name = ctx.get_local(id).0,
             ));
             (format!("%r{}", reg), _type)
         }
 
+        Expr::Const(name) => emit_expr(out, arena, ctx.get_const(name), pool, ctx, state),
+
         Expr::Binary (op, left, right) => {
-            let (l_value, l_type) = emit_expr(out, arena, *left, fn_name, ctx, state);
-            let (r_value, _) = emit_expr(out, arena, *right, fn_name, ctx, state);
+            let (l_value, l_type) = emit_expr(out, arena, *left, pool, ctx, state);
+            let (r_value, _) = emit_expr(out, arena, *right, pool, ctx, state);
 
-            let instr = llvm_instr_for_operator_by_type(op, &l_type);
-            let llvm_type = l_type.get_alloca_type();
-            let reg = state.next_reg();
+            if let BinaryOp::Add = op && l_type == LlvmType::I8Ptr {
+                let reg = state.next_reg();
 
-            out.push_str(&format!(
-                "  %r{reg} = {instr} {_type} {l_value}, {r_value}\n",
-                reg = reg, instr = instr, _type = llvm_type, l_value = l_value, r_value = r_value,
-            ));
+                out.push_str(&format!(
+                    "  %r{reg} = call i8* @lit.str.concat(i8* {l_value}, i8* {r_value})\n",
+                    reg = reg, l_value = l_value, r_value = r_value,
+                ));
+
+                return (format!("%r{}", reg), LlvmType::I8Ptr);
+            }
+
+            let check_span = ctx.has_checks().then_some(&expr_node.span);
+            let value = emit_binary(out, op, &l_value, &r_value, &l_type, check_span, pool, ctx, state);
 
             let final_type = if op.is_comparison() || op.is_arranging() {
                 LlvmType::I1
@@ -235,11 +545,18 @@ fn emit_expr(
                 l_type
             };
 
-            (format!("%r{}", reg), final_type)
+            (value, final_type)
         }
 
         Expr::Unary (op, expr) => {
-            let (value, _type) = emit_expr(out, arena, *expr, fn_name, ctx, state);
+            let (value, _type) = emit_expr(out, arena, *expr, pool, ctx, state);
+
+            if let UnaryOp::Minus = op && _type == LlvmType::I64Signed && ctx.has_checks() {
+                let (result, overflow) = emit_with_overflow(out, "llvm.ssub.with.overflow.i64", "0", &value, state);
+                emit_check(out, &overflow, runtime::NEG_OVERFLOW_MSG, &expr_node.span, pool, ctx, state);
+
+                return (result, _type);
+            }
 
             let (instr, literal) = llvm_instr_and_literal_for_unary_operator_by_type(op, &_type);
             let llvm_type = _type.get_alloca_type();
@@ -254,7 +571,7 @@ fn emit_expr(
         }
 
         Expr::Cast (to, expr) => {
-            let (value, from_type) = emit_expr(out, arena, *expr, fn_name, ctx, state);
+            let (value, from_type) = emit_expr(out, arena, *expr, pool, ctx, state);
 
             let to_type = LlvmType::from(to);
 
@@ -275,9 +592,857 @@ fn emit_expr(
 
             (format!("%r{}", reg), to_type)
         }
+
+        Expr::Field (expr, name) => {
+            let (value, _type) = emit_expr(out, arena, *expr, pool, ctx, state);
+
+            if let LlvmType::Object(class) = &_type {
+                let (ptr, field_type) = emit_field_ptr(out, &value, class, name, ctx, state);
+                let reg = state.next_reg();
+
+                out.push_str(&format!(
+                    "  %r{reg} = load {_type}, {_type}* {ptr}\n",
+                    reg = reg, _type = field_type.get_alloca_type(), ptr = ptr,
+                ));
+
+                return (format!("%r{}", reg), field_type);
+            }
+
+            let reg = state.next_reg();
+
+            if let LlvmType::Array(_) = _type {
+                out.push_str(&format!(
+                    "  %r{reg} = extractvalue {_type} {value}, 0\n",
+                    reg = reg, _type = _type.get_alloca_type(), value = value,
+                ));
+            } else {
+                out.push_str(&format!(
+                    "  %r{reg} = call i64 @strlen(i8* {value})\n",
+                    reg = reg, value = value,
+                ));
+            }
+
+            (format!("%r{}", reg), LlvmType::I64Unsigned)
+        }
+
+        Expr::Index (..) => {
+            let (ptr, elem_type) = emit_elem_ptr(out, arena, id, pool, ctx, state);
+            let elem = elem_type.get_alloca_type();
+            let reg = state.next_reg();
+
+            out.push_str(&format!(
+                "  %r{reg} = load {elem}, {elem}* {ptr}\n",
+                reg = reg, elem = elem, ptr = ptr,
+            ));
+
+            (format!("%r{}", reg), elem_type)
+        }
+
+        Expr::Call (name, _, args) => match Builtin::from_name(name) {
+            Some(Builtin::Panic) => {
+                let prefix = emit_str(out, runtime::PANIC_MSG_PREFIX, pool, state);
+                let (value, _) = emit_expr(out, arena, args[0], pool, ctx, state);
+                let reg = state.next_reg();
+
+                out.push_str(&format!(
+                    "  %r{reg} = call i8* @lit.str.concat(i8* {prefix}, i8* {value})\n",
+                    reg = reg, prefix = prefix, value = value,
+                ));
+
+                emit_abort(out, &format!("%r{}", reg), &expr_node.span, pool, ctx, state);
+
+                (String::new(), LlvmType::Void)
+            }
+
+            Some(Builtin::Assert) => {
+                emit_assert(out, arena, args, &expr_node.span, pool, ctx, state);
+
+                (String::new(), LlvmType::Void)
+            }
+
+            Some(builtin) => {
+                let op = builtin.arith_op().unwrap();
+                let (l_value, _type) = emit_expr(out, arena, args[0], pool, ctx, state);
+                let (r_value, _) = emit_expr(out, arena, args[1], pool, ctx, state);
+
+                let check_span = builtin.is_checked().then_some(&expr_node.span);
+                let value = emit_binary(out, &op, &l_value, &r_value, &_type, check_span, pool, ctx, state);
+
+                (value, _type)
+            }
+
+            None => emit_call(out, arena, name, args, pool, ctx, state),
+        }
+
+        Expr::Construct (name, ctor, args) => {
+            let values = emit_args(out, arena, args, pool, ctx, state);
+            let _type = LlvmType::Object(name.clone());
+            let reg = state.next_reg();
+
+            out.push_str(&format!(
+                "  %r{reg} = call {_type} {ctor}({values})\n",
+                reg = reg, _type = _type.get_alloca_type(), ctor = mangle::ctor(name, *ctor), values = values,
+            ));
+
+            (format!("%r{}", reg), _type)
+        }
+
+        Expr::Variant (_enum, name, args) => {
+            let (tag, variant) = ctx.get_enum(_enum).variant(name).unwrap();
+            let _struct = mangle::object(_enum);
+            let object = emit_alloc(out, _enum, state);
+            let tag_ptr = state.next_reg();
+
+            out.push_str(&format!("  \
+                   %r{tag_ptr} = getelementptr inbounds {_struct}, {_struct}* {object}, i32 0, i32 0\n  \
+                   store i64 {tag}, i64* %r{tag_ptr}\n\
+                ",
+                tag_ptr = tag_ptr, _struct = _struct, object = object, tag = tag,
+            ));
+
+            if !args.is_empty() {
+                let fields = variant.fields.iter().map(LlvmType::from).collect::<Vec<_>>();
+                let payload = emit_payload_ptr(out, _enum, &object, &fields, ctx, state);
+                let payload_type = payload_type(&fields);
+
+                for (i, (arg, field)) in args.iter().zip(&fields).enumerate() {
+                    let (value, _) = emit_expr(out, arena, *arg, pool, ctx, state);
+                    let field_type = field.get_alloca_type();
+                    let ptr = state.next_reg();
+
+                    out.push_str(&format!("  \
+                           %r{ptr} = getelementptr inbounds {payload_type}, {payload_type}* {payload}, i32 0, i32 {i}\n  \
+                           store {field_type} {value}, {field_type}* %r{ptr}\n\
+                        ",
+                        ptr = ptr, payload_type = payload_type, payload = payload, i = i, field_type = field_type, value = value,
+                    ));
+                }
+            }
+
+            (object, LlvmType::Object(_enum.clone()))
+        }
+
+        Expr::Invoke (owner, name, receiver, args) => {
+            let (receiver, receiver_type) = emit_expr(out, arena, *receiver, pool, ctx, state);
+            let values = emit_args(out, arena, args, pool, ctx, state);
+
+            let values = match values.is_empty() {
+                true => format!("{} {}", receiver_type.get_alloca_type(), receiver),
+                false => format!("{} {}, {}", receiver_type.get_alloca_type(), receiver, values),
+            };
+
+            emit_method_call(out, owner, name, &values, ctx, state)
+        }
+
+        Expr::Associated (owner, name, args) => {
+            let values = emit_args(out, arena, args, pool, ctx, state);
+
+            emit_method_call(out, owner, name, &values, ctx, state)
+        }
+
+        // every arm stores its value into one slot, which is read after the
+        // switch; arms that `panic` store nothing
+        Expr::Switch (scrutinee, arms) => {
+            let _type = infer_llvm_type(arena, id, ctx);
+            let alloca_type = _type.get_alloca_type();
+
+            let result = (_type != LlvmType::Void).then(|| {
+                let reg = state.next_reg();
+                out.push_str(&format!("  %r{} = alloca {}\n", reg, alloca_type));
+                format!("%r{}", reg)
+            });
+
+            let patterns = arms.iter().map(|arm| &arm.pattern).collect::<Vec<_>>();
+
+            emit_switch(out, arena, *scrutinee, &patterns, pool, ctx, state, &mut |out, arm, state| {
+                let (value, arm_type) = emit_expr(out, arena, arms[arm].expr, pool, ctx, state);
+
+                if let Some(result) = &result && !state.is_terminated() && arm_type != LlvmType::Void {
+                    out.push_str(&format!(
+                        "  store {_type} {value}, {_type}* {result}\n",
+                        _type = alloca_type, value = value, result = result,
+                    ));
+                }
+            });
+
+            let Some(result) = result.filter(|_| !state.is_terminated()) else {
+                return (String::new(), LlvmType::Void);
+            };
+
+            let reg = state.next_reg();
+
+            out.push_str(&format!(
+                "  %r{reg} = load {_type}, {_type}* {result}\n",
+                reg = reg, _type = alloca_type, result = result,
+            ));
+
+            (format!("%r{}", reg), _type)
+        }
+
+        Expr::MethodCall(..) | Expr::Null => unreachable!(),
+    }
+}
+
+/// Emits a switch on `scrutinee`, whose arms have `patterns`, and has
+/// `emit_arm` emit the body of the arm that matches. `unt`, `int` and `char`
+/// values are matched against literal patterns with a single LLVM `switch`;
+/// any other value is tested against the patterns one after another.
+/// Control goes on after the switch in a fresh block, unless no arm reaches
+/// its end.
+#[allow(clippy::too_many_arguments)]
+fn emit_switch(
+    out: &mut String,
+    arena: &ExprArena,
+    scrutinee: ExprId,
+    patterns: &[&Pattern],
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+    emit_arm: &mut dyn FnMut(&mut String, usize, &mut EmitState),
+) {
+    let (value, _type) = emit_expr(out, arena, scrutinee, pool, ctx, state);
+    let label = state.next_label();
+    let mut reaches_end = false;
+
+    let mut finish_arm = |out: &mut String, state: &mut EmitState| {
+        if !state.is_terminated() {
+            out.push_str(&format!("  br label %switch.end.{}\n", label));
+            state.terminate();
+            reaches_end = true;
+        }
+    };
+
+    if let LlvmType::I64Unsigned | LlvmType::I64Signed | LlvmType::Char = _type {
+        // arms past the first one that is not a literal are never chosen,
+        // and neither are repeated literals
+        let mut cases: Vec<(String, usize)> = Vec::new();
+        let mut default = None;
+
+        for (arm, pattern) in patterns.iter().enumerate() {
+            let case = match pattern {
+                Pattern::Lit(Lit::Unt(u), _) => (*u as i64).to_string(),
+                Pattern::Lit(Lit::Int(i), _) => i.to_string(),
+                Pattern::Lit(Lit::Char(c), _) => (*c as i32).to_string(),
+                _ => {
+                    default = Some(arm);
+                    break;
+                }
+            };
+
+            if !cases.iter().any(|(c, _)| *c == case) {
+                cases.push((case, arm));
+            }
+        }
+
+        let default_label = match default {
+            Some(arm) => format!("switch.arm.{}.{}", label, arm),
+            None => format!("switch.none.{}", label),
+        };
+
+        let llvm_type = _type.get_alloca_type();
+        out.push_str(&format!("  switch {} {}, label %{} [\n", llvm_type, value, default_label));
+
+        for (case, arm) in &cases {
+            out.push_str(&format!("    {} {}, label %switch.arm.{}.{}\n", llvm_type, case, label, arm));
+        }
+
+        out.push_str("  ]\n");
+
+        for arm in cases.iter().map(|(_, arm)| *arm).chain(default) {
+            out.push_str(&format!("switch.arm.{}.{}:\n", label, arm));
+            state.open_block();
+
+            // only the catch-all arm binds the value
+            if default == Some(arm) {
+                emit_pattern(out, scrutinee, arm, patterns[arm], &value, &_type, &default_label, pool, ctx, state);
+            }
+
+            emit_arm(out, arm, state);
+            finish_arm(out, state);
+        }
+
+        if default.is_none() {
+            out.push_str(&format!("{}:\n  unreachable\n", default_label));
+        }
+    } else {
+        for (arm, pattern) in patterns.iter().enumerate() {
+            let next = format!("switch.next.{}.{}", label, arm);
+
+            emit_pattern(out, scrutinee, arm, pattern, &value, &_type, &next, pool, ctx, state);
+            emit_arm(out, arm, state);
+            finish_arm(out, state);
+
+            out.push_str(&format!("{}:\n", next));
+            state.open_block();
+        }
+
+        // the analyzer has checked that some arm matches
+        out.push_str("  unreachable\n");
+    }
+
+    state.terminate();
+
+    if reaches_end {
+        out.push_str(&format!("switch.end.{}:\n", label));
+        state.open_block();
     }
 }
 
+/// Emits a test of `value`, of `_type`, against `pattern`, the pattern of
+/// arm `arm` of the switch on `scrutinee`. The test branches to `fail` if
+/// the value does not match, and otherwise stores the values that the
+/// pattern binds and goes on in a fresh block.
+#[allow(clippy::too_many_arguments)]
+fn emit_pattern(
+    out: &mut String,
+    scrutinee: ExprId,
+    arm: usize,
+    pattern: &Pattern,
+    value: &str,
+    _type: &LlvmType,
+    fail: &str,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) {
+    let cond = match pattern {
+        Pattern::Wildcard(_) => return,
+
+        Pattern::Binding(name, _, _) => {
+            let (slot, _) = ctx.get_binding(scrutinee, arm, name);
+            let alloca_type = _type.get_alloca_type();
+
+            out.push_str(&format!("  \
+                   {slot} = alloca {_type}\n  \
+                   store {_type} {value}, {_type}* {slot}\n\
+                ",
+                slot = slot, _type = alloca_type, value = value,
+            ));
+            return;
+        }
+
+        Pattern::Lit(Lit::Str(s), _) => {
+            let lit = emit_str(out, s, pool, state);
+            let cmp = state.next_reg();
+            let cond = state.next_reg();
+
+            out.push_str(&format!("  \
+                   %r{cmp} = call i32 @strcmp(i8* {value}, i8* {lit})\n  \
+                   %r{cond} = icmp eq i32 %r{cmp}, 0\n\
+                ",
+                cmp = cmp, cond = cond, value = value, lit = lit,
+            ));
+
+            format!("%r{}", cond)
+        }
+
+        Pattern::Lit(lit, _) => {
+            let lit = match lit {
+                Lit::Unt(u) => (*u as i64).to_string(),
+                Lit::Int(i) => i.to_string(),
+                Lit::Float(f) => format!("{:.6e}", f),
+                Lit::Bool(b) => (*b as i32).to_string(),
+                Lit::Char(c) => (*c as i32).to_string(),
+                Lit::Str(_) => unreachable!(),
+            };
+
+            emit_binary(out, &BinaryOp::EqEq, value, &lit, _type, None, pool, ctx, state)
+        }
+
+        Pattern::Variant(name, _, _) => {
+            let (_enum, variant) = name.rsplit_once("::").unwrap();
+            let (tag, _) = ctx.get_enum(_enum).variant(variant).unwrap();
+            let _struct = mangle::object(_enum);
+            let tag_ptr = state.next_reg();
+            let tag_value = state.next_reg();
+            let cond = state.next_reg();
+
+            out.push_str(&format!("  \
+                   %r{tag_ptr} = getelementptr inbounds {_struct}, {_struct}* {value}, i32 0, i32 0\n  \
+                   %r{tag_value} = load i64, i64* %r{tag_ptr}\n  \
+                   %r{cond} = icmp eq i64 %r{tag_value}, {tag}\n\
+                ",
+                tag_ptr = tag_ptr, tag_value = tag_value, cond = cond, _struct = _struct, value = value, tag = tag,
+            ));
+
+            format!("%r{}", cond)
+        }
+
+        Pattern::Name(..) => unreachable!(),
+    };
+
+    let ok = state.next_label();
+
+    out.push_str(&format!("  \
+           br i1 {cond}, label %pattern.ok.{ok}, label %{fail}\n\
+           pattern.ok.{ok}:\n\
+        ",
+        cond = cond, ok = ok, fail = fail,
+    ));
+    state.open_block();
+
+    // the fields of a variant are tested once its tag matches
+    let Pattern::Variant(name, fields, _) = pattern else {
+        return;
+    };
+
+    if fields.iter().all(|field| matches!(field, Pattern::Wildcard(_))) {
+        return;
+    }
+
+    let (_enum, variant) = name.rsplit_once("::").unwrap();
+    let (_, def) = ctx.get_enum(_enum).variant(variant).unwrap();
+    let field_types = def.fields.iter().map(LlvmType::from).collect::<Vec<_>>();
+    let payload = emit_payload_ptr(out, _enum, value, &field_types, ctx, state);
+    let payload_type = payload_type(&field_types);
+
+    for (i, (field, field_type)) in fields.iter().zip(&field_types).enumerate() {
+        if let Pattern::Wildcard(_) = field {
+            continue;
+        }
+
+        let ptr = state.next_reg();
+        let field_value = state.next_reg();
+
+        out.push_str(&format!("  \
+               %r{ptr} = getelementptr inbounds {payload_type}, {payload_type}* {payload}, i32 0, i32 {i}\n  \
+               %r{field_value} = load {field_type}, {field_type}* %r{ptr}\n\
+            ",
+            ptr = ptr, field_value = field_value, payload_type = payload_type, payload = payload, i = i,
+            field_type = field_type.get_alloca_type(),
+        ));
+
+        emit_pattern(out, scrutinee, arm, field, &format!("%r{}", field_value), field_type, fail, pool, ctx, state);
+    }
+}
+
+/// The struct that the payload of a variant with `fields` is read as.
+fn payload_type(fields: &[LlvmType]) -> String {
+    let fields = fields.iter().map(LlvmType::get_alloca_type).collect::<Vec<_>>();
+    format!("{{ {} }}", fields.join(", "))
+}
+
+/// Emits the address of the payload of `object`, a value of `_enum`, as a
+/// pointer to the struct of `fields`.
+fn emit_payload_ptr(out: &mut String, _enum: &str, object: &str, fields: &[LlvmType], ctx: &FuncCtx, state: &mut EmitState) -> String {
+    let _struct = mangle::object(_enum);
+    let words = state.next_reg();
+    let ptr = state.next_reg();
+
+    out.push_str(&format!("  \
+           %r{words} = getelementptr inbounds {_struct}, {_struct}* {object}, i32 0, i32 1\n  \
+           %r{ptr} = bitcast [{size} x i64]* %r{words} to {payload_type}*\n\
+        ",
+        words = words, ptr = ptr, _struct = _struct, object = object,
+        size = payload_words(&ctx.get_enum(_enum).variants), payload_type = payload_type(fields),
+    ));
+
+    format!("%r{}", ptr)
+}
+
+/// Emits the address of field `name` of `object`, which points to an
+/// object of `class`.
+fn emit_field_ptr(out: &mut String, object: &str, class: &str, name: &str, ctx: &FuncCtx, state: &mut EmitState) -> (String, LlvmType) {
+    let (index, field) = ctx.get_class(class).field(name).unwrap();
+    let _struct = mangle::object(class);
+    let ptr = state.next_reg();
+
+    out.push_str(&format!(
+        "  %r{ptr} = getelementptr inbounds {_struct}, {_struct}* {object}, i32 0, i32 {index}\n",
+        ptr = ptr, _struct = _struct, object = object, index = index,
+    ));
+
+    (format!("%r{}", ptr), LlvmType::from(&field._type))
+}
+
+/// Emits the address of the element that index expression `id` picks from
+/// an array or a string. With checks, indexing a string aborts when the
+/// index is negative or not below its length.
+fn emit_elem_ptr(out: &mut String, arena: &ExprArena, id: ExprId, pool: &StrPool, ctx: &FuncCtx, state: &mut EmitState) -> (String, LlvmType) {
+    let node = arena.get(id);
+    let Expr::Index(expr, index) = &node.expr else { unreachable!() };
+
+    let (value, _type) = emit_expr(out, arena, *expr, pool, ctx, state);
+    let (index_value, _) = emit_expr(out, arena, *index, pool, ctx, state);
+
+    let (base, elem_type) = match _type {
+        LlvmType::Array(elem) => {
+            let data = state.next_reg();
+
+            out.push_str(&format!(
+                "  %r{data} = extractvalue {_type} {value}, 1\n",
+                data = data, _type = LlvmType::Array(elem.clone()).get_alloca_type(), value = value,
+            ));
+
+            (format!("%r{}", data), *elem)
+        }
+        _ => {
+            if ctx.has_checks() {
+                let len = state.next_reg();
+                let negative = state.next_reg();
+                let too_big = state.next_reg();
+                let failed = state.next_reg();
+
+                out.push_str(&format!("  \
+                       %r{len} = call i64 @strlen(i8* {value})\n  \
+                       %r{negative} = icmp slt i64 {index}, 0\n  \
+                       %r{too_big} = icmp sge i64 {index}, %r{len}\n  \
+                       %r{failed} = or i1 %r{negative}, %r{too_big}\n\
+                    ",
+                    len = len, negative = negative, too_big = too_big, failed = failed, value = value, index = index_value,
+                ));
+
+                emit_check(out, &format!("%r{}", failed), runtime::INDEX_OUT_OF_BOUNDS_MSG, &node.span, pool, ctx, state);
+            }
+
+            (value, LlvmType::Char)
+        }
+    };
+
+    let elem = elem_type.get_alloca_type();
+    let ptr = state.next_reg();
+
+    out.push_str(&format!(
+        "  %r{ptr} = getelementptr inbounds {elem}, {elem}* {base}, i64 {index}\n",
+        ptr = ptr, elem = elem, base = base, index = index_value,
+    ));
+
+    (format!("%r{}", ptr), elem_type)
+}
+
+/// Emits the address that assignment target `id` stores to: the slot of a
+/// variable, a field of an object or an element of an array.
+fn emit_place(out: &mut String, arena: &ExprArena, id: ExprId, pool: &StrPool, ctx: &FuncCtx, state: &mut EmitState) -> (String, LlvmType) {
+    match &arena.get(id).expr {
+        Expr::Var(_) => {
+            let (slot, _type) = ctx.get_local(id);
+            (slot.clone(), LlvmType::from(_type))
+        }
+
+        Expr::Field(expr, name) => {
+            let (object, _type) = emit_expr(out, arena, *expr, pool, ctx, state);
+            let LlvmType::Object(class) = _type else { unreachable!() };
+
+            emit_field_ptr(out, &object, &class, name, ctx, state)
+        }
+
+        Expr::Index(..) => emit_elem_ptr(out, arena, id, pool, ctx, state),
+
+        _ => unreachable!(),
+    }
+}
+
+/// Emits `target = value`, or `target op= value` when `op` is given.
+#[allow(clippy::too_many_arguments)]
+fn emit_assign(
+    out: &mut String,
+    arena: &ExprArena,
+    target: ExprId,
+    op: Option<&BinaryOp>,
+    value: ExprId,
+    span: &Span,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) {
+    let (ptr, _type) = emit_place(out, arena, target, pool, ctx, state);
+    let (mut value, _) = emit_expr(out, arena, value, pool, ctx, state);
+    let llvm_type = _type.get_alloca_type();
+
+    if let Some(op) = op {
+        let current = state.next_reg();
+
+        out.push_str(&format!(
+            "  %r{reg} = load {_type}, {_type}* {ptr}\n",
+            reg = current, _type = llvm_type, ptr = ptr,
+        ));
+
+        value = match (op, &_type) {
+            (BinaryOp::Add, LlvmType::I8Ptr) => {
+                let reg = state.next_reg();
+
+                out.push_str(&format!(
+                    "  %r{reg} = call i8* @lit.str.concat(i8* %r{current}, i8* {value})\n",
+                    reg = reg, current = current, value = value,
+                ));
+
+                format!("%r{}", reg)
+            }
+            _ => {
+                let check_span = ctx.has_checks().then_some(span);
+                emit_binary(out, op, &format!("%r{}", current), &value, &_type, check_span, pool, ctx, state)
+            }
+        };
+    }
+
+    out.push_str(&format!(
+        "  store {_type} {value}, {_type}* {ptr}\n",
+        _type = llvm_type, value = value, ptr = ptr,
+    ));
+}
+
+fn emit_call(
+    out: &mut String,
+    arena: &ExprArena,
+    name: &str,
+    args: &[ExprId],
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) -> (String, LlvmType) {
+    let sig = ctx.get_funcs().get(name).unwrap();
+    let values = emit_args(out, arena, args, pool, ctx, state);
+
+    let Some(ret_type) = &sig.ret_type else {
+        out.push_str(&format!("  call void {}({})\n", mangle::func(name), values));
+        return (String::new(), LlvmType::Void);
+    };
+
+    let ret_type = LlvmType::from(ret_type);
+    let reg = state.next_reg();
+
+    out.push_str(&format!(
+        "  %r{reg} = call {_type} {func}({values})\n",
+        reg = reg, _type = ret_type.get_alloca_type(), func = mangle::func(name), values = values,
+    ));
+
+    (format!("%r{}", reg), ret_type)
+}
+
+/// Emits every argument of a call and returns them as an argument list.
+fn emit_args(out: &mut String, arena: &ExprArena, args: &[ExprId], pool: &StrPool, ctx: &FuncCtx, state: &mut EmitState) -> String {
+    args.iter()
+        .map(|arg| {
+            let (value, _type) = emit_expr(out, arena, *arg, pool, ctx, state);
+            format!("{} {}", _type.get_alloca_type(), value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Emits a call of method `name` of `owner` with the LLVM argument list
+/// `values`, which starts with the receiver unless the method is static.
+fn emit_method_call(out: &mut String, owner: &str, name: &str, values: &str, ctx: &FuncCtx, state: &mut EmitState) -> (String, LlvmType) {
+    let method = mangle::method(owner, name);
+
+    let Some(ret_type) = &ctx.get_method(owner, name).sig.ret_type else {
+        out.push_str(&format!("  call void {}({})\n", method, values));
+        return (String::new(), LlvmType::Void);
+    };
+
+    let ret_type = LlvmType::from(ret_type);
+    let reg = state.next_reg();
+
+    out.push_str(&format!(
+        "  %r{reg} = call {_type} {method}({values})\n",
+        reg = reg, _type = ret_type.get_alloca_type(), method = method, values = values,
+    ));
+
+    (format!("%r{}", reg), ret_type)
+}
+
+/// Emits `left op right`. When `check_span` is given, integer arithmetic
+/// aborts with that location instead of overflowing or dividing by zero.
+#[allow(clippy::too_many_arguments)]
+fn emit_binary(
+    out: &mut String,
+    op: &BinaryOp,
+    left: &str,
+    right: &str,
+    _type: &LlvmType,
+    check_span: Option<&Span>,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) -> String {
+    if let Some(span) = check_span && _type.is_integer() {
+        let signed = *_type == LlvmType::I64Signed;
+
+        match op {
+            BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul => {
+                let (intrinsic, msg) = match (op, signed) {
+                    (BinaryOp::Add, true) => ("llvm.sadd.with.overflow.i64", runtime::ADD_OVERFLOW_MSG),
+                    (BinaryOp::Add, false) => ("llvm.uadd.with.overflow.i64", runtime::ADD_OVERFLOW_MSG),
+                    (BinaryOp::Sub, true) => ("llvm.ssub.with.overflow.i64", runtime::SUB_OVERFLOW_MSG),
+                    (BinaryOp::Sub, false) => ("llvm.usub.with.overflow.i64", runtime::SUB_OVERFLOW_MSG),
+                    (_, true) => ("llvm.smul.with.overflow.i64", runtime::MUL_OVERFLOW_MSG),
+                    (_, false) => ("llvm.umul.with.overflow.i64", runtime::MUL_OVERFLOW_MSG),
+                };
+
+                let (result, overflow) = emit_with_overflow(out, intrinsic, left, right, state);
+                emit_check(out, &overflow, msg, span, pool, ctx, state);
+
+                return result;
+            }
+
+            BinaryOp::Div | BinaryOp::Mod => {
+                let (zero_msg, overflow_msg) = match op {
+                    BinaryOp::Div => (runtime::DIV_BY_ZERO_MSG, runtime::DIV_OVERFLOW_MSG),
+                    _ => (runtime::REM_BY_ZERO_MSG, runtime::REM_OVERFLOW_MSG),
+                };
+
+                let zero = state.next_reg();
+                out.push_str(&format!("  %r{zero} = icmp eq i64 {right}, 0\n", zero = zero, right = right));
+                emit_check(out, &format!("%r{}", zero), zero_msg, span, pool, ctx, state);
+
+                // `int.min / -1` is the one signed quotient that does not fit
+                if signed {
+                    let is_min = state.next_reg();
+                    let is_neg_one = state.next_reg();
+                    let overflow = state.next_reg();
+
+                    out.push_str(&format!("  \
+                           %r{is_min} = icmp eq i64 {left}, {min}\n  \
+                           %r{is_neg_one} = icmp eq i64 {right}, -1\n  \
+                           %r{overflow} = and i1 %r{is_min}, %r{is_neg_one}\n\
+                        ",
+                        is_min = is_min, is_neg_one = is_neg_one, overflow = overflow,
+                        left = left, right = right, min = i64::MIN,
+                    ));
+                    emit_check(out, &format!("%r{}", overflow), overflow_msg, span, pool, ctx, state);
+                }
+            }
+
+            BinaryOp::LShift | BinaryOp::RShift => {
+                let msg = match op {
+                    BinaryOp::LShift => runtime::SHL_OVERFLOW_MSG,
+                    _ => runtime::SHR_OVERFLOW_MSG,
+                };
+
+                let too_wide = state.next_reg();
+                out.push_str(&format!("  %r{reg} = icmp uge i64 {right}, 64\n", reg = too_wide, right = right));
+                emit_check(out, &format!("%r{}", too_wide), msg, span, pool, ctx, state);
+            }
+
+            _ => {}
+        }
+    }
+
+    // strings are equal by content; `+` makes new ones, so their pointers
+    // say nothing
+    if *_type == LlvmType::I8Ptr && let BinaryOp::EqEq | BinaryOp::NotEq = op {
+        let cmp = state.next_reg();
+        let reg = state.next_reg();
+        let cond = if let BinaryOp::EqEq = op { "eq" } else { "ne" };
+
+        out.push_str(&format!("  \
+               %r{cmp} = call i32 @strcmp(i8* {left}, i8* {right})\n  \
+               %r{reg} = icmp {cond} i32 %r{cmp}, 0\n\
+            ",
+            cmp = cmp, reg = reg, cond = cond, left = left, right = right,
+        ));
+
+        return format!("%r{}", reg);
+    }
+
+    let instr = llvm_instr_for_operator_by_type(op, _type);
+    let reg = state.next_reg();
+
+    out.push_str(&format!(
+        "  %r{reg} = {instr} {_type} {left}, {right}\n",
+        reg = reg, instr = instr, _type = _type.get_alloca_type(), left = left, right = right,
+    ));
+
+    format!("%r{}", reg)
+}
+
+/// Calls an `llvm.*.with.overflow` intrinsic, returning the wrapped result
+/// and the overflow bit.
+fn emit_with_overflow(out: &mut String, intrinsic: &str, left: &str, right: &str, state: &mut EmitState) -> (String, String) {
+    let pair = state.next_reg();
+    let result = state.next_reg();
+    let overflow = state.next_reg();
+
+    out.push_str(&format!("  \
+           %r{pair} = call {{ i64, i1 }} @{intrinsic}(i64 {left}, i64 {right})\n  \
+           %r{result} = extractvalue {{ i64, i1 }} %r{pair}, 0\n  \
+           %r{overflow} = extractvalue {{ i64, i1 }} %r{pair}, 1\n\
+        ",
+        pair = pair, result = result, overflow = overflow, intrinsic = intrinsic, left = left, right = right,
+    ));
+
+    (format!("%r{}", result), format!("%r{}", overflow))
+}
+
+/// Aborts with `msg` when `failed` is set, otherwise continues in a fresh block.
+fn emit_check(
+    out: &mut String,
+    failed: &str,
+    msg: &str,
+    span: &Span,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) {
+    let label = state.next_label();
+
+    out.push_str(&format!("  \
+           br i1 {failed}, label %check.fail.{label}, label %check.ok.{label}\n\
+           check.fail.{label}:\n\
+        ",
+        failed = failed, label = label,
+    ));
+
+    let msg = emit_str(out, msg, pool, state);
+    emit_abort(out, &msg, span, pool, ctx, state);
+
+    out.push_str(&format!("check.ok.{}:\n", label));
+    state.open_block();
+}
+
+fn emit_assert(
+    out: &mut String,
+    arena: &ExprArena,
+    args: &[ExprId],
+    span: &Span,
+    pool: &StrPool,
+    ctx: &FuncCtx,
+    state: &mut EmitState,
+) {
+    let (cond, _) = emit_expr(out, arena, args[0], pool, ctx, state);
+    let label = state.next_label();
+
+    out.push_str(&format!("  \
+           br i1 {cond}, label %assert.ok.{label}, label %assert.fail.{label}\n\
+           assert.fail.{label}:\n\
+        ",
+        cond = cond, label = label,
+    ));
+
+    let msg = match args.get(1) {
+        Some(msg) => {
+            let prefix = emit_str(out, runtime::ASSERT_MSG_PREFIX, pool, state);
+            let (value, _) = emit_expr(out, arena, *msg, pool, ctx, state);
+            let reg = state.next_reg();
+
+            out.push_str(&format!(
+                "  %r{reg} = call i8* @lit.str.concat(i8* {prefix}, i8* {value})\n",
+                reg = reg, prefix = prefix, value = value,
+            ));
+
+            format!("%r{}", reg)
+        }
+        None => emit_str(out, runtime::ASSERT_MSG, pool, state),
+    };
+
+    emit_abort(out, &msg, span, pool, ctx, state);
+
+    out.push_str(&format!("assert.ok.{}:\n", label));
+    state.open_block();
+}
+
+fn emit_str(out: &mut String, s: &str, pool: &StrPool, state: &mut EmitState) -> String {
+    let (symbol, b) = pool.get(s);
+    let reg = state.next_reg();
+
+    out.push_str(&format!(
+        "  %r{reg} = getelementptr inbounds [{b} x i8], [{b} x i8]* {symbol}, i32 0, i32 0\n",
+        reg = reg, b = b, symbol = symbol,
+    ));
+
+    format!("%r{}", reg)
+}
+
 fn llvm_instr_for_operator_by_type(op: &BinaryOp, llvm_type: &LlvmType) -> &'static str {
     match (op, llvm_type) {
         (BinaryOp::Add, LlvmType::I64Unsigned | LlvmType::I64Signed) => "add",
@@ -364,7 +1529,7 @@ fn llvm_instr_for_cast(from: &LlvmType, to: &LlvmType) -> &'static str {
     }
 }
 
-pub fn infer_llvm_type(arena: &ExprArena, id: ExprId, var_types: &HashMap<String, Type>) -> LlvmType {
+pub fn infer_llvm_type(arena: &ExprArena, id: ExprId, ctx: &FuncCtx) -> LlvmType {
     let expr_node = arena.get(id);
     let expr = &expr_node.expr;
 
@@ -376,20 +1541,52 @@ pub fn infer_llvm_type(arena: &ExprArena, id: ExprId, var_types: &HashMap<String
         Expr::Lit(Lit::Char(_)) => LlvmType::Char,
         Expr::Lit(Lit::Str(_)) => LlvmType::I8Ptr,
 
-        Expr::Var(name) => {
-            LlvmType::from(var_types.get(name).unwrap())
-        }
+        Expr::Var(_) => LlvmType::from(&ctx.get_local(id).1),
+
+        Expr::Const(name) => infer_llvm_type(arena, ctx.get_const(name), ctx),
 
         Expr::Binary (op, left, _) => {
             if op.is_comparison() || op.is_arranging() {
                 LlvmType::I1
             } else {
-                infer_llvm_type(arena, *left, var_types)
+                infer_llvm_type(arena, *left, ctx)
             }
         },
 
-        Expr::Unary (_, expr) => infer_llvm_type(arena, *expr, var_types),
+        Expr::Unary (_, expr) => infer_llvm_type(arena, *expr, ctx),
 
         Expr::Cast (to, _) => LlvmType::from(to),
+
+        Expr::Field (expr, name) => match infer_llvm_type(arena, *expr, ctx) {
+            LlvmType::Object(class) => LlvmType::from(&ctx.get_class(&class).field(name).unwrap().1._type),
+            _ => LlvmType::I64Unsigned,
+        },
+
+        Expr::Index (expr, _) => match infer_llvm_type(arena, *expr, ctx) {
+            LlvmType::Array(elem) => *elem,
+            _ => LlvmType::Char,
+        },
+
+        Expr::Call (name, _, args) => match Builtin::from_name(name) {
+            Some(builtin) if builtin.arith_op().is_some() => infer_llvm_type(arena, args[0], ctx),
+            _ => match ctx.get_funcs().get(name).and_then(|sig| sig.ret_type.as_ref()) {
+                Some(ret_type) => LlvmType::from(ret_type),
+                None => LlvmType::Void,
+            },
+        },
+
+        Expr::Construct (name, _, _) | Expr::Variant (name, _, _) => LlvmType::Object(name.clone()),
+
+        Expr::Invoke (owner, name, _, _) | Expr::Associated (owner, name, _) => match &ctx.get_method(owner, name).sig.ret_type {
+            Some(ty) => LlvmType::from(ty),
+            None => LlvmType::Void,
+        },
+
+        Expr::Switch (_, arms) => arms.iter()
+            .map(|arm| infer_llvm_type(arena, arm.expr, ctx))
+            .find(|_type| *_type != LlvmType::Void)
+            .unwrap_or(LlvmType::Void),
+
+        Expr::MethodCall(..) | Expr::Null => unreachable!(),
     }
 }
\ No newline at end of file
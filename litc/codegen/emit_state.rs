@@ -1,12 +1,13 @@
 pub struct EmitState {
     reg: usize,
-    str_idx: usize,
+    label: usize,
+    terminated: bool,
 }
 
 impl EmitState {
     #[inline]
     pub fn new() -> EmitState {
-        EmitState { reg: 0, str_idx: 0 }
+        EmitState { reg: 0, label: 0, terminated: false }
     }
 
     pub fn next_reg(&mut self) -> usize {
@@ -15,9 +16,23 @@ impl EmitState {
         r
     }
 
-    pub fn next_str_idx(&mut self) -> usize {
-        let i = self.str_idx;
-        self.str_idx += 1;
-        i
+    pub fn next_label(&mut self) -> usize {
+        let l = self.label;
+        self.label += 1;
+        l
+    }
+
+    /// Marks the current basic block as closed by a terminator.
+    pub fn terminate(&mut self) {
+        self.terminated = true;
+    }
+
+    /// Starts a new basic block after a label.
+    pub fn open_block(&mut self) {
+        self.terminated = false;
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
     }
 }
\ No newline at end of file
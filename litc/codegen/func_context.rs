@@ -1,43 +1,268 @@
 use std::collections::HashMap;
 
+use super::mangle;
 use crate::ast::*;
+use crate::lexer::Source;
+use crate::lexer::SourceMap;
+use crate::lexer::Span;
 
-pub struct FuncCtx {
-    str_consts: Vec<String>,
-    var_types: HashMap<String, Type>,
+/// What every function of the program can refer to.
+pub struct Globals<'a> {
+    pub funcs: HashMap<String, FuncSig>,
+    pub classes: HashMap<String, ClassSig>,
+    pub enums: HashMap<String, EnumSig>,
+    pub methods: HashMap<String, HashMap<String, MethodSig>>,
+    pub consts: HashMap<String, ExprId>,
+    pub sources: &'a SourceMap,
+    pub checks: bool,
 }
 
-impl FuncCtx {
-    pub fn build(func: &FuncDef, arena: &ExprArena) -> FuncCtx {
-        let mut str_consts: Vec<String> = Vec::new();
-        let mut var_types = HashMap::new();
+pub struct FuncCtx<'a> {
+    locals: HashMap<ExprId, (String, Type)>,
+    decls: HashMap<ExprId, (String, Type)>,
+    bindings: HashMap<(ExprId, usize, String), (String, Type)>,
+    globals: &'a Globals<'a>,
+    is_entry_point: bool,
+    // the class whose constructor is emitted, which returns `self`
+    ctor_of: Option<String>,
+}
+
+impl<'a> FuncCtx<'a> {
+    /// Resolves every variable of `func` to its stack slot.
+    pub fn build(func: &FuncDef, arena: &ExprArena, globals: &'a Globals<'a>) -> FuncCtx<'a> {
+        let scope = func.params.iter()
+            .map(|p| (p.name.clone(), (mangle::local(&p.name), p._type.clone())))
+            .collect();
+
+        let binder = Binder::bind(&func.body, arena, scope);
+
+        FuncCtx {
+            locals: binder.locals,
+            decls: binder.decls,
+            bindings: binder.bindings,
+            globals,
+            is_entry_point: func.name == mangle::ENTRY_POINT,
+            ctor_of: None,
+        }
+    }
+
+    /// Resolves every variable of constructor `ctor` of `class` to its stack
+    /// slot, `self` included.
+    pub fn build_ctor(class: &str, ctor: &CtorDef, arena: &ExprArena, globals: &'a Globals<'a>) -> FuncCtx<'a> {
+        let mut scope: HashMap<String, (String, Type)> = ctor.params.iter()
+            .map(|p| (p.name.clone(), (mangle::local(&p.name), p._type.clone())))
+            .collect();
+
+        scope.insert("self".to_string(), (mangle::local("self"), Type::Named(class.to_string(), Vec::new())));
+
+        let binder = Binder::bind(&ctor.body, arena, scope);
+
+        FuncCtx {
+            locals: binder.locals,
+            decls: binder.decls,
+            bindings: binder.bindings,
+            globals,
+            is_entry_point: false,
+            ctor_of: Some(class.to_string()),
+        }
+    }
+
+    /// Resolves every variable of method `func` of `owner` to its stack
+    /// slot, `self` included.
+    pub fn build_method(owner: &Type, func: &FuncDef, arena: &ExprArena, globals: &'a Globals<'a>) -> FuncCtx<'a> {
+        let mut scope: HashMap<String, (String, Type)> = func.params.iter()
+            .map(|p| (p.name.clone(), (mangle::local(&p.name), p._type.clone())))
+            .collect();
+
+        scope.insert("self".to_string(), (mangle::local("self"), owner.clone()));
+
+        let binder = Binder::bind(&func.body, arena, scope);
+
+        FuncCtx {
+            locals: binder.locals,
+            decls: binder.decls,
+            bindings: binder.bindings,
+            globals,
+            is_entry_point: false,
+            ctor_of: None,
+        }
+    }
+
+    /// Slot and type of the variable read by `Expr::Var` node `id`.
+    pub fn get_local(&self, id: ExprId) -> &(String, Type) {
+        self.locals.get(&id).unwrap()
+    }
+
+    /// Slot and type of the variable declared by `v`.
+    pub fn get_decl(&self, v: &VarDecl) -> &(String, Type) {
+        &self.decls[&v.expr_id]
+    }
+
+    /// Slot and type of `name`, bound by the pattern of arm `arm` of the
+    /// switch on `scrutinee`.
+    pub fn get_binding(&self, scrutinee: ExprId, arm: usize, name: &str) -> &(String, Type) {
+        &self.bindings[&(scrutinee, arm, name.to_string())]
+    }
+
+    pub fn get_funcs(&self) -> &HashMap<String, FuncSig> {
+        &self.globals.funcs
+    }
+
+    pub fn get_class(&self, name: &str) -> &ClassSig {
+        &self.globals.classes[name]
+    }
+
+    pub fn get_enum(&self, name: &str) -> &EnumSig {
+        &self.globals.enums[name]
+    }
+
+    /// Method `name` of the type called `owner`.
+    pub fn get_method(&self, owner: &str, name: &str) -> &MethodSig {
+        &self.globals.methods[owner][name]
+    }
+
+    /// Initializer of constant `name`, which is emitted in place of every
+    /// use of the constant.
+    pub fn get_const(&self, name: &str) -> ExprId {
+        self.globals.consts[name]
+    }
+
+    /// The file that `span` points into.
+    pub fn get_source(&self, span: &Span) -> &Source {
+        self.globals.sources.get(span.file).unwrap()
+    }
+
+    pub fn is_entry_point(&self) -> bool {
+        self.is_entry_point
+    }
+
+    /// The class built by the constructor being emitted, if it is one.
+    pub fn ctor_of(&self) -> Option<&str> {
+        self.ctor_of.as_deref()
+    }
+
+    pub fn has_checks(&self) -> bool {
+        self.globals.checks
+    }
+}
+
+type Scope = HashMap<String, (String, Type)>;
+
+/// Binds every `Expr::Var` of `body` to the stack slot of the variable it
+/// reads, starting from the parameters in `scope`.
+///
+/// Each declaration gets its own slot, numbered when its name was declared
+/// before, so a redeclared variable shadows the earlier one from that point
+/// on and the variables of different `switch` arms never share a slot.
+/// Every `Expr::Var` is bound to whichever slot is in scope where it is
+/// read.
+struct Binder<'b> {
+    arena: &'b ExprArena,
+    locals: HashMap<ExprId, (String, Type)>,
+    // declarations, keyed by their initializer, which is a read of another
+    // variable in `int b = a;`
+    decls: HashMap<ExprId, (String, Type)>,
+    // names bound by the patterns of a switch, keyed by its scrutinee, the
+    // index of the arm and the name
+    bindings: HashMap<(ExprId, usize, String), (String, Type)>,
+    declared: HashMap<String, usize>,
+}
+
+impl<'b> Binder<'b> {
+    fn bind(body: &Block, arena: &'b ExprArena, scope: Scope) -> Binder<'b> {
+        let mut binder = Binder {
+            arena,
+            locals: HashMap::new(),
+            decls: HashMap::new(),
+            bindings: HashMap::new(),
+            declared: scope.keys().map(|name| (name.clone(), 1)).collect(),
+        };
+
+        binder.bind_block(body, scope);
+        binder
+    }
+
+    /// A fresh slot for a variable called `name`.
+    fn slot(&mut self, name: &str) -> String {
+        let n = self.declared.entry(name.to_string()).or_insert(0);
+        *n += 1;
+
+        match *n {
+            1 => mangle::local(name),
+            n => mangle::shadowed_local(name, n - 1),
+        }
+    }
+
+    fn bind_block(&mut self, body: &Block, mut scope: Scope) {
+        for node in body.stmts() {
+            let exprs = match &node.stmt {
+                Stmt::VarDecl(v) => vec![v.expr_id],
+                Stmt::Println(id) | Stmt::Unreachable(Some(id)) | Stmt::Return(Some(id)) | Stmt::Expr(id) => vec![*id],
+                Stmt::Assign(target, _, value) => vec![*target, *value],
+                Stmt::Switch(scrutinee, _) => vec![*scrutinee],
+                Stmt::Unreachable(None) | Stmt::Return(None) => Vec::new(),
+                Stmt::If(..) | Stmt::While(..) | Stmt::For(..) | Stmt::Llvm(_) => unreachable!(),
+            };
+
+            for id in exprs {
+                self.bind_vars(id, &scope);
+            }
 
-        for stmt in func.body.stmts() {
-            match stmt {
+            match &node.stmt {
                 Stmt::VarDecl(v) => {
-                    var_types.insert(v.name.clone(), v._type.clone());
+                    let slot = self.slot(&v.name);
 
-                    if let Expr::Lit(Lit::Str(s)) = arena.get(v.expr_id).expr.clone() {
-                        str_consts.push(s.clone());
-                    }
+                    self.decls.insert(v.expr_id, (slot.clone(), v._type.clone()));
+                    scope.insert(v.name.clone(), (slot, v._type.clone()));
                 }
-                Stmt::Println(id) => {
-                    if let Expr::Lit(Lit::Str(s)) = arena.get(*id).expr.clone() {
-                        str_consts.push(s.clone());
+
+                Stmt::Switch(scrutinee, cases) => {
+                    for (arm, case) in cases.iter().enumerate() {
+                        let inner = self.bind_pattern(*scrutinee, arm, &case.pattern, &scope);
+                        self.bind_block(&case.body, inner);
                     }
                 }
+
                 _ => {}
             }
         }
-
-        FuncCtx { str_consts, var_types }
     }
 
-    pub fn get_string_consts(&self) -> &[String] {
-        &self.str_consts
+    fn bind_vars(&mut self, id: ExprId, scope: &Scope) {
+        let expr = &self.arena.get(id).expr;
+
+        if let Expr::Var(name) = expr {
+            self.locals.insert(id, scope.get(name).unwrap().clone());
+        }
+
+        if let Expr::Switch(scrutinee, arms) = expr {
+            self.bind_vars(*scrutinee, scope);
+
+            for (arm, switch_arm) in arms.iter().enumerate() {
+                let inner = self.bind_pattern(*scrutinee, arm, &switch_arm.pattern, scope);
+                self.bind_vars(switch_arm.expr, &inner);
+            }
+
+            return;
+        }
+
+        for child in expr.children() {
+            self.bind_vars(child, scope);
+        }
     }
 
-    pub fn get_var_types(&self) -> &HashMap<String, Type> {
-        &self.var_types
+    /// Gives every name bound by `pattern`, the pattern of arm `arm` of the
+    /// switch on `scrutinee`, a slot and returns the scope of the arm.
+    fn bind_pattern(&mut self, scrutinee: ExprId, arm: usize, pattern: &Pattern, scope: &Scope) -> Scope {
+        let mut inner = scope.clone();
+
+        for (name, _type, _) in pattern.bindings() {
+            let slot = (self.slot(name), _type.clone());
+
+            self.bindings.insert((scrutinee, arm, name.to_string()), slot.clone());
+            inner.insert(name.to_string(), slot);
+        }
+
+        inner
     }
-}
\ No newline at end of file
+}
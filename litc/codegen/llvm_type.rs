@@ -1,3 +1,4 @@
+use super::mangle;
 use crate::ast::Type;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -8,6 +9,9 @@ pub enum LlvmType {
     I1,             // i1
     Char,           // i8
     I8Ptr,          // i8*
+    Array(Box<LlvmType>), // { i64, T* }, length and pointer to elements
+    Object(String), // pointer to the struct of a class or enum value, e.g. `%lit.type.Point*`
+    Void,           // void
 }
 
 impl LlvmType {
@@ -19,17 +23,37 @@ impl LlvmType {
             Type::Bool => LlvmType::I1,
             Type::Char => LlvmType::Char,
             Type::Str => LlvmType::I8Ptr,
+            Type::Array(elem) => LlvmType::Array(Box::new(LlvmType::from(elem))),
+            Type::Void => LlvmType::Void,
+            Type::Named(name, _) => LlvmType::Object(name.clone()),
+            Type::Ptr(_) | Type::Fallible(_) | Type::Lambda(..) => unreachable!(),
         }
     }
     
-    pub fn get_alloca_type(&self) -> &'static str {
+    /// Size of a value in 8-byte words. Every type is aligned to at most 8
+    /// bytes, so a struct of fields takes the sum of their sizes.
+    pub fn size_in_words(&self) -> usize {
         match self {
-            LlvmType::I64Unsigned => "i64",
-            LlvmType::I64Signed => "i64",
-            LlvmType::Double => "double",
-            LlvmType::I1 => "i1",
-            LlvmType::Char => "i8",
-            LlvmType::I8Ptr => "i8*",
+            LlvmType::Array(_) => 2,
+            _ => 1,
         }
     }
-}
\ No newline at end of file
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, LlvmType::I64Unsigned | LlvmType::I64Signed)
+    }
+
+    pub fn get_alloca_type(&self) -> String {
+        match self {
+            LlvmType::I64Unsigned => "i64".to_string(),
+            LlvmType::I64Signed => "i64".to_string(),
+            LlvmType::Double => "double".to_string(),
+            LlvmType::I1 => "i1".to_string(),
+            LlvmType::Char => "i8".to_string(),
+            LlvmType::I8Ptr => "i8*".to_string(),
+            LlvmType::Array(elem) => format!("{{ i64, {}* }}", elem.get_alloca_type()),
+            LlvmType::Object(name) => format!("{}*", mangle::object(name)),
+            LlvmType::Void => "void".to_string(),
+        }
+    }
+}
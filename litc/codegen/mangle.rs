@@ -0,0 +1,66 @@
+// Symbol mangling for user-defined names.
+//
+// Compiler temporaries are `%r<n>` and runtime symbols live under `@lit.*`,
+// `@fmt.*`, `@str.*` etc. User symbols always carry a prefix containing a
+// `.`, which Lit identifiers cannot contain, so the two can never collide.
+// Names outside LLVM's unquoted identifier charset (e.g. Unicode letters)
+// are emitted quoted.
+
+pub const ENTRY_POINT: &str = "main";
+
+pub fn local(name: &str) -> String {
+    format!("%{}", quote(&format!("v.{}", name)))
+}
+
+/// The `n`-th redeclaration of a local that shadows an earlier one.
+pub fn shadowed_local(name: &str, n: usize) -> String {
+    format!("%{}", quote(&format!("v.{}.{}", name, n)))
+}
+
+pub fn param(name: &str) -> String {
+    format!("%{}", quote(&format!("p.{}", name)))
+}
+
+pub fn func(name: &str) -> String {
+    if name == ENTRY_POINT {
+        return format!("@{}", ENTRY_POINT);
+    }
+
+    // `a::b::f` becomes `lit.fn.a.b.f`
+    format!("@{}", quote(&format!("lit.fn.{}", name.replace("::", "."))))
+}
+
+/// The struct type of class or enum `name`; values of the type point to one.
+pub fn object(name: &str) -> String {
+    format!("%{}", quote(&format!("lit.type.{}", name.replace("::", "."))))
+}
+
+/// The `index`-th constructor of class `name`.
+pub fn ctor(name: &str, index: usize) -> String {
+    format!("@{}", quote(&format!("lit.ctor.{}.{}", name.replace("::", "."), index)))
+}
+
+/// Method `name` of the type called `owner`, e.g. `str` or `a::Point`.
+pub fn method(owner: &str, name: &str) -> String {
+    format!("@{}", quote(&format!("lit.method.{}.{}", owner.replace("::", "."), name)))
+}
+
+fn quote(name: &str) -> String {
+    let is_plain = name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '$' | '.' | '_'));
+
+    if is_plain {
+        return name.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for b in name.bytes() {
+        if b == b'"' || b == b'\\' || !(0x20..=0x7e).contains(&b) {
+            quoted.push_str(&format!("\\{:02X}", b));
+        } else {
+            quoted.push(b as char);
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
@@ -2,9 +2,14 @@ mod codegen;
 mod llvm_type;
 mod func_context;
 mod emit_state;
+mod str_pool;
+mod mangle;
 mod utils;
+mod runtime;
 
 pub use codegen::generate;
 use llvm_type::LlvmType;
 use func_context::FuncCtx;
-use emit_state::EmitState;
\ No newline at end of file
+use func_context::Globals;
+use emit_state::EmitState;
+use str_pool::StrPool;
\ No newline at end of file
@@ -0,0 +1,83 @@
+// Runtime helpers emitted into every module.
+//
+// Strings are null-terminated `i8*`. Literals point into private constants,
+// while every string produced at runtime (e.g. by `+`) is a fresh `malloc`
+// allocation sized `len + 1`. Objects of classes are zeroed `calloc`
+// allocations. Nothing is freed yet: heap strings and objects live until
+// the process exits.
+
+pub const UNREACHABLE_MSG: &str = "entered unreachable code";
+pub const UNREACHABLE_MSG_PREFIX: &str = "entered unreachable code: ";
+pub const PANIC_MSG_PREFIX: &str = "panicked: ";
+pub const ASSERT_MSG: &str = "assertion failed";
+pub const ASSERT_MSG_PREFIX: &str = "assertion failed: ";
+
+pub const ADD_OVERFLOW_MSG: &str = "attempt to add with overflow";
+pub const SUB_OVERFLOW_MSG: &str = "attempt to subtract with overflow";
+pub const MUL_OVERFLOW_MSG: &str = "attempt to multiply with overflow";
+pub const NEG_OVERFLOW_MSG: &str = "attempt to negate with overflow";
+pub const DIV_OVERFLOW_MSG: &str = "attempt to divide with overflow";
+pub const REM_OVERFLOW_MSG: &str = "attempt to calculate the remainder with overflow";
+pub const DIV_BY_ZERO_MSG: &str = "attempt to divide by zero";
+pub const REM_BY_ZERO_MSG: &str = "attempt to calculate the remainder with a divisor of zero";
+pub const SHL_OVERFLOW_MSG: &str = "attempt to shift left with overflow";
+pub const SHR_OVERFLOW_MSG: &str = "attempt to shift right with overflow";
+pub const INDEX_OUT_OF_BOUNDS_MSG: &str = "index out of bounds";
+
+/// Every message a runtime check may abort with.
+pub const CHECK_MSGS: [&str; 11] = [
+    ADD_OVERFLOW_MSG, SUB_OVERFLOW_MSG, MUL_OVERFLOW_MSG, NEG_OVERFLOW_MSG, DIV_OVERFLOW_MSG,
+    REM_OVERFLOW_MSG, DIV_BY_ZERO_MSG, REM_BY_ZERO_MSG, SHL_OVERFLOW_MSG, SHR_OVERFLOW_MSG,
+    INDEX_OUT_OF_BOUNDS_MSG,
+];
+
+pub const DECLARATIONS: &str = "\
+    declare i8* @malloc(i64)\n\
+    declare i8* @calloc(i64, i64)\n\
+    declare i8* @memcpy(i8*, i8*, i64)\n\
+    declare i64 @strlen(i8*)\n\
+    declare i32 @strcmp(i8*, i8*)\n\
+    declare i32 @sprintf(i8*, i8*, ...)\n\
+    declare i64 @write(i32, i8*, i64)\n\n\
+    declare { i64, i1 } @llvm.sadd.with.overflow.i64(i64, i64)\n\
+    declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64)\n\
+    declare { i64, i1 } @llvm.ssub.with.overflow.i64(i64, i64)\n\
+    declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64)\n\
+    declare { i64, i1 } @llvm.smul.with.overflow.i64(i64, i64)\n\
+    declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64)\n\n\
+    @fmt.abort = private unnamed_addr constant [18 x i8] c\"%s:%lld:%lld: %s\\0A\\00\"\n\n\
+";
+
+/// Returns a fresh heap string holding `a` followed by `b`.
+pub const STR_CONCAT: &str = "\
+    define private i8* @lit.str.concat(i8* %a, i8* %b) {\n  \
+        %a.len = call i64 @strlen(i8* %a)\n  \
+        %b.len = call i64 @strlen(i8* %b)\n  \
+        %len = add i64 %a.len, %b.len\n  \
+        %size = add i64 %len, 1\n  \
+        %buf = call i8* @malloc(i64 %size)\n  \
+        call i8* @memcpy(i8* %buf, i8* %a, i64 %a.len)\n  \
+        %tail = getelementptr inbounds i8, i8* %buf, i64 %a.len\n  \
+        call i8* @memcpy(i8* %tail, i8* %b, i64 %b.len)\n  \
+        %end = getelementptr inbounds i8, i8* %buf, i64 %len\n  \
+        store i8 0, i8* %end\n  \
+        ret i8* %buf\n\
+    }\n\n\
+";
+
+/// Writes `file:line:col: msg` to stderr and exits with code 1.
+pub const ABORT: &str = "\
+    define private void @lit.abort(i8* %file, i64 %line, i64 %col, i8* %msg) noreturn {\n  \
+        %file.len = call i64 @strlen(i8* %file)\n  \
+        %msg.len = call i64 @strlen(i8* %msg)\n  \
+        %len = add i64 %file.len, %msg.len\n  \
+        %size = add i64 %len, 64\n  \
+        %buf = call i8* @malloc(i64 %size)\n  \
+        %fmt = getelementptr inbounds [18 x i8], [18 x i8]* @fmt.abort, i32 0, i32 0\n  \
+        %n = call i32 (i8*, i8*, ...) @sprintf(i8* %buf, i8* %fmt, i8* %file, i64 %line, i64 %col, i8* %msg)\n  \
+        %n.64 = sext i32 %n to i64\n  \
+        call i64 @write(i32 2, i8* %buf, i64 %n.64)\n  \
+        call void @exit(i32 1)\n  \
+        unreachable\n\
+    }\n\n\
+";
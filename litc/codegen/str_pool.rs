@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use super::utils;
+use super::runtime;
+use crate::ast::*;
+use crate::lexer::SourceMap;
+
+/// Module-wide pool of string literals.
+///
+/// Every `Lit::Str` reachable from a function body is interned once, so
+/// identical literals share one `@str.<n>` constant regardless of where
+/// they appear in an expression.
+pub struct StrPool {
+    strs: Vec<String>,
+    indices: HashMap<String, usize>,
+}
+
+impl StrPool {
+    pub fn build(program: &Program, sources: &SourceMap, checks: bool) -> StrPool {
+        let mut pool = StrPool {
+            strs: Vec::new(),
+            indices: HashMap::new(),
+        };
+
+        for source in sources.files() {
+            pool.intern(&source.name);
+        }
+
+        if checks {
+            pool.intern_check_msgs();
+        }
+
+        for constant in &program.consts {
+            pool.collect_expr(&program.expr_arena, constant.expr_id);
+        }
+
+        for func in &program.funcs {
+            pool.collect_block(&program.expr_arena, &func.body);
+        }
+
+        for ctor in program.classes.iter().flat_map(|c| &c.ctors) {
+            pool.collect_block(&program.expr_arena, &ctor.body);
+        }
+
+        for (_, method) in program.methods() {
+            pool.collect_block(&program.expr_arena, &method.body);
+        }
+
+        pool
+    }
+
+    fn collect_block(&mut self, arena: &ExprArena, block: &Block) {
+        for node in block.stmts() {
+            match &node.stmt {
+                Stmt::VarDecl(v) => self.collect_expr(arena, v.expr_id),
+                Stmt::Println(id) | Stmt::Return(Some(id)) | Stmt::Expr(id) => {
+                    self.collect_expr(arena, *id)
+                }
+                Stmt::Unreachable(Some(id)) => {
+                    self.intern(runtime::UNREACHABLE_MSG_PREFIX);
+                    self.collect_expr(arena, *id);
+                }
+                Stmt::Unreachable(None) => self.intern(runtime::UNREACHABLE_MSG),
+                Stmt::Return(None) => {}
+                Stmt::Assign(target, _, value) => {
+                    self.collect_expr(arena, *target);
+                    self.collect_expr(arena, *value);
+                }
+                Stmt::Switch(scrutinee, cases) => {
+                    self.collect_expr(arena, *scrutinee);
+
+                    for case in cases {
+                        self.collect_pattern(&case.pattern);
+                        self.collect_block(arena, &case.body);
+                    }
+                }
+                Stmt::If(..) | Stmt::While(..) | Stmt::For(..) | Stmt::Llvm(_) => unreachable!(),
+            }
+        }
+    }
+
+    fn collect_expr(&mut self, arena: &ExprArena, id: ExprId) {
+        match &arena.get(id).expr {
+            Expr::Lit(Lit::Str(s)) => self.intern(s),
+            Expr::Lit(_) | Expr::Var(_) | Expr::Const(_) => {}
+            Expr::MethodCall(..) | Expr::Null => unreachable!(),
+
+            Expr::Switch (scrutinee, arms) => {
+                self.collect_expr(arena, *scrutinee);
+
+                for arm in arms {
+                    self.collect_pattern(&arm.pattern);
+                    self.collect_expr(arena, arm.expr);
+                }
+            }
+
+            Expr::Binary (_, left, right) => {
+                self.collect_expr(arena, *left);
+                self.collect_expr(arena, *right);
+            }
+
+            Expr::Unary (_, expr) |
+            Expr::Cast (_, expr) |
+            Expr::Field (expr, _) => self.collect_expr(arena, *expr),
+
+            Expr::Index (expr, index) => {
+                self.collect_expr(arena, *expr);
+                self.collect_expr(arena, *index);
+            }
+
+            Expr::Construct (_, _, args) | Expr::Variant (_, _, args) | Expr::Associated (_, _, args) => {
+                for arg in args {
+                    self.collect_expr(arena, *arg);
+                }
+            }
+
+            Expr::Invoke (_, _, receiver, args) => {
+                self.collect_expr(arena, *receiver);
+
+                for arg in args {
+                    self.collect_expr(arena, *arg);
+                }
+            }
+
+            Expr::Call (name, _, args) => {
+                match (Builtin::from_name(name), args.len()) {
+                    (Some(Builtin::Panic), _) => self.intern(runtime::PANIC_MSG_PREFIX),
+                    (Some(Builtin::Assert), 1) => self.intern(runtime::ASSERT_MSG),
+                    (Some(Builtin::Assert), _) => self.intern(runtime::ASSERT_MSG_PREFIX),
+                    (Some(builtin), _) if builtin.is_checked() => self.intern_check_msgs(),
+                    _ => {}
+                }
+
+                for arg in args {
+                    self.collect_expr(arena, *arg);
+                }
+            }
+        }
+    }
+
+    /// Interns the string literals that `pattern` compares against.
+    fn collect_pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Lit(Lit::Str(s), _) => self.intern(s),
+            Pattern::Variant(_, fields, _) => {
+                for field in fields {
+                    self.collect_pattern(field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn intern_check_msgs(&mut self) {
+        for msg in runtime::CHECK_MSGS {
+            self.intern(msg);
+        }
+    }
+
+    fn intern(&mut self, s: &str) {
+        if self.indices.contains_key(s) {
+            return;
+        }
+
+        self.indices.insert(s.to_string(), self.strs.len());
+        self.strs.push(s.to_string());
+    }
+
+    pub fn emit(&self, out: &mut String) {
+        for (i, s) in self.strs.iter().enumerate() {
+            out.push_str(&format!(
+                "@str.{i} = private unnamed_addr constant [{b} x i8] c\"{esc}\\00\"\n",
+                i = i, b = s.len() + 1, esc = utils::escape_llvm(s),
+            ));
+        }
+        out.push('\n');
+    }
+
+    /// Returns the symbol and the array length of the constant holding `s`.
+    pub fn get(&self, s: &str) -> (String, usize) {
+        let i = self.indices.get(s).unwrap();
+
+        (format!("@str.{}", i), s.len() + 1)
+    }
+}
@@ -0,0 +1,102 @@
+// Catalogue of diagnostic codes.
+//
+// Codes are stable: once published, a code keeps its meaning and is never
+// reused. Errors are `E____`, lints are `W____`. Each long-form explanation
+// lives in `explanations/<code>.md` and is compiled into the binary, so
+// `litc --explain <code>` works without any files next to it.
+
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+macro_rules! catalogue {
+    ($($code:ident: $title:literal,)*) => {
+        $(
+            pub const $code: ErrorCode = ErrorCode {
+                code: stringify!($code),
+                title: $title,
+                explanation: include_str!(concat!("explanations/", stringify!($code), ".md")),
+            };
+        )*
+
+        pub const ALL: &[ErrorCode] = &[$($code),*];
+    };
+}
+
+catalogue! {
+    E0001: "Unterminated string literal",
+    E0002: "Unterminated char literal",
+    E0003: "Unrecognized character",
+    E0004: "Unknown escape sequence",
+    E0005: "Unexpected token",
+    E0006: "Unknown type",
+    E0007: "Expected a statement or expression",
+    E0008: "Builtin redefined",
+    E0009: "Function defined more than once",
+    E0010: "Missing entry point",
+    E0011: "Invalid signature of `main`",
+    E0012: "Duplicate parameter",
+    E0013: "Missing return value",
+    E0014: "Mismatched types in declaration",
+    E0015: "Value cannot be printed",
+    E0016: "Message of `unreachable` is not a string",
+    E0017: "Mismatched return type",
+    E0018: "Value returned from a function without return type",
+    E0019: "Undeclared variable",
+    E0020: "Invalid operands for binary operator",
+    E0021: "Invalid operand for unary operator",
+    E0022: "Invalid cast",
+    E0023: "Unknown field",
+    E0024: "Invalid index type",
+    E0025: "Value cannot be indexed",
+    E0026: "Undefined function",
+    E0027: "Wrong number of arguments",
+    E0028: "Mismatched argument type",
+    E0029: "Invalid arguments to builtin",
+    E0030: "Number literal out of range",
+    E0031: "Nesting too deep",
+    E0032: "Unresolved import",
+    E0033: "Import cycle",
+    E0034: "Name defined more than once in a module",
+    E0035: "Unresolved path",
+    E0036: "Invalid constant",
+    E0037: "Private item",
+    E0038: "Standard library not found",
+    E0039: "Unterminated `llvm` block",
+    E0040: "Not supported yet",
+    E0041: "Field declared more than once",
+    E0042: "No matching constructor",
+    E0043: "Mismatched types in assignment",
+    E0044: "Invalid assignment target",
+    E0045: "Method defined more than once",
+    E0046: "Unknown method",
+    E0047: "`self` outside of a method",
+    E0048: "Variant declared more than once",
+    E0049: "Unknown variant",
+    E0050: "Pattern does not match the type of the value",
+    E0051: "Arms of `switch` have different types",
+    E0052: "Non-exhaustive `switch`",
+    E0053: "Interface not implemented",
+    E0054: "Method does not match its interface",
+    E0055: "Unsatisfied bound",
+    E0056: "Wrong number of type arguments",
+    E0057: "Cannot infer type arguments",
+    E0058: "Misplaced bound attribute",
+    E0059: "Type arguments nested too deep",
+
+    W0001: "Unused variable (`unused_variables`)",
+    W0002: "Unused function (`unused_functions`)",
+    W0003: "Shadowed binding (`shadowing`)",
+    W0004: "Constant condition (`constant_condition`)",
+    W0005: "Unnecessary cast (`unnecessary_cast`)",
+    W0006: "Useless `unt` comparison (`unt_comparison`)",
+    W0007: "Unreachable code (`unreachable_code`)",
+    W0008: "Unreachable pattern (`unreachable_patterns`)",
+}
+
+/// Looks up a code, ignoring the case of its letter.
+pub fn lookup(code: &str) -> Option<&'static ErrorCode> {
+    ALL.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
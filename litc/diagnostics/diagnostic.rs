@@ -0,0 +1,121 @@
+use std::fmt;
+
+use super::codes::ErrorCode;
+use super::emitter;
+use crate::lexer::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A secondary location that explains the primary one.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+/// A fix that replaces the text under `span` with `replacement`.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub span: Span,
+    pub message: String,
+    pub replacement: String,
+}
+
+/// A single error or warning, built up and then handed to the emitter.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: String) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code: None,
+            message,
+            span: None,
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn error(message: String) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: String) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+    pub fn with_code(mut self, code: &'static ErrorCode) -> Diagnostic {
+        self.code = Some(code.code);
+        self
+    }
+
+    pub fn with_span(mut self, span: &Span) -> Diagnostic {
+        self.span = Some(span.clone());
+        self
+    }
+
+    pub fn with_label(mut self, span: &Span, message: String) -> Diagnostic {
+        self.labels.push(Label { span: span.clone(), message });
+        self
+    }
+
+    pub fn with_note(mut self, note: String) -> Diagnostic {
+        self.notes.push(note);
+        self
+    }
+
+    /// Suggests replacing `span` with `replacement`, if there is one.
+    pub fn with_suggestion(mut self, span: &Span, replacement: Option<&str>) -> Diagnostic {
+        if let Some(replacement) = replacement {
+            self.suggestions.push(Suggestion {
+                span: span.clone(),
+                message: format!("did you mean `{}`?", replacement),
+                replacement: replacement.to_string(),
+            });
+        }
+        self
+    }
+
+    /// Emits the diagnostic and stops the compilation.
+    pub fn abort(self) -> ! {
+        emitter::emit(&self);
+
+        if let Some(code) = self.code {
+            emitter::emit_explain_hint(code);
+        }
+
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,122 @@
+use std::env;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+
+use super::json;
+use super::Diagnostic;
+use super::Severity;
+use crate::lexer::Source;
+use crate::lexer::SourceMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,   // coloured text for terminals
+    Json,    // one JSON object per line
+}
+
+struct Config {
+    format: ErrorFormat,
+    color: bool,
+}
+
+impl Config {
+    fn detect(format: ErrorFormat) -> Config {
+        let color = format == ErrorFormat::Human && wants_color(&std::io::stderr());
+
+        Config { format, color }
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+static SOURCES: RwLock<SourceMap> = RwLock::new(SourceMap::new());
+
+/// Chooses how diagnostics are written. Colour is used only for the human
+/// format, when stderr is a terminal and `NO_COLOR` is unset or empty.
+pub fn init(format: ErrorFormat) {
+    let _ = CONFIG.set(Config::detect(format));
+}
+
+/// Loads a file into the global source map, so spans in it can be turned
+/// into locations, and hands it back for the rest of the pipeline.
+pub fn add_source(name: String, text: String) -> &'static Source {
+    SOURCES.write().unwrap_or_else(PoisonError::into_inner).add(name, text)
+}
+
+/// A snapshot of every file loaded so far.
+pub fn source_map() -> SourceMap {
+    SOURCES.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+/// Writes `diag` to stderr in the configured format.
+pub fn emit(diag: &Diagnostic) {
+    let config = CONFIG.get_or_init(|| Config::detect(ErrorFormat::Human));
+
+    match config.format {
+        ErrorFormat::Human => eprintln!("{}", render_human(diag, &source_map(), config.color)),
+        ErrorFormat::Json => eprintln!("{}", json::render(diag, &source_map())),
+    }
+}
+
+/// Styles `text` printed to stdout with the ANSI attributes `code`, unless
+/// stdout is not a terminal or `NO_COLOR` is set.
+pub fn paint_stdout(code: &str, text: &str) -> String {
+    paint(wants_color(&std::io::stdout()), code, text)
+}
+
+fn wants_color(stream: &impl IsTerminal) -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+
+    !no_color && stream.is_terminal()
+}
+
+fn paint(color: bool, code: &str, text: &str) -> String {
+    match color {
+        true => format!("\x1B[{}m{}\x1B[0m", code, text),
+        false => text.to_string(),
+    }
+}
+
+/// Points at `litc --explain` after a fatal error in the human format.
+pub fn emit_explain_hint(code: &str) {
+    let config = CONFIG.get_or_init(|| Config::detect(ErrorFormat::Human));
+
+    if config.format == ErrorFormat::Human {
+        eprintln!("For more information about this error, try `litc --explain {}`.", code);
+    }
+}
+
+fn render_human(diag: &Diagnostic, sources: &SourceMap, color: bool) -> String {
+    let paint = |code: &str, text: &str| paint(color, code, text);
+
+    let tag = match diag.code {
+        Some(code) => format!("[{} {}]:", diag.severity.title(), code),
+        None => format!("[{}]:", diag.severity.title()),
+    };
+
+    let header = match diag.severity {
+        Severity::Error => paint("1;31", &tag),
+        Severity::Warning => paint("1;33", &tag),
+    };
+
+    let mut out = format!("{} {}", header, diag.message);
+
+    if let Some(span) = &diag.span {
+        out.push_str(&format!("\n  {} {}", paint("1;34", "-->"), sources.location(span)));
+    }
+
+    for label in &diag.labels {
+        out.push_str(&format!("\n  {} {}: {}", paint("1;34", ":::"), sources.location(&label.span), label.message));
+    }
+
+    for note in &diag.notes {
+        out.push_str(&format!("\n  {} {}", paint("1;36", "= note:"), note));
+    }
+
+    for suggestion in &diag.suggestions {
+        out.push_str(&format!("\n  {} {}", paint("1;36", "= help:"), suggestion.message));
+    }
+
+    out
+}
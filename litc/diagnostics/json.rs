@@ -0,0 +1,103 @@
+// Rendering of diagnostics for `--error-format=json`.
+//
+// Every diagnostic becomes one JSON object on a single line:
+//
+//   {"severity": "error", "code": null, "message": "...", "file": "main.lit",
+//    "span": {"start": 10, "end": 15},
+//    "range": {"start": {"line": 2, "column": 5}, "end": {"line": 2, "column": 10}},
+//    "labels": [{"message": "...", "file": "other.lit", "span": ..., "range": ...}],
+//    "notes": ["..."],
+//    "suggestions": [{"message": "...", "replacement": "...", "file": ..., "span": ..., "range": ...}]}
+//
+// Spans are character offsets into their file, ranges are 1-based and the
+// end is exclusive. Labels may point into other files than the primary span.
+// File, span and range are `null` when the diagnostic has no location.
+
+use super::Diagnostic;
+use crate::lexer::SourceMap;
+use crate::lexer::Span;
+
+pub fn render(diag: &Diagnostic, sources: &SourceMap) -> String {
+    let labels = diag.labels.iter()
+        .map(|label| format!(
+            "{{\"message\":{},\"file\":{},\"span\":{},\"range\":{}}}",
+            string(&label.message), file(Some(&label.span), sources),
+            span(Some(&label.span)), range(Some(&label.span), sources),
+        ))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let notes = diag.notes.iter()
+        .map(|note| string(note))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let suggestions = diag.suggestions.iter()
+        .map(|suggestion| format!(
+            "{{\"message\":{},\"replacement\":{},\"file\":{},\"span\":{},\"range\":{}}}",
+            string(&suggestion.message), string(&suggestion.replacement), file(Some(&suggestion.span), sources),
+            span(Some(&suggestion.span)), range(Some(&suggestion.span), sources),
+        ))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        "{{\"severity\":{},\"code\":{},\"message\":{},\"file\":{},\"span\":{},\"range\":{},\
+          \"labels\":[{}],\"notes\":[{}],\"suggestions\":[{}]}}",
+        string(&diag.severity.to_string()),
+        diag.code.map(string).unwrap_or("null".to_string()),
+        string(&diag.message),
+        file(diag.span.as_ref(), sources),
+        span(diag.span.as_ref()),
+        range(diag.span.as_ref(), sources),
+        labels, notes, suggestions,
+    )
+}
+
+fn span(span: Option<&Span>) -> String {
+    match span {
+        Some(span) => format!("{{\"start\":{},\"end\":{}}}", span.start, span.end),
+        None => "null".to_string(),
+    }
+}
+
+fn file(span: Option<&Span>, sources: &SourceMap) -> String {
+    match span.and_then(|span| sources.get(span.file)) {
+        Some(source) => string(&source.name),
+        None => "null".to_string(),
+    }
+}
+
+fn range(span: Option<&Span>, sources: &SourceMap) -> String {
+    let Some((span, source)) = span.and_then(|span| Some((span, sources.get(span.file)?))) else {
+        return "null".to_string();
+    };
+
+    let (start_line, start_col) = source.position(span.start);
+    let (end_line, end_col) = source.position(span.end);
+
+    format!(
+        "{{\"start\":{{\"line\":{},\"column\":{}}},\"end\":{{\"line\":{},\"column\":{}}}}}",
+        start_line, start_col, end_line, end_col,
+    )
+}
+
+/// Quotes `s` as a JSON string.
+fn string(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
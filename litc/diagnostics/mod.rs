@@ -0,0 +1,13 @@
+pub mod codes;
+mod diagnostic;
+mod emitter;
+mod json;
+
+pub use diagnostic::Diagnostic;
+pub use diagnostic::Severity;
+pub use emitter::ErrorFormat;
+pub use emitter::init;
+pub use emitter::add_source;
+pub use emitter::source_map;
+pub use emitter::emit;
+pub use emitter::paint_stdout;
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use litc::lexer::FileId;
+use litc::lexer::Lexer;
+
+// Any input must either tokenize or produce a diagnostic, never panic.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(src) = std::str::from_utf8(data) {
+        let _ = Lexer::new(src, FileId::default()).tokenize();
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use litc::lexer::FileId;
+use litc::lexer::Lexer;
+use litc::parser::Parser;
+
+// Any input that tokenizes must either parse or produce a diagnostic,
+// never panic or overflow the stack.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(src) = std::str::from_utf8(data)
+        && let Ok(tokens) = Lexer::new(src, FileId::default()).tokenize()
+    {
+        let _ = Parser::new(tokens).parse();
+    }
+});
@@ -1,19 +1,26 @@
-use crate::generate_plain_error;
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::codes;
+use crate::diagnostics::codes::ErrorCode;
 use super::token::Token;
 use super::token::TokenKind;
 
 use crate::lexer::Span;
+use crate::lexer::FileId;
 
 pub struct Lexer {
     chars: Vec<char>,
     pos: usize,
+    file: FileId,
 }
 
+// Errors end the compilation, so their size never matters on the hot path.
+#[allow(clippy::result_large_err)]
 impl Lexer {
-    pub fn new(src: &str) -> Lexer {
+    pub fn new(src: &str, file: FileId) -> Lexer {
         Lexer {
             chars: src.chars().collect(),
             pos: 0,
+            file,
         }
     }
 
@@ -57,41 +64,112 @@ impl Lexer {
         }
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    fn error(&self, code: &'static ErrorCode, start: usize, message: String) -> Diagnostic {
+        Diagnostic::error(message)
+            .with_code(code)
+            .with_span(&Span::new(self.file, start, self.pos.min(self.chars.len())))
+    }
+
+    /// Splits the whole source into tokens, always ending with `Eof`.
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Diagnostic> {
         let mut tokens = Vec::new();
 
         loop {
-            let token = self.next_token();
+            let token = self.next_token()?;
             let is_eof = token.kind.is_eof();
+            let is_llvm = token.kind == TokenKind::Llvm;
 
             tokens.push(token);
 
+            if is_llvm && let Some(code) = self.read_llvm_code()? {
+                tokens.push(code);
+            }
+
             if is_eof { break; }
         }
 
-        tokens
+        Ok(tokens)
     }
 
-    fn next_token(&mut self) -> Token {
+    /// Reads the body of an `llvm { ... }` block as raw text, since LLVM IR
+    /// is not made of Lit tokens. Braces inside it must be balanced.
+    fn read_llvm_code(&mut self) -> Result<Option<Token>, Diagnostic> {
+        self.skip_whitespace();
+
+        let start = self.pos;
+
+        if self.peek() != Some('{') {
+            return Ok(None);
+        }
+
+        self.scroll();
+
+        let mut depth = 1;
+
+        while depth > 0 {
+            match self.advance() {
+                Some('{') => depth += 1,
+                Some('}') => depth -= 1,
+                Some(_) => {}
+                None => return Err(self.error(&codes::E0039, start, "Unterminated `llvm` block".to_string())),
+            }
+        }
+
+        let code = self.chars[start + 1..self.pos - 1].iter().collect();
+        Ok(Some(Token::new(TokenKind::LlvmCode(code), Span::new(self.file, start, self.pos))))
+    }
+
+    fn next_token(&mut self) -> Result<Token, Diagnostic> {
         self.skip_whitespace();
         
         let start = self.pos;
 
-        let char = self.advance().unwrap_or('\0');
+        let Some(char) = self.advance() else {
+            self.pos = start;
+            return Ok(Token::new(TokenKind::Eof, Span::new(self.file, start, start)));
+        };
 
         let kind = match char {
-            '\0' => TokenKind::Eof,
             '(' => TokenKind::LParen,
             ')' => TokenKind::RParen,
             '{' => TokenKind::LBrace,
             '}' => TokenKind::RBrace,
+            '[' => TokenKind::LBracket,
+            ']' => TokenKind::RBracket,
             ';' => TokenKind::Semicolon,
-            '=' => self.match_next('=', TokenKind::EqEq, TokenKind::Assign),
-            '+' => TokenKind::Plus,
-            '-' => TokenKind::Minus,
-            '*' => TokenKind::Star,
-            '/' => TokenKind::Slash,
-            '%' => TokenKind::Percent,
+            ':' => self.match_next(':', TokenKind::ColonColon, TokenKind::Colon),
+            ',' => TokenKind::Comma,
+            '.' if self.peek() == Some('.') && self.chars.get(self.pos + 1) == Some(&'.') => {
+                self.pos += 2;
+                TokenKind::Ellipsis
+            }
+            '.' => TokenKind::Dot,
+            '#' => TokenKind::Hash,
+            '$' => TokenKind::Dollar,
+            '=' => {
+                match self.peek() {
+                    Some('=') => { self.scroll(); TokenKind::EqEq },
+                    Some('>') => { self.scroll(); TokenKind::FatArrow },
+                    _ => TokenKind::Assign,
+                }
+            },
+            '+' => {
+                match self.peek() {
+                    Some('=') => { self.scroll(); TokenKind::PlusAssign },
+                    Some('+') => { self.scroll(); TokenKind::PlusPlus },
+                    _ => TokenKind::Plus,
+                }
+            },
+            '-' => {
+                match self.peek() {
+                    Some('=') => { self.scroll(); TokenKind::MinusAssign },
+                    Some('-') => { self.scroll(); TokenKind::MinusMinus },
+                    _ => TokenKind::Minus,
+                }
+            },
+            '*' => self.match_next('=', TokenKind::StarAssign, TokenKind::Star),
+            '/' => self.match_next('=', TokenKind::SlashAssign, TokenKind::Slash),
+            '%' => self.match_next('=', TokenKind::PercentAssign, TokenKind::Percent),
             '~' => TokenKind::Tilde,
             '&' => self.match_next('&', TokenKind::AndAnd, TokenKind::And),
             '|' => self.match_next('|', TokenKind::OrOr, TokenKind::Or),
@@ -115,17 +193,21 @@ impl Lexer {
             q @ '"' => {
                 let mut s = String::new();
 
-                while let Some(c) = self.peek() {
+                loop {
+                    let Some(c) = self.peek() else {
+                        return Err(self.error(&codes::E0001, start, "Unterminated string literal".to_string()));
+                    };
+
                     if c == '\\' {
                         self.scroll();
-                        let unicode = self.read_escape();
+                        let unicode = self.read_escape(start, &codes::E0001)?;
 
                         s.push(unicode);
                         continue;
                     }
 
                     if c == '\n' {
-                        generate_plain_error!("Unterminated string literal");
+                        return Err(self.error(&codes::E0001, start, "Unterminated string literal".to_string()));
                     }
                     
                     if c == q {
@@ -140,15 +222,18 @@ impl Lexer {
             }
 
             q @ '\'' => {
-                let mut ch = self.advance().unwrap();
+                let mut ch = match self.advance() {
+                    Some(ch) if ch != '\n' => ch,
+                    _ => return Err(self.error(&codes::E0002, start, "Unterminated char literal".to_string())),
+                };
 
                 if ch == '\\' {
-                    let unicode = self.read_escape();
+                    let unicode = self.read_escape(start, &codes::E0002)?;
                     ch = unicode;
                 }
 
                 if self.peek() != Some(q) {
-                    generate_plain_error!("Unterminated char literal");
+                    return Err(self.error(&codes::E0002, start, "Unterminated char literal".to_string()));
                 }
 
                 self.scroll();
@@ -173,11 +258,15 @@ impl Lexer {
                     }
                 }
 
-                if is_float {
-                    TokenKind::FloatLit(num.parse().unwrap())
+                let parsed = if is_float {
+                    num.parse().ok().filter(|f: &f64| f.is_finite()).map(TokenKind::FloatLit)
                 } else {
-                    TokenKind::NumLit(num.parse().unwrap())
-                }
+                    num.parse().ok().map(TokenKind::NumLit)
+                };
+
+                parsed.ok_or_else(|| {
+                    self.error(&codes::E0030, start, format!("Number literal `{}` is too large", num))
+                })?
             }
 
             c if c.is_alphabetic() || c == '_' => {
@@ -195,6 +284,26 @@ impl Lexer {
                     "fun" => TokenKind::Fun,
                     "unreachable" => TokenKind::Unreachable,
                     "as" => TokenKind::As,
+                    "return" => TokenKind::Return,
+                    "import" => TokenKind::Import,
+                    "module" => TokenKind::Module,
+                    "const" => TokenKind::Const,
+                    "pub" => TokenKind::Pub,
+                    "if" => TokenKind::If,
+                    "else" => TokenKind::Else,
+                    "while" => TokenKind::While,
+                    "for" => TokenKind::For,
+                    "in" => TokenKind::In,
+                    "switch" => TokenKind::Switch,
+                    "class" => TokenKind::Class,
+                    "enum" => TokenKind::Enum,
+                    "interface" => TokenKind::Interface,
+                    "impl" => TokenKind::Impl,
+                    "override" => TokenKind::Override,
+                    "llvm" => TokenKind::Llvm,
+                    "lambda" => TokenKind::Lambda,
+                    "null" => TokenKind::Null,
+                    "self" => TokenKind::SelfValue,
                     "unt" => TokenKind::Unt,
                     "int" => TokenKind::Int,
                     "float" => TokenKind::Float,
@@ -208,25 +317,27 @@ impl Lexer {
                 }
             }
 
-            other => generate_plain_error!("Unrecognized character: `{}`", other),
+            other => return Err(self.error(&codes::E0003, start, format!("Unrecognized character: `{}`", other))),
         };
 
-        let span = Span::new(start, self.pos);
-        Token::new(kind, span)
+        let span = Span::new(self.file, start, self.pos);
+        Ok(Token::new(kind, span))
     }
 
-    fn read_escape(&mut self) -> char {
-        match self.advance().unwrap() {
-            'n' => '\n',
-            'r' => '\r',
-            't' => '\t',
-            'c' => '\x1B',
-            '"' => '"',
-            '\\' => '\\',
-            '\'' => '\'',
-            other => {
-                generate_plain_error!("Cannot resolve: `\\{}` unicode", other)
+    /// Reads the character after a `\\` inside the literal that begins at `start`.
+    fn read_escape(&mut self, start: usize, unterminated: &'static ErrorCode) -> Result<char, Diagnostic> {
+        match self.advance() {
+            Some('n') => Ok('\n'),
+            Some('r') => Ok('\r'),
+            Some('t') => Ok('\t'),
+            Some('c') => Ok('\x1B'),
+            Some('"') => Ok('"'),
+            Some('\\') => Ok('\\'),
+            Some('\'') => Ok('\''),
+            Some(other) if other != '\n' => {
+                Err(self.error(&codes::E0004, start, format!("Cannot resolve: `\\{}` unicode", other)))
             },
+            _ => Err(self.error(unterminated, start, unterminated.title.to_string())),
         }
     }
 }
\ No newline at end of file
@@ -1,8 +1,13 @@
 mod lexer;
 mod token;
 mod span;
+mod source;
+mod source_map;
 
 pub use lexer::Lexer;
 pub use token::Token;
 pub use token::TokenKind;
-pub use span::Span;
\ No newline at end of file
+pub use span::Span;
+pub use source::Source;
+pub use source_map::SourceMap;
+pub use source_map::FileId;
@@ -0,0 +1,40 @@
+use super::FileId;
+use super::Span;
+
+/// One loaded file, with the offsets of its line starts precomputed so that
+/// spans turn into locations with a binary search.
+pub struct Source {
+    pub id: FileId,
+    pub name: String,
+    pub text: String,
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    pub fn new(id: FileId, name: String, text: String) -> Source {
+        let line_starts = std::iter::once(0)
+            .chain(text.chars().enumerate().filter(|(_, c)| *c == '\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Source { id, name, text, line_starts }
+    }
+
+    /// 1-based line and column of char offset `pos`.
+    pub fn position(&self, pos: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= pos);
+
+        (line, pos - self.line_starts[line - 1] + 1)
+    }
+
+    /// 1-based line and column of the start of `span`, counted in chars.
+    pub fn line_col(&self, span: &Span) -> (usize, usize) {
+        self.position(span.start)
+    }
+
+    /// Formats `span` as `file:line:col`.
+    pub fn location(&self, span: &Span) -> String {
+        let (line, col) = self.line_col(span);
+
+        format!("{}:{}:{}", self.name, line, col)
+    }
+}
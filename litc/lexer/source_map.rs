@@ -0,0 +1,47 @@
+use super::Source;
+use super::Span;
+
+/// Index of a file in the `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(pub u32);
+
+/// Every file loaded during a compilation.
+///
+/// Files stay alive until the process exits, so each one is handed out as a
+/// `&'static Source` and the map itself is cheap to clone.
+#[derive(Clone, Default)]
+pub struct SourceMap {
+    files: Vec<&'static Source>,
+}
+
+impl SourceMap {
+    pub const fn new() -> SourceMap {
+        SourceMap { files: Vec::new() }
+    }
+
+    /// Takes ownership of a file and gives it the next `FileId`.
+    pub fn add(&mut self, name: String, text: String) -> &'static Source {
+        let id = FileId(self.files.len() as u32);
+        let source = Box::leak(Box::new(Source::new(id, name, text)));
+
+        self.files.push(source);
+        source
+    }
+
+    pub fn get(&self, id: FileId) -> Option<&'static Source> {
+        self.files.get(id.0 as usize).copied()
+    }
+
+    pub fn files(&self) -> &[&'static Source] {
+        &self.files
+    }
+
+    /// Formats `span` as `file:line:col`, or `<unknown>` for a file that was
+    /// never loaded.
+    pub fn location(&self, span: &Span) -> String {
+        match self.get(span.file) {
+            Some(source) => source.location(span),
+            None => String::from("<unknown>"),
+        }
+    }
+}
@@ -1,12 +1,21 @@
+use super::FileId;
+
 #[derive(Debug, Clone)]
 pub struct Span {
+    pub file: FileId,
     pub start: usize,
     pub end: usize,
 }
 
 impl Span {
     #[inline]
-    pub fn new(start: usize, end: usize) -> Span {
-        Span { start, end }
+    pub fn new(file: FileId, start: usize, end: usize) -> Span {
+        Span { file, start, end }
     }
-}
\ No newline at end of file
+
+    /// The span from the start of `self` to the end of `other`.
+    #[inline]
+    pub fn to(&self, other: &Span) -> Span {
+        Span::new(self.file, self.start, other.end)
+    }
+}
@@ -28,6 +28,26 @@ pub enum TokenKind {
     Fun,                // `fun`
     Unreachable,        // `unreachable`
     As,                 // `as`
+    Return,             // `return`
+    Import,             // `import`
+    Module,             // `module`
+    Const,              // `const`
+    Pub,                // `pub`
+    If,                 // `if`
+    Else,               // `else`
+    While,              // `while`
+    For,                // `for`
+    In,                 // `in`
+    Switch,             // `switch`
+    Class,              // `class`
+    Enum,               // `enum`
+    Interface,          // `interface`
+    Impl,               // `impl`
+    Override,           // `override`
+    Llvm,               // `llvm`
+    Lambda,             // `lambda`
+    Null,               // `null`
+    SelfValue,          // `self`
 
     // Primitive types
     Unt,                // `unt`
@@ -46,9 +66,17 @@ pub enum TokenKind {
     BoolLit(bool),
     CharLit(char),
     StringLit(String),
+    LlvmCode(String),   // body of an `llvm { ... }` block, kept as raw text
 
     // Operators
     Assign,             // `=`
+    PlusAssign,         // `+=`
+    MinusAssign,        // `-=`
+    StarAssign,         // `*=`
+    SlashAssign,        // `/=`
+    PercentAssign,      // `%=`
+    PlusPlus,           // `++`
+    MinusMinus,         // `--`
     Plus,               // `+`
     Minus,              // `-`
     Star,               // `*`
@@ -83,7 +111,17 @@ pub enum TokenKind {
     RParen,             // `)`
     LBrace,             // `{`
     RBrace,             // `}`
+    LBracket,           // `[`
+    RBracket,           // `]`
     Semicolon,          // `;`
+    Colon,              // `:`
+    ColonColon,         // `::`
+    Comma,              // `,`
+    Dot,                // `.`
+    Hash,               // `#`
+    Dollar,             // `$`
+    Ellipsis,           // `...`
+    FatArrow,           // `=>`
 
     // End Of File
     Eof,
@@ -116,6 +154,26 @@ impl fmt::Display for TokenKind {
             TokenKind::Fun => write!(f, "fun"),
             TokenKind::Unreachable => write!(f, "unreachable"),
             TokenKind::As => write!(f, "as"),
+            TokenKind::Return => write!(f, "return"),
+            TokenKind::Import => write!(f, "import"),
+            TokenKind::Module => write!(f, "module"),
+            TokenKind::Const => write!(f, "const"),
+            TokenKind::Pub => write!(f, "pub"),
+            TokenKind::If => write!(f, "if"),
+            TokenKind::Else => write!(f, "else"),
+            TokenKind::While => write!(f, "while"),
+            TokenKind::For => write!(f, "for"),
+            TokenKind::In => write!(f, "in"),
+            TokenKind::Switch => write!(f, "switch"),
+            TokenKind::Class => write!(f, "class"),
+            TokenKind::Enum => write!(f, "enum"),
+            TokenKind::Interface => write!(f, "interface"),
+            TokenKind::Impl => write!(f, "impl"),
+            TokenKind::Override => write!(f, "override"),
+            TokenKind::Llvm => write!(f, "llvm"),
+            TokenKind::Lambda => write!(f, "lambda"),
+            TokenKind::Null => write!(f, "null"),
+            TokenKind::SelfValue => write!(f, "self"),
             TokenKind::Unt => write!(f, "unt"),
             TokenKind::Int => write!(f, "int"),
             TokenKind::Float => write!(f, "float"),
@@ -124,11 +182,19 @@ impl fmt::Display for TokenKind {
             TokenKind::Str => write!(f, "str"),
             TokenKind::Ident(name) => write!(f, "{}", name),
             TokenKind::StringLit(s) => write!(f, "\"{}\"", s),
+            TokenKind::LlvmCode(_) => write!(f, "{{ ... }}"),
             TokenKind::NumLit(n) => write!(f, "{}", n),
             TokenKind::FloatLit(n) => write!(f, "{}", n),
             TokenKind::BoolLit(b) => write!(f, "{}", b),
             TokenKind::CharLit(c) => write!(f, "{}", c),
             TokenKind::Assign => write!(f, "="),
+            TokenKind::PlusAssign => write!(f, "+="),
+            TokenKind::MinusAssign => write!(f, "-="),
+            TokenKind::StarAssign => write!(f, "*="),
+            TokenKind::SlashAssign => write!(f, "/="),
+            TokenKind::PercentAssign => write!(f, "%="),
+            TokenKind::PlusPlus => write!(f, "++"),
+            TokenKind::MinusMinus => write!(f, "--"),
             TokenKind::Plus => write!(f, "+"),
             TokenKind::Minus => write!(f, "-"),
             TokenKind::Star => write!(f, "*"),
@@ -154,7 +220,17 @@ impl fmt::Display for TokenKind {
             TokenKind::RParen => write!(f, ")"),
             TokenKind::LBrace => write!(f, "{{"),
             TokenKind::RBrace => write!(f, "}}"),
+            TokenKind::LBracket => write!(f, "["),
+            TokenKind::RBracket => write!(f, "]"),
             TokenKind::Semicolon => write!(f, ";"),
+            TokenKind::Colon => write!(f, ":"),
+            TokenKind::ColonColon => write!(f, "::"),
+            TokenKind::Comma => write!(f, ","),
+            TokenKind::Dot => write!(f, "."),
+            TokenKind::Hash => write!(f, "#"),
+            TokenKind::Dollar => write!(f, "$"),
+            TokenKind::Ellipsis => write!(f, "..."),
+            TokenKind::FatArrow => write!(f, "=>"),
             TokenKind::Eof => write!(f, "End_Of_File"),
         }
     }
@@ -0,0 +1,14 @@
+//! The Lit compiler as a library, so that the `litc` binary and the fuzz
+//! targets in `fuzz/` drive the same front end.
+
+pub mod analyzer;
+pub mod ast;
+pub mod codegen;
+pub mod lexer;
+pub mod loader;
+pub mod parser;
+pub mod resolver;
+pub mod lint;
+pub mod diagnostics;
+pub mod utils;
+pub mod hir;
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use super::Level;
+use super::Lint;
+
+/// Name that addresses every lint at once, as in `-D warnings`.
+pub const ALL_LINTS: &str = "warnings";
+
+/// Lint levels requested on the command line. Every lint warns by default.
+///
+/// Flags are applied in order, so a later `-A shadowing` overrides an
+/// earlier `-D warnings`. `-Werror` promotes whatever ends up as a warning
+/// into an error.
+#[derive(Default)]
+pub struct LintConfig {
+    levels: HashMap<Lint, Level>,
+    warnings_as_errors: bool,
+}
+
+impl LintConfig {
+    pub fn new() -> LintConfig {
+        LintConfig {
+            levels: HashMap::new(),
+            warnings_as_errors: false,
+        }
+    }
+
+    /// Sets the level of the lint called `name` (or of all lints, for
+    /// `warnings`). Returns `false` if no such lint exists.
+    pub fn set(&mut self, name: &str, level: Level) -> bool {
+        if name == ALL_LINTS {
+            for lint in Lint::ALL {
+                self.levels.insert(lint, level);
+            }
+            return true;
+        }
+
+        match Lint::from_name(name) {
+            Some(lint) => {
+                self.levels.insert(lint, level);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_warnings_as_errors(&mut self) {
+        self.warnings_as_errors = true;
+    }
+
+    pub fn level(&self, lint: Lint) -> Level {
+        let level = self.levels.get(&lint).copied().unwrap_or(Level::Warn);
+
+        match level {
+            Level::Warn if self.warnings_as_errors => Level::Deny,
+            level => level,
+        }
+    }
+}
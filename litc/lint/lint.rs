@@ -0,0 +1,75 @@
+use std::fmt;
+
+use crate::diagnostics::codes;
+use crate::diagnostics::codes::ErrorCode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,     // a variable or parameter that is never read
+    UnusedFunctions,     // a function that cannot be reached from `main`
+    Shadowing,           // a variable that redeclares an earlier binding
+    ConstantCondition,   // a condition that does not depend on any variable
+    UnnecessaryCast,     // `x as T` where `x` is already `T`
+    UntComparison,       // `u >= 0`, `u < 0`, ... where `u` is `unt`
+    UnreachableCode,     // statements after `return`, `unreachable`, ...
+    UnreachablePatterns, // a `switch` arm that the arms above it already cover
+}
+
+impl Lint {
+    pub const ALL: [Lint; 8] = [
+        Lint::UnusedVariables,
+        Lint::UnusedFunctions,
+        Lint::Shadowing,
+        Lint::ConstantCondition,
+        Lint::UnnecessaryCast,
+        Lint::UntComparison,
+        Lint::UnreachableCode,
+        Lint::UnreachablePatterns,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::UnusedFunctions => "unused_functions",
+            Lint::Shadowing => "shadowing",
+            Lint::ConstantCondition => "constant_condition",
+            Lint::UnnecessaryCast => "unnecessary_cast",
+            Lint::UntComparison => "unt_comparison",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::UnreachablePatterns => "unreachable_patterns",
+        }
+    }
+
+    pub fn code(&self) -> &'static ErrorCode {
+        match self {
+            Lint::UnusedVariables => &codes::W0001,
+            Lint::UnusedFunctions => &codes::W0002,
+            Lint::Shadowing => &codes::W0003,
+            Lint::ConstantCondition => &codes::W0004,
+            Lint::UnnecessaryCast => &codes::W0005,
+            Lint::UntComparison => &codes::W0006,
+            Lint::UnreachableCode => &codes::W0007,
+            Lint::UnreachablePatterns => &codes::W0008,
+        }
+    }
+
+    /// Looks a lint up by name. Dashes are accepted in place of underscores.
+    pub fn from_name(name: &str) -> Option<Lint> {
+        let name = name.replace('-', "_");
+
+        Lint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,   // `-A`, `"allow"`
+    Warn,    // `-W`, `"warn"`
+    Deny,    // `-D`, `"deny"`
+}
@@ -0,0 +1,50 @@
+use std::cell::Cell;
+
+use super::Level;
+use super::Lint;
+use super::LintConfig;
+use crate::diagnostics;
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::Severity;
+use crate::generate_plain_error;
+
+/// Reports lints at the level chosen by a `LintConfig`.
+///
+/// Denied lints are emitted as errors but do not stop analysis, so every
+/// finding is shown before `finish` aborts the compilation.
+pub struct Linter<'a> {
+    config: &'a LintConfig,
+    denied: Cell<usize>,
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(config: &'a LintConfig) -> Linter<'a> {
+        Linter { config, denied: Cell::new(0) }
+    }
+
+    pub fn report(&self, lint: Lint, diag: Diagnostic) {
+        let (severity, level) = match self.config.level(lint) {
+            Level::Allow => return,
+            Level::Warn => (Severity::Warning, "warn"),
+            Level::Deny => (Severity::Error, "deny"),
+        };
+
+        if severity == Severity::Error {
+            self.denied.set(self.denied.get() + 1);
+        }
+
+        let mut diag = diag
+            .with_code(lint.code())
+            .with_note(format!("lint `{}` is set to `{}`", lint, level));
+        diag.severity = severity;
+
+        diagnostics::emit(&diag);
+    }
+
+    /// Aborts the compilation if any denied lint was reported.
+    pub fn finish(&self) {
+        if self.denied.get() > 0 {
+            generate_plain_error!("Aborting due to {} denied lint(s)", self.denied.get());
+        }
+    }
+}
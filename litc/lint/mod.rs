@@ -0,0 +1,9 @@
+mod lint;
+mod config;
+mod linter;
+
+pub use lint::Lint;
+pub use lint::Level;
+pub use config::LintConfig;
+pub use config::ALL_LINTS;
+pub use linter::Linter;
@@ -0,0 +1,1060 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::iter;
+use std::mem;
+use std::path::Path;
+use std::path::PathBuf;
+use std::slice;
+
+use crate::ast::*;
+use crate::diagnostics;
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::codes;
+use crate::lexer::Lexer;
+use crate::lexer::Span;
+use crate::parser::Parser;
+use crate::utils::suggest::closest;
+use super::sysroot;
+
+/// Loads every module reachable through the imports of `root`, the program
+/// parsed from `entry`, and links them into one program.
+///
+/// A file is a module and directories nest them: `import a::b::f;` loads
+/// `a/b.lit` from the directory of `entry`, from one of `search_paths` or
+/// from the standard library, which is looked for in `sysroot` (see
+/// [`sysroot::find_std`]). `module` blocks nest further modules inside a
+/// file.
+/// Functions and constants are renamed to their qualified names (`a::b::f`),
+/// and every name and `::` path in an expression is rewritten to the item it
+/// refers to in the scope of its own module, so later passes see unique
+/// names.
+///
+/// Unless the entry file opts out with `#![no_std]`, every module outside
+/// the standard library sees the [`PRELUDE`] without importing it.
+pub fn load(root: Program, entry: &Path, search_paths: &[PathBuf], sysroot: Option<&Path>) -> Program {
+    let package_root = entry.parent().map_or(PathBuf::new(), Path::to_path_buf);
+
+    let mut roots = vec![package_root];
+    roots.extend(search_paths.iter().cloned());
+
+    let root_name = entry.file_stem().map_or("main".to_string(), |s| s.to_string_lossy().to_string());
+    let no_std = root.no_std.is_some();
+    let (imports, defs, blocks, expr_arena) = Defs::split(root);
+
+    let mut loader = Loader {
+        roots,
+        std: sysroot::find_std(sysroot),
+        no_std,
+        modules: Vec::new(),
+        by_path: HashMap::new(),
+        by_file: HashMap::new(),
+        loading: Vec::new(),
+        variants: None,
+        generics: Vec::new(),
+        arena: expr_arena,
+    };
+
+    loader.add_file(Vec::new(), root_name, imports, defs, blocks);
+
+    if let Ok(file) = fs::canonicalize(entry) {
+        loader.by_file.insert(file, 0);
+    }
+
+    loader.load_imports(0);
+    loader.link()
+}
+
+/// Names in scope everywhere outside the standard library, provided by the
+/// compiler.
+const PRELUDE: [&str; 2] = ["println", "panic"];
+
+/// Where the file of a module is looked for.
+#[derive(Clone, Copy)]
+enum Place {
+    // the directory of the entry file, then `-L` directories
+    Package,
+    Std,
+}
+
+/// What a name in scope refers to.
+#[derive(Clone)]
+enum Item {
+    Func(String),
+    Const(String),
+    Module(usize),
+    // a class, enum or interface
+    Type(String),
+}
+
+/// A name defined in or imported into a module.
+struct Entry {
+    item: Item,
+    span: Span,
+    is_pub: bool,
+}
+
+/// The definitions of a file or `module` block, apart from nested blocks.
+#[derive(Default)]
+struct Defs {
+    funcs: Vec<FuncDef>,
+    consts: Vec<ConstDef>,
+    classes: Vec<ClassDef>,
+    enums: Vec<EnumDef>,
+    interfaces: Vec<InterfaceDef>,
+    impls: Vec<ImplDef>,
+    llvm: Vec<LlvmBlock>,
+}
+
+impl Defs {
+    fn split(program: Program) -> (Vec<Import>, Defs, Vec<ModuleDef>, ExprArena) {
+        let Program { imports, funcs, consts, modules, classes, enums, interfaces, impls, llvm, expr_arena, .. } = program;
+
+        (imports, Defs { funcs, consts, classes, enums, interfaces, impls, llvm }, modules, expr_arena)
+    }
+
+    fn from_block(block: ModuleDef) -> (Defs, Vec<ModuleDef>) {
+        let ModuleDef { funcs, consts, modules, classes, enums, interfaces, impls, llvm, .. } = block;
+
+        (Defs { funcs, consts, classes, enums, interfaces, impls, llvm }, modules)
+    }
+}
+
+struct Module {
+    path: Vec<String>,
+    name: String,
+    // the module around a `module` block, whose names are visible inside it
+    parent: Option<usize>,
+    // part of the standard library, which does not see the prelude
+    is_std: bool,
+    imports: Vec<Import>,
+    // the file module each import starts with, and how many of its segments
+    // name that module
+    import_targets: Vec<(usize, usize)>,
+    defs: Defs,
+    // functions, constants and modules
+    items: HashMap<String, Entry>,
+    // classes, enums and interfaces, which live apart from items so that a
+    // type can share its name with a module, e.g. `class Vec` and the
+    // `module Vec` of functions that make one
+    types: HashMap<String, Entry>,
+    imported: HashMap<String, Entry>,
+    imported_types: HashMap<String, Entry>,
+}
+
+impl Module {
+    fn qualify(&self, name: &str) -> String {
+        match self.path.is_empty() {
+            true => name.to_string(),
+            false => format!("{}::{}", self.path.join("::"), name),
+        }
+    }
+}
+
+struct Loader {
+    roots: Vec<PathBuf>,
+    // the standard library, or the directories it was looked for in
+    std: Result<PathBuf, Vec<PathBuf>>,
+    no_std: bool,
+    modules: Vec<Module>,
+    // file modules only
+    by_path: HashMap<Vec<String>, usize>,
+    by_file: HashMap<PathBuf, usize>,
+    // file modules whose imports are being loaded, with the import that led on
+    loading: Vec<(usize, Option<Span>)>,
+    // the enum whose methods are rewritten, by its qualified name, and the
+    // variants they can name without a path
+    variants: Option<(String, Vec<String>)>,
+    // generic parameters in scope, which name no type of a module
+    generics: Vec<String>,
+    arena: ExprArena,
+}
+
+impl Loader {
+    fn add_file(&mut self, path: Vec<String>, name: String, imports: Vec<Import>, defs: Defs, blocks: Vec<ModuleDef>) -> usize {
+        let index = self.add(path.clone(), name, None, defs);
+
+        self.modules[index].imports = imports;
+        self.by_path.insert(path, index);
+
+        for block in blocks {
+            self.add_block(index, block);
+        }
+
+        index
+    }
+
+    fn add_block(&mut self, parent: usize, block: ModuleDef) {
+        let mut path = self.modules[parent].path.clone();
+        path.push(block.name.clone());
+
+        let (name, span, is_pub) = (block.name.clone(), block.span.clone(), block.is_pub);
+        let (defs, blocks) = Defs::from_block(block);
+
+        let index = self.add(path.clone(), path.join("::"), Some(parent), defs);
+        self.define(parent, &name, Item::Module(index), &span, is_pub);
+
+        for inner in blocks {
+            self.add_block(index, inner);
+        }
+    }
+
+    fn add(&mut self, path: Vec<String>, name: String, parent: Option<usize>, defs: Defs) -> usize {
+        let index = self.modules.len();
+
+        self.modules.push(Module {
+            is_std: path.first().is_some_and(|first| first == "std"),
+            path,
+            name,
+            parent,
+            imports: Vec::new(),
+            import_targets: Vec::new(),
+            defs: Defs::default(),
+            items: HashMap::new(),
+            types: HashMap::new(),
+            imported: HashMap::new(),
+            imported_types: HashMap::new(),
+        });
+
+        for func in &defs.funcs {
+            let qualified = self.modules[index].qualify(&func.name);
+            self.define(index, &func.name, Item::Func(qualified), &func.span, func.is_pub);
+        }
+
+        for constant in &defs.consts {
+            let qualified = self.modules[index].qualify(&constant.name);
+            self.define(index, &constant.name, Item::Const(qualified), &constant.span, constant.is_pub);
+        }
+
+        let types = defs.classes.iter().map(|c| (&c.name, &c.span, c.is_pub))
+            .chain(defs.enums.iter().map(|e| (&e.name, &e.span, e.is_pub)))
+            .chain(defs.interfaces.iter().map(|i| (&i.name, &i.span, i.is_pub)));
+
+        for (name, span, is_pub) in types {
+            let qualified = self.modules[index].qualify(name);
+            self.define(index, name, Item::Type(qualified), span, is_pub);
+        }
+
+        self.modules[index].defs = defs;
+
+        index
+    }
+
+    /// Adds `name` to the items of module `index`. Functions defined twice
+    /// are left for the analyzer to report.
+    fn define(&mut self, index: usize, name: &str, item: Item, span: &Span, is_pub: bool) {
+        let module = &mut self.modules[index];
+        let names = match item {
+            Item::Type(_) => &mut module.types,
+            _ => &mut module.items,
+        };
+
+        match names.get(name) {
+            Some(Entry { item: Item::Func(_), .. }) if matches!(item, Item::Func(_)) => {}
+
+            Some(Entry { span: previous, .. }) => {
+                Diagnostic::error(format!("`{}` is defined more than once in module `{}`", name, module.name))
+                    .with_code(&codes::E0034)
+                    .with_span(span)
+                    .with_label(previous, format!("`{}` is first defined here", name))
+                    .abort();
+            }
+
+            None => {
+                names.insert(name.to_string(), Entry { item, span: span.clone(), is_pub });
+            }
+        }
+    }
+
+    fn load_imports(&mut self, index: usize) {
+        let imports: Vec<(Vec<String>, Span)> = self.modules[index].imports.iter()
+            .map(|import| (import.path.clone(), import.span.clone()))
+            .collect();
+
+        self.loading.push((index, None));
+
+        let is_std = self.modules[index].is_std;
+        let mut targets = Vec::new();
+
+        for (path, span) in imports {
+            self.loading.last_mut().unwrap().1 = Some(span.clone());
+
+            let target = self.load_module(&path, &span, is_std);
+
+            if self.loading.iter().any(|(m, _)| *m == target.0) {
+                self.report_cycle(target.0);
+            }
+
+            targets.push(target);
+        }
+
+        self.loading.pop();
+        self.modules[index].import_targets = targets;
+    }
+
+    /// Finds the file module that import `path` starts with, the longest
+    /// prefix that is a loaded module or a file, and loads it if needed.
+    /// Returns the module and how many segments of `path` name it.
+    ///
+    /// The package comes first, except for imports inside the standard
+    /// library; paths that start with `std` only look in the standard
+    /// library.
+    fn load_module(&mut self, path: &[String], span: &Span, from_std: bool) -> (usize, usize) {
+        if path[0] == "std" {
+            self.check_std(span);
+        }
+
+        let (skip, places) = match (path[0] == "std", from_std) {
+            (true, _) => (1, [Place::Std].as_slice()),
+            (false, true) => (0, [Place::Std, Place::Package].as_slice()),
+            (false, false) => (0, [Place::Package, Place::Std].as_slice()),
+        };
+
+        for len in (skip + 1..path.len()).rev() {
+            let prefix = &path[skip..len];
+
+            for &place in places {
+                let key = match place {
+                    Place::Package => prefix.to_vec(),
+                    Place::Std => iter::once("std".to_string()).chain(prefix.iter().cloned()).collect(),
+                };
+
+                if let Some(&index) = self.by_path.get(&key) {
+                    return (index, len);
+                }
+
+                if let Some(file) = self.find(place, prefix) {
+                    return (self.load_file(key, file, span), len);
+                }
+            }
+        }
+
+        let module = &path[..path.len() - 1];
+
+        let searched = places.iter()
+            .flat_map(|place| match (place, &self.std) {
+                (Place::Package, _) => self.roots.as_slice(),
+                (Place::Std, Ok(std)) => slice::from_ref(std),
+                (Place::Std, Err(_)) => &[],
+            })
+            .map(|r| match r.as_os_str().is_empty() {
+                true => "`.`".to_string(),
+                false => format!("`{}`", r.display()),
+            })
+            .collect::<Vec<_>>();
+
+        let mut diag = Diagnostic::error(format!("Cannot find module `{}`", module.join("::")))
+            .with_code(&codes::E0032)
+            .with_span(span)
+            .with_note(format!(
+                "searched for `{}` in: {}",
+                Path::new(&module[skip..].join("/")).with_extension("lit").display(),
+                searched.join(", "),
+            ));
+
+        if self.std.is_err() {
+            diag = diag.with_note("the standard library was not found; set `LIT_HOME` or pass `--sysroot`".to_string());
+        }
+
+        diag.abort()
+    }
+
+    /// Loads module `path` from `file`, unless it turns out to be a file
+    /// that is already loaded, and then everything it imports.
+    fn load_file(&mut self, path: Vec<String>, file: PathBuf, span: &Span) -> usize {
+        let name = path.join("::");
+        let canonical = fs::canonicalize(&file).unwrap_or(file.clone());
+
+        if let Some(&index) = self.by_file.get(&canonical) {
+            self.by_path.insert(path, index);
+            return index;
+        }
+
+        let text = fs::read_to_string(&file).unwrap_or_else(|e| {
+            Diagnostic::error(format!("Cannot read module `{}` from `{}` due to: {}", name, file.display(), e))
+                .with_code(&codes::E0032)
+                .with_span(span)
+                .abort()
+        });
+
+        let source = diagnostics::add_source(file.display().to_string(), text);
+        let tokens = Lexer::new(&source.text, source.id).tokenize().unwrap_or_else(|diag| diag.abort());
+        let program = Parser::with_arena(tokens, mem::take(&mut self.arena)).parse().unwrap_or_else(|diag| diag.abort());
+
+        // `#![no_std]` is only read from the entry file
+        let (imports, defs, blocks, expr_arena) = Defs::split(program);
+        self.arena = expr_arena;
+
+        let index = self.add_file(path, name, imports, defs, blocks);
+
+        self.by_file.insert(canonical, index);
+        self.load_imports(index);
+
+        index
+    }
+
+    fn find(&self, place: Place, path: &[String]) -> Option<PathBuf> {
+        let roots = match (place, &self.std) {
+            (Place::Package, _) => self.roots.as_slice(),
+            (Place::Std, Ok(std)) => slice::from_ref(std),
+            (Place::Std, Err(_)) => return None,
+        };
+
+        roots.iter()
+            .map(|root| path.iter().fold(root.clone(), |dir, part| dir.join(part)).with_extension("lit"))
+            .find(|file| file.is_file())
+    }
+
+    fn check_std(&self, span: &Span) {
+        let Err(tried) = &self.std else {
+            return;
+        };
+
+        let mut diag = Diagnostic::error("Cannot find the standard library".to_string())
+            .with_code(&codes::E0038)
+            .with_span(span);
+
+        if !tried.is_empty() {
+            let dirs = tried.iter().map(|dir| format!("`{}`", dir.display())).collect::<Vec<_>>();
+            diag = diag.with_note(format!("looked in: {}", dirs.join(", ")));
+        }
+
+        diag.with_note("set `LIT_HOME` or pass `--sysroot` to the directory that contains `std_lib`".to_string())
+            .abort()
+    }
+
+    fn report_cycle(&self, target: usize) -> ! {
+        let start = self.loading.iter().position(|(m, _)| *m == target).unwrap();
+        let cycle = &self.loading[start..];
+
+        let names = cycle.iter()
+            .map(|(m, _)| format!("`{}`", self.modules[*m].name))
+            .chain([format!("`{}`", self.modules[target].name)])
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        let (_, span) = cycle.last().unwrap();
+        let mut diag = Diagnostic::error(format!("Import cycle: {}", names))
+            .with_code(&codes::E0033)
+            .with_span(span.as_ref().unwrap());
+
+        for (pair, (_, span)) in cycle.windows(2).zip(cycle) {
+            let message = format!("`{}` imports `{}` here", self.modules[pair[0].0].name, self.modules[pair[1].0].name);
+            diag = diag.with_label(span.as_ref().unwrap(), message);
+        }
+
+        diag.abort()
+    }
+
+    /// Binds every import, then renames items and the names that refer to
+    /// them to qualified names.
+    fn link(mut self) -> Program {
+        for index in 0..self.modules.len() {
+            self.bind_imports(index);
+        }
+
+        let mut program = Program {
+            imports: Vec::new(),
+            funcs: Vec::new(),
+            consts: Vec::new(),
+            modules: Vec::new(),
+            classes: Vec::new(),
+            enums: Vec::new(),
+            interfaces: Vec::new(),
+            impls: Vec::new(),
+            llvm: Vec::new(),
+            no_std: None,
+            templates: Templates::default(),
+            expr_arena: ExprArena::new(),
+        };
+
+        for index in 0..self.modules.len() {
+            let defs = mem::take(&mut self.modules[index].defs);
+
+            for mut func in defs.funcs {
+                self.rewrite_func(index, &mut func, false);
+                func.name = self.modules[index].qualify(&func.name);
+                program.funcs.push(func);
+            }
+
+            for mut constant in defs.consts {
+                self.rewrite_expr(index, constant.expr_id, &HashSet::new());
+                constant.name = self.modules[index].qualify(&constant.name);
+                program.consts.push(constant);
+            }
+
+            for mut class in defs.classes {
+                self.rewrite_class(index, &mut class);
+                class.name = self.modules[index].qualify(&class.name);
+                program.classes.push(class);
+            }
+
+            for mut _impl in defs.impls {
+                self.rewrite_impl(index, &mut _impl);
+                program.impls.push(_impl);
+            }
+
+            for mut _enum in defs.enums {
+                let qualified = self.modules[index].qualify(&_enum.name);
+                self.rewrite_enum(index, &qualified, &mut _enum);
+                _enum.name = qualified;
+                program.enums.push(_enum);
+            }
+
+            for mut interface in defs.interfaces {
+                self.rewrite_interface(index, &mut interface);
+                interface.name = self.modules[index].qualify(&interface.name);
+                program.interfaces.push(interface);
+            }
+
+            program.llvm.extend(defs.llvm);
+
+            program.imports.append(&mut self.modules[index].imports);
+        }
+
+        program.expr_arena = self.arena;
+
+        program
+    }
+
+    fn bind_imports(&mut self, index: usize) {
+        let mut imported: HashMap<String, Entry> = HashMap::new();
+        let mut imported_types: HashMap<String, Entry> = HashMap::new();
+        let module = &self.modules[index];
+
+        for (import, &(file, len)) in module.imports.iter().zip(&module.import_targets) {
+            let mut target = file;
+
+            for segment in &import.path[len..import.path.len() - 1] {
+                target = match self.modules[target].items.get(segment) {
+                    Some(entry @ Entry { item: Item::Module(inner), .. }) => {
+                        self.check_visible(index, target, segment, entry, &import.span);
+                        *inner
+                    }
+                    _ => self.report_missing(index, target, segment, &import.span),
+                };
+            }
+
+            let item = import.item();
+            let found = [
+                (self.modules[target].items.get(item), &module.items, &mut imported),
+                (self.modules[target].types.get(item), &module.types, &mut imported_types),
+            ];
+
+            if found.iter().all(|(entry, _, _)| entry.is_none()) {
+                self.report_missing(index, target, item, &import.item_span);
+            }
+
+            for (entry, defined, imported) in found {
+                let Some(entry) = entry else {
+                    continue;
+                };
+
+                self.check_visible(index, target, item, entry, &import.item_span);
+
+                if let Some(Entry { span: previous, .. }) = defined.get(item).or(imported.get(item)) {
+                    Diagnostic::error(format!("`{}` is defined more than once in module `{}`", item, module.name))
+                        .with_code(&codes::E0034)
+                        .with_span(&import.span)
+                        .with_label(previous, format!("`{}` is first defined here", item))
+                        .abort();
+                }
+
+                imported.insert(item.to_string(), Entry { item: entry.item.clone(), span: import.span.clone(), is_pub: false });
+            }
+        }
+
+        self.modules[index].imported = imported;
+        self.modules[index].imported_types = imported_types;
+    }
+
+    fn report_missing(&self, from: usize, module: usize, name: &str, span: &Span) -> ! {
+        Diagnostic::error(format!("Module `{}` has no item named `{}`", self.modules[module].name, name))
+            .with_code(&codes::E0032)
+            .with_span(span)
+            .with_suggestion(span, closest(name, self.visible_names(from, module)))
+            .abort()
+    }
+
+    /// Whether `entry`, an item of module `owner`, can be named from module
+    /// `from`. Items without `pub` are private to the module that defines
+    /// them and the modules nested in it.
+    fn is_visible(&self, from: usize, owner: usize, entry: &Entry) -> bool {
+        entry.is_pub || iter::successors(Some(from), |&m| self.modules[m].parent).any(|m| m == owner)
+    }
+
+    fn visible_names(&self, from: usize, module: usize) -> impl Iterator<Item = &str> {
+        self.modules[module].items.iter()
+            .chain(&self.modules[module].types)
+            .filter(move |(_, entry)| self.is_visible(from, module, entry))
+            .map(|(name, _)| name.as_str())
+    }
+
+    fn check_visible(&self, from: usize, owner: usize, name: &str, entry: &Entry, span: &Span) {
+        if self.is_visible(from, owner, entry) {
+            return;
+        }
+
+        let kind = match entry.item {
+            Item::Func(_) => "Function",
+            Item::Const(_) => "Constant",
+            Item::Module(_) => "Module",
+            Item::Type(_) => "Type",
+        };
+
+        Diagnostic::error(format!("{} `{}` is private to module `{}`", kind, name, self.modules[owner].name))
+            .with_code(&codes::E0037)
+            .with_span(span)
+            .with_label(&entry.span, format!("`{}` is defined here without `pub`", name))
+            .abort()
+    }
+
+    /// Looks up `path` from inside module `index`. The first segment is
+    /// searched in the module, then in the modules around it and in the
+    /// imports of its file; the rest are items of the modules before them,
+    /// which must be visible from module `index`.
+    fn lookup(&self, index: usize, path: &[&str], span: &Span) -> Option<Item> {
+        let (first, rest) = path.split_first()?;
+
+        let mut item = self.in_scope(index, first).map(|entry| entry.item.clone());
+
+        for segment in rest {
+            let Some(Item::Module(module)) = item else {
+                return None;
+            };
+
+            let entry = self.modules[module].items.get(*segment)?;
+            self.check_visible(index, module, segment, entry, span);
+
+            item = Some(entry.item.clone());
+        }
+
+        item
+    }
+
+    /// The entry for `name` in module `index` or in the modules around it.
+    fn in_scope(&self, index: usize, name: &str) -> Option<&Entry> {
+        iter::successors(Some(index), |&m| self.modules[m].parent)
+            .find_map(|m| self.modules[m].items.get(name).or(self.modules[m].imported.get(name)))
+    }
+
+    /// Looks up the type that `path` names from inside module `index`, and
+    /// returns its qualified name. Like [`Loader::lookup`], but the last
+    /// segment is searched among types.
+    fn lookup_type(&self, index: usize, path: &str, span: &Span) -> Option<String> {
+        let segments: Vec<&str> = path.split("::").collect();
+        let (last, parent) = segments.split_last().unwrap();
+
+        let entry = match parent.is_empty() {
+            true => iter::successors(Some(index), |&m| self.modules[m].parent)
+                .find_map(|m| self.modules[m].types.get(*last).or(self.modules[m].imported_types.get(*last))),
+
+            false => {
+                let Some(Item::Module(module)) = self.lookup(index, parent, span) else {
+                    return None;
+                };
+
+                let entry = self.modules[module].types.get(*last)?;
+                self.check_visible(index, module, last, entry, span);
+
+                Some(entry)
+            }
+        };
+
+        match &entry?.item {
+            Item::Type(qualified) => Some(qualified.clone()),
+            _ => None,
+        }
+    }
+
+    /// Looks up the enum variant that `path` names from inside module
+    /// `index`: `Enum::Variant` through any path to the enum, or a bare
+    /// variant name inside the methods of its enum. Returns the qualified
+    /// name of the enum and the name of the variant.
+    fn lookup_variant(&self, index: usize, path: &str, span: &Span) -> Option<(String, String)> {
+        match path.rsplit_once("::") {
+            Some((parent, last)) => self.lookup_type(index, parent, span).map(|_enum| (_enum, last.to_string())),
+            None => self.variants.as_ref()
+                .filter(|(_, names)| names.iter().any(|n| n == path))
+                .map(|(_enum, _)| (_enum.clone(), path.to_string())),
+        }
+    }
+
+    /// Reports a use of a builtin from the prelude when `#![no_std]` left
+    /// the prelude out.
+    fn check_builtin(&self, index: usize, name: &str, span: &Span) {
+        if !self.no_std || self.modules[index].is_std || !PRELUDE.contains(&name) || self.in_scope(index, name).is_some() {
+            return;
+        }
+
+        Diagnostic::error(format!("Function `{}` is not defined", name))
+            .with_code(&codes::E0026)
+            .with_span(span)
+            .with_note(format!("`{}` is part of the prelude, which `#![no_std]` leaves out", name))
+            .abort()
+    }
+
+    /// Rewrites the signature and body of `func`. In a method, `self` is in
+    /// scope whether or not it is written.
+    fn rewrite_func(&mut self, index: usize, func: &mut FuncDef, is_method: bool) {
+        let outer = self.generics.len();
+        self.generics.extend(func.generics.iter().map(|g| g.name.clone()));
+
+        self.rewrite_generics(index, &mut func.generics);
+
+        for attr in &mut func.attrs {
+            if let AttrKind::Bound(_, bounds) = &mut attr.kind {
+                for bound in bounds {
+                    self.rewrite_type(index, bound, &attr.span);
+                }
+            }
+        }
+
+        let mut locals: HashSet<String> = func.params.iter().map(|p| p.name.clone()).collect();
+
+        if is_method || func.receiver.is_some() {
+            locals.insert("self".to_string());
+        }
+
+        for param in &mut func.params {
+            self.rewrite_type(index, &mut param._type, &param.span);
+        }
+
+        if let Some(ret_type) = &mut func.ret_type {
+            self.rewrite_type(index, ret_type, &func.span);
+        }
+
+        self.rewrite_block(index, &mut func.body, locals);
+        self.generics.truncate(outer);
+    }
+
+    /// Points the interfaces that bound `generics` at their definitions.
+    fn rewrite_generics(&mut self, index: usize, generics: &mut [GenericParam]) {
+        for generic in generics {
+            for bound in &mut generic.bounds {
+                self.rewrite_type(index, bound, &generic.span);
+            }
+        }
+    }
+
+    fn rewrite_class(&mut self, index: usize, class: &mut ClassDef) {
+        self.generics = class.generics.iter().map(|g| g.name.clone()).collect();
+        self.rewrite_generics(index, &mut class.generics);
+
+        for interface in &mut class.interfaces {
+            self.rewrite_type(index, interface, &class.span);
+        }
+
+        for field in &mut class.fields {
+            self.rewrite_type(index, &mut field._type, &field.span);
+        }
+
+        for ctor in &mut class.ctors {
+            let mut locals: HashSet<String> = ctor.params.iter().map(|p| p.name.clone()).collect();
+            locals.insert("self".to_string());
+
+            for param in &mut ctor.params {
+                self.rewrite_type(index, &mut param._type, &param.span);
+            }
+
+            self.rewrite_block(index, &mut ctor.body, locals);
+        }
+
+        for method in &mut class.methods {
+            self.rewrite_func(index, method, true);
+        }
+
+        self.generics.clear();
+    }
+
+    fn rewrite_enum(&mut self, index: usize, qualified: &str, _enum: &mut EnumDef) {
+        self.generics = _enum.generics.iter().map(|g| g.name.clone()).collect();
+        self.rewrite_generics(index, &mut _enum.generics);
+
+        for interface in &mut _enum.interfaces {
+            self.rewrite_type(index, interface, &_enum.span);
+        }
+
+        for variant in &mut _enum.variants {
+            for field in &mut variant.fields {
+                self.rewrite_type(index, field, &variant.span);
+            }
+        }
+
+        self.variants = Some((qualified.to_string(), _enum.variants.iter().map(|v| v.name.clone()).collect()));
+
+        for method in &mut _enum.methods {
+            self.rewrite_func(index, method, true);
+        }
+
+        self.variants = None;
+        self.generics.clear();
+    }
+
+    fn rewrite_interface(&mut self, index: usize, interface: &mut InterfaceDef) {
+        for method in &mut interface.methods {
+            for param in &mut method.params {
+                self.rewrite_type(index, &mut param._type, &param.span);
+            }
+
+            if let Some(ret_type) = &mut method.ret_type {
+                self.rewrite_type(index, ret_type, &method.span);
+            }
+        }
+    }
+
+    fn rewrite_impl(&mut self, index: usize, _impl: &mut ImplDef) {
+        if let Some(interface) = &mut _impl.interface {
+            self.rewrite_type(index, interface, &_impl.span);
+        }
+
+        for block in &mut _impl.blocks {
+            self.rewrite_type(index, &mut block.target, &block.span);
+
+            for method in &mut block.methods {
+                self.rewrite_func(index, method, true);
+            }
+        }
+    }
+
+    /// Points the class, enum and interface names in `_type` at the types
+    /// they refer to. Unknown names are left for the analyzer to report.
+    fn rewrite_type(&mut self, index: usize, _type: &mut Type, span: &Span) {
+        match _type {
+            Type::Named(name, args) if args.is_empty() && self.generics.contains(name) => {}
+
+            Type::Named(name, args) => {
+                if let Some(qualified) = self.lookup_type(index, name, span) {
+                    *name = qualified;
+                }
+
+                for arg in args {
+                    self.rewrite_type(index, arg, span);
+                }
+            }
+
+            Type::Array(inner) | Type::Ptr(inner) | Type::Fallible(inner) => self.rewrite_type(index, inner, span),
+
+            Type::Lambda(params, ret) => {
+                for param in params {
+                    self.rewrite_type(index, param, span);
+                }
+
+                self.rewrite_type(index, ret, span);
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Rewrites the statements of `block`, whose variables go out of scope
+    /// at its end.
+    fn rewrite_block(&mut self, index: usize, block: &mut Block, mut locals: HashSet<String>) {
+        for node in block.mut_stmts() {
+            self.rewrite_stmt(index, node, &mut locals);
+        }
+    }
+
+    fn rewrite_stmt(&mut self, index: usize, node: &mut StmtNode, locals: &mut HashSet<String>) {
+        match &mut node.stmt {
+            Stmt::VarDecl(v) => {
+                self.rewrite_type(index, &mut v._type, &node.span);
+                self.rewrite_expr(index, v.expr_id, locals);
+                locals.insert(v.name.clone());
+            }
+            Stmt::Println(id) => {
+                self.check_builtin(index, "println", &node.span);
+                self.rewrite_expr(index, *id, locals);
+            }
+            Stmt::Unreachable(Some(id)) | Stmt::Return(Some(id)) | Stmt::Expr(id) => {
+                self.rewrite_expr(index, *id, locals);
+            }
+            Stmt::Unreachable(None) | Stmt::Return(None) | Stmt::Llvm(_) => {}
+            Stmt::Assign(target, _, value) => {
+                self.rewrite_expr(index, *target, locals);
+                self.rewrite_expr(index, *value, locals);
+            }
+            Stmt::If(cond, then, otherwise) => {
+                self.rewrite_expr(index, *cond, locals);
+                self.rewrite_block(index, then, locals.clone());
+
+                if let Some(otherwise) = otherwise {
+                    self.rewrite_block(index, otherwise, locals.clone());
+                }
+            }
+            Stmt::While(cond, body) => {
+                self.rewrite_expr(index, *cond, locals);
+                self.rewrite_block(index, body, locals.clone());
+            }
+            Stmt::For(ForLoop::Each { index: counter, elem, iter, body }) => {
+                self.rewrite_expr(index, *iter, locals);
+
+                let mut inner = locals.clone();
+                inner.extend(counter.iter().chain([&*elem]).cloned());
+
+                self.rewrite_block(index, body, inner);
+            }
+            Stmt::For(ForLoop::Counted { init, cond, step, body }) => {
+                let mut inner = locals.clone();
+
+                self.rewrite_stmt(index, init, &mut inner);
+                self.rewrite_expr(index, *cond, &inner);
+                self.rewrite_stmt(index, step, &mut inner);
+                self.rewrite_block(index, body, inner);
+            }
+            Stmt::Switch(scrutinee, cases) => {
+                self.rewrite_expr(index, *scrutinee, locals);
+
+                for case in cases {
+                    self.rewrite_pattern(index, &mut case.pattern);
+
+                    let mut inner = locals.clone();
+                    inner.extend(case.pattern.names());
+
+                    self.rewrite_block(index, &mut case.body, inner);
+                }
+            }
+        }
+    }
+
+    /// Points the `Enum::Variant` paths in `pattern` at their enums. Plain
+    /// names are left for the resolver, which knows the type they match.
+    fn rewrite_pattern(&self, index: usize, pattern: &mut Pattern) {
+        if let Pattern::Name(path, span) | Pattern::Variant(path, _, span) = pattern
+            && path.contains("::")
+        {
+            match self.lookup_variant(index, path, span) {
+                Some((_enum, variant)) => *path = format!("{}::{}", _enum, variant),
+                None => self.report_unresolved(index, "variant", path, span),
+            }
+        }
+
+        if let Pattern::Variant(_, fields, _) = pattern {
+            for field in fields {
+                self.rewrite_pattern(index, field);
+            }
+        }
+    }
+
+    /// Points the names in expression `id` at the items they refer to.
+    /// Unknown plain names are left for the analyzer, which knows about
+    /// builtins and reports the rest; unknown paths are reported here.
+    fn rewrite_expr(&mut self, index: usize, id: ExprId, locals: &HashSet<String>) {
+        let node = self.arena.get(id).clone();
+
+        if let Expr::Var(name) | Expr::Call(name, ..) = &node.expr
+            && !locals.contains(name)
+        {
+            let first = name.split("::").next().unwrap();
+
+            self.check_builtin(index, first, &node.span);
+        }
+
+        let renamed = match &node.expr {
+            Expr::Var(name) if !locals.contains(name) => {
+                match self.lookup(index, &name.split("::").collect::<Vec<_>>(), &node.span) {
+                    Some(Item::Const(qualified)) => Some(Expr::Const(qualified)),
+                    _ => match self.lookup_variant(index, name, &node.span) {
+                        Some((_enum, variant)) => Some(Expr::Variant(_enum, variant, Vec::new())),
+                        None if name.contains("::") => self.report_unresolved(index, "constant", name, &node.span),
+                        None => None,
+                    },
+                }
+            }
+
+            // a class is called to construct it, and so is a variant with
+            // fields; `T::parse(s)` is left for the resolver to point at the
+            // method of the type argument for `T`
+            Expr::Call(name, type_args, args) => {
+                let mut type_args = type_args.clone();
+
+                for arg in &mut type_args {
+                    self.rewrite_type(index, arg, &node.span);
+                }
+
+                match self.lookup(index, &name.split("::").collect::<Vec<_>>(), &node.span) {
+                    Some(Item::Func(qualified)) => Some(Expr::Call(qualified, type_args, args.clone())),
+                    _ => match self.lookup_type(index, name, &node.span) {
+                        Some(qualified) => Some(Expr::Call(qualified, type_args, args.clone())),
+                        None => match self.lookup_variant(index, name, &node.span) {
+                            Some((_enum, variant)) => Some(Expr::Variant(_enum, variant, args.clone())),
+                            None if name.contains("::") && !self.is_generic_path(name) => {
+                                self.report_unresolved(index, "function", name, &node.span)
+                            }
+                            None => Some(Expr::Call(name.clone(), type_args, args.clone())),
+                        },
+                    },
+                }
+            }
+
+            Expr::MethodCall(receiver, name, type_args, args) => {
+                let mut type_args = type_args.clone();
+
+                for arg in &mut type_args {
+                    self.rewrite_type(index, arg, &node.span);
+                }
+
+                Some(Expr::MethodCall(*receiver, name.clone(), type_args, args.clone()))
+            }
+
+            Expr::Cast(to, expr) => {
+                let mut to = to.clone();
+                self.rewrite_type(index, &mut to, &node.span);
+
+                Some(Expr::Cast(to, *expr))
+            }
+
+            _ => None,
+        };
+
+        if let Some(expr) = renamed {
+            self.arena.set(id, ExprNode::new(expr, node.span.clone()));
+        }
+
+        // names bound by a pattern are in scope in the expression of its arm
+        if let Expr::Switch(scrutinee, arms) = &node.expr {
+            let mut arms = arms.clone();
+
+            self.rewrite_expr(index, *scrutinee, locals);
+
+            for arm in &mut arms {
+                self.rewrite_pattern(index, &mut arm.pattern);
+
+                let mut inner = locals.clone();
+                inner.extend(arm.pattern.names());
+
+                self.rewrite_expr(index, arm.expr, &inner);
+            }
+
+            self.arena.set(id, ExprNode::new(Expr::Switch(*scrutinee, arms), node.span.clone()));
+            return;
+        }
+
+        for child in node.expr.children() {
+            self.rewrite_expr(index, child, locals);
+        }
+    }
+
+    /// Whether `path` starts with a generic parameter in scope, as
+    /// `T::parse` does.
+    fn is_generic_path(&self, path: &str) -> bool {
+        path.split_once("::").is_some_and(|(first, _)| self.generics.iter().any(|g| g == first))
+    }
+
+    fn report_unresolved(&self, index: usize, kind: &str, path: &str, span: &Span) -> ! {
+        let segments: Vec<&str> = path.split("::").collect();
+        let (last, parent) = segments.split_last().unwrap();
+
+        let suggestion = match self.lookup(index, parent, span) {
+            Some(Item::Module(module)) => closest(last, self.visible_names(index, module))
+                .map(|name| format!("{}::{}", parent.join("::"), name)),
+            _ => None,
+        };
+
+        Diagnostic::error(format!("Cannot find {} `{}` in this scope", kind, path))
+            .with_code(&codes::E0035)
+            .with_span(span)
+            .with_suggestion(span, suggestion.as_deref())
+            .abort()
+    }
+}
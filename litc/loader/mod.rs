@@ -0,0 +1,4 @@
+mod loader;
+mod sysroot;
+
+pub use loader::load;
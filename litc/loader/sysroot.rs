@@ -0,0 +1,32 @@
+use std::env;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Directory of the standard library inside a Lit installation.
+const STD_DIR: &str = "std_lib";
+
+/// Finds the standard library of the installation at `--sysroot`, else at
+/// `$LIT_HOME`, else in one of the directories above the running compiler,
+/// which covers both an installed `<home>/bin/litc` and a `target/debug/litc`
+/// built in this repository.
+///
+/// On failure, returns every directory that was tried.
+pub fn find_std(sysroot: Option<&Path>) -> Result<PathBuf, Vec<PathBuf>> {
+    let homes: Vec<PathBuf> = match (sysroot, env::var_os("LIT_HOME")) {
+        (Some(sysroot), _) => vec![sysroot.to_path_buf()],
+        (None, Some(home)) if !home.is_empty() => vec![PathBuf::from(home)],
+        _ => env::current_exe()
+            .ok()
+            // the binary may be reached through a symlink, e.g. `/usr/local/bin/litc`
+            .and_then(|exe| exe.canonicalize().ok())
+            .map(|exe| exe.ancestors().skip(1).map(Path::to_path_buf).collect())
+            .unwrap_or_default(),
+    };
+
+    let candidates: Vec<PathBuf> = homes.iter().map(|home| home.join(STD_DIR)).collect();
+
+    match candidates.iter().find(|dir| dir.is_dir()) {
+        Some(dir) => Ok(dir.clone()),
+        None => Err(candidates),
+    }
+}
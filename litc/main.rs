@@ -1,23 +1,22 @@
-mod analyzer;
-mod ast;
-mod codegen;
-mod lexer;
-mod parser;
-mod resolver;
-mod utils;
-mod hir;
-
 use std::path::PathBuf;
 use std::time::Instant;
 use std::env;
 use std::fs;
 use std::process;
 
-use ast::*;
-use lexer::Lexer;
-use parser::Parser;
-use resolver::resolve;
-use analyzer::analyze;
+use litc::ast::*;
+use litc::codegen;
+use litc::diagnostics;
+use litc::generate_plain_error;
+use litc::lexer::Lexer;
+use litc::loader;
+use litc::parser::Parser;
+use litc::resolver::resolve;
+use litc::analyzer::analyze;
+use litc::lint::Level;
+use litc::lint::LintConfig;
+use litc::diagnostics::ErrorFormat;
+use litc::diagnostics::codes;
 
 const VERSION: &str = "v0.1.0";
 const HELP_TEXT: &str = "litc - Lit language compiler\n\
@@ -28,16 +27,32 @@ const HELP_TEXT: &str = "litc - Lit language compiler\n\
 \x1B[1m[Options]\x1B[0m\n  \
   -h, --help       Show this help message\n  \
   -v, --version    Show version\n  \
+  --explain <code> Explain an error or lint code, e.g. `E0019`\n  \
   -T               Mark time of compilation\n  \
   -S, --check      Check for semantic errors only (no output)\n  \
+  --parse-only     Check the syntax of <file.lit> alone, without its imports\n  \
+  -C, --checks     Abort on integer overflow and division by zero\n  \
+  -L <dir>         Also search <dir> for imported modules\n  \
+  --sysroot <dir>  Use the standard library of the Lit installation in <dir>\n                   (default: $LIT_HOME, else found next to litc)\n  \
+  -W <lint>        Warn about <lint> (`warnings` selects every lint)\n  \
+  -A <lint>        Allow <lint>\n  \
+  -D <lint>        Deny <lint>\n  \
+  -Werror          Treat every warning as an error\n  \
+  --error-format=<human|json>\n                   Print diagnostics as text or as one JSON object per line\n  \
   -o <file>        Set output file path (default: <input>.lit)\
 ";
 
 struct Options {
     input: PathBuf,
     output: PathBuf,
+    search_paths: Vec<PathBuf>,
+    sysroot: Option<PathBuf>,
     mark_time: bool,
     check_only: bool,
+    parse_only: bool,
+    checks: bool,
+    lints: LintConfig,
+    error_format: ErrorFormat,
     print_ast: bool,
     print_tokens: bool,
 }
@@ -52,8 +67,14 @@ impl Options {
 
         let mut input: Option<PathBuf> = None;
         let mut output: Option<PathBuf> = None;
+        let mut search_paths = Vec::new();
+        let mut sysroot = None;
         let mut mark_time = false;
         let mut check_only = false;
+        let mut parse_only = false;
+        let mut checks = false;
+        let mut lints = LintConfig::new();
+        let mut error_format = ErrorFormat::Human;
         let mut print_ast = false;
         let mut print_tokens = false;
 
@@ -70,6 +91,16 @@ impl Options {
                     process::exit(0);
                 }
 
+                "--explain" => {
+                    i += 1;
+                    if i >= args.len() {
+                        generate_plain_error!("Expected error code after `--explain`");
+                    }
+
+                    Options::explain(&args[i]);
+                    process::exit(0);
+                }
+
                 "-T" => {
                     mark_time = true;
                 }
@@ -78,6 +109,35 @@ impl Options {
                     check_only = true;
                 }
 
+                "--parse-only" => {
+                    parse_only = true;
+                }
+
+                "-C" | "--checks" => {
+                    checks = true;
+                }
+
+                "-Werror" => {
+                    lints.set_warnings_as_errors();
+                }
+
+                flag @ ("-W" | "-A" | "-D") => {
+                    i += 1;
+                    if i >= args.len() {
+                        generate_plain_error!("Expected lint name after `{}`", flag);
+                    }
+
+                    Options::set_lint(&mut lints, flag, &args[i]);
+                }
+
+                arg if arg.starts_with("--error-format=") => {
+                    error_format = match &arg["--error-format=".len()..] {
+                        "human" => ErrorFormat::Human,
+                        "json" => ErrorFormat::Json,
+                        other => generate_plain_error!("Unknown error format: `{}` (expected `human` or `json`)", other),
+                    };
+                }
+
                 "-TOK" => {
                     print_tokens = true;
                 }
@@ -86,6 +146,24 @@ impl Options {
                     print_ast = true;
                 }
 
+                "-L" => {
+                    i += 1;
+                    if i >= args.len() {
+                        generate_plain_error!("Expected directory after `-L`");
+                    }
+
+                    search_paths.push(PathBuf::from(&args[i]));
+                }
+
+                "--sysroot" => {
+                    i += 1;
+                    if i >= args.len() {
+                        generate_plain_error!("Expected directory after `--sysroot`");
+                    }
+
+                    sysroot = Some(PathBuf::from(&args[i]));
+                }
+
                 "-o" => {
                     i += 1;
                     if i >= args.len() {
@@ -95,13 +173,17 @@ impl Options {
                     output = Some(PathBuf::from(&args[i]));
                 }
 
+                arg if ["-W", "-A", "-D"].iter().any(|flag| arg.starts_with(flag)) => {
+                    Options::set_lint(&mut lints, &arg[..2], &arg[2..]);
+                }
+
                 arg if arg.starts_with("-") => {
                     generate_plain_error!("Unexpected option: `{}`", arg);
                 }
 
                 path => {
                     if input.is_some() {
-                        generate_plain_error!("Multiple input files are not supported yet. sorry!");
+                        generate_plain_error!("Expected one input file, but got `{}` as well (other modules are loaded through `import`)", path);
                     }
 
                     input = Some(PathBuf::from(path));
@@ -116,7 +198,28 @@ impl Options {
 
         let output = output.unwrap_or_else(|| input.with_extension("ll"));
 
-        Options { input, output, mark_time, check_only, print_ast, print_tokens }
+        Options { input, output, search_paths, sysroot, mark_time, check_only, parse_only, checks, lints, error_format, print_ast, print_tokens }
+    }
+
+    fn set_lint(lints: &mut LintConfig, flag: &str, name: &str) {
+        let level = match flag {
+            "-A" => Level::Allow,
+            "-W" => Level::Warn,
+            _ => Level::Deny,
+        };
+
+        if !lints.set(name, level) {
+            generate_plain_error!("Unknown lint: `{}`", name);
+        }
+    }
+
+    fn explain(code: &str) {
+        let Some(entry) = codes::lookup(code) else {
+            generate_plain_error!("`{}` is not a valid error code", code);
+        };
+
+        println!("{}\n", diagnostics::paint_stdout("1", &format!("{}: {}", entry.code, entry.title)));
+        print!("{}", entry.explanation);
     }
 
     fn version() {
@@ -132,6 +235,8 @@ fn main() {
     let argv: Vec<String> = env::args().skip(1).collect();
     let options = Options::parse(&argv);
 
+    diagnostics::init(options.error_format);
+
     if options.input.extension().and_then(|e| e.to_str()) != Some("lit") {
         generate_plain_error!("Input file must have `.lit` extension");
     }
@@ -140,14 +245,31 @@ fn main() {
         generate_plain_error!("Cannot read `{}` due to: {}", options.input.display(), e);
     });
 
+    let source = diagnostics::add_source(options.input.display().to_string(), src);
+
     let now = Instant::now();
-    let tokens = Lexer::new(&src).tokenize();
+    let tokens = Lexer::new(&source.text, source.id).tokenize().unwrap_or_else(|diag| diag.abort());
 
     if options.print_tokens {
         tokens.iter().for_each(|t| println!("{}", t));
     }
 
-    let mut program = Parser::new(tokens).parse();
+    let program = Parser::new(tokens).parse().unwrap_or_else(|diag| diag.abort());
+
+    if options.parse_only {
+        if options.print_ast {
+            print_ast(&program);
+        }
+
+        if options.mark_time {
+            println!("Took: {:?}", now.elapsed());
+        }
+
+        println!("{}: No syntax errors found", diagnostics::paint_stdout("1;32", "[Parse complete]"));
+        return;
+    }
+
+    let mut program = loader::load(program, &options.input, &options.search_paths, options.sysroot.as_deref());
 
     // TODO: See Resolver through to the end
     resolve(&mut program);
@@ -156,18 +278,18 @@ fn main() {
         print_ast(&program);
     }
 
-    analyze(&program);
+    analyze(&mut program, &options.lints);
 
     if options.check_only {
         if options.mark_time {
             println!("Took: {:?}", now.elapsed());
         }
 
-        println!("\x1B[1;32m[Analysis complete]\x1B[0m: No Semantic errors found");
+        println!("{}: No Semantic errors found", diagnostics::paint_stdout("1;32", "[Analysis complete]"));
         return;
     }
 
-    let ir = codegen::generate(program);
+    let ir = codegen::generate(program, &diagnostics::source_map(), options.checks);
 
     if options.mark_time {
         println!("Took: {:?}", now.elapsed());
@@ -187,6 +309,8 @@ fn print_expr(expr_arena: &ExprArena, expr_id: ExprId, indent: usize) {
 
         Expr::Var(name) => println!("{}${},", padding, name),
 
+        Expr::Const(name) => println!("{}{},", padding, name),
+
         Expr::Binary (op, left, right) => {
             println!("{}Binary {{", padding);
             print_expr(expr_arena, *left, indent + 2);
@@ -208,9 +332,97 @@ fn print_expr(expr_arena: &ExprArena, expr_id: ExprId, indent: usize) {
             println!("{}  as {}", padding, to);
             println!("{}}}", padding);
         }
+
+        Expr::Field (expr, name) => {
+            println!("{}Field {{", padding);
+            print_expr(expr_arena, *expr, indent + 2);
+            println!("{}  .{}", padding, name);
+            println!("{}}}", padding);
+        }
+
+        Expr::Index (expr, index) => {
+            println!("{}Index {{", padding);
+            print_expr(expr_arena, *expr, indent + 2);
+            print_expr(expr_arena, *index, indent + 2);
+            println!("{}}}", padding);
+        }
+
+        Expr::Call (name, type_args, args) => {
+            println!("{}Call {}{}(", padding, name, type_args_to_string(type_args));
+            for arg in args {
+                print_expr(expr_arena, *arg, indent + 2);
+            }
+            println!("{})", padding);
+        }
+
+        Expr::Construct (name, ctor, args) => {
+            println!("{}Construct {}#{}(", padding, name, ctor);
+            for arg in args {
+                print_expr(expr_arena, *arg, indent + 2);
+            }
+            println!("{})", padding);
+        }
+
+        Expr::Variant (_enum, name, args) => {
+            println!("{}Variant {}::{}(", padding, _enum, name);
+            for arg in args {
+                print_expr(expr_arena, *arg, indent + 2);
+            }
+            println!("{})", padding);
+        }
+
+        Expr::Invoke (owner, name, receiver, args) => {
+            println!("{}Invoke {{", padding);
+            print_expr(expr_arena, *receiver, indent + 2);
+            println!("{}  .{}.{}(", padding, owner, name);
+            for arg in args {
+                print_expr(expr_arena, *arg, indent + 4);
+            }
+            println!("{}  )", padding);
+            println!("{}}}", padding);
+        }
+
+        Expr::Associated (owner, name, args) => {
+            println!("{}Associated {}::{}(", padding, owner, name);
+            for arg in args {
+                print_expr(expr_arena, *arg, indent + 2);
+            }
+            println!("{})", padding);
+        }
+
+        Expr::MethodCall (receiver, name, type_args, args) => {
+            println!("{}MethodCall {{", padding);
+            print_expr(expr_arena, *receiver, indent + 2);
+            println!("{}  .{}{}(", padding, name, type_args_to_string(type_args));
+            for arg in args {
+                print_expr(expr_arena, *arg, indent + 4);
+            }
+            println!("{}  )", padding);
+            println!("{}}}", padding);
+        }
+
+        Expr::Switch (scrutinee, arms) => {
+            println!("{}Switch {{", padding);
+            print_expr(expr_arena, *scrutinee, indent + 2);
+            for arm in arms {
+                println!("{}  {} =>", padding, arm.pattern);
+                print_expr(expr_arena, arm.expr, indent + 4);
+            }
+            println!("{}}}", padding);
+        }
+
+        Expr::Null => println!("{}null,", padding),
     }
 }
 
+fn type_args_to_string(type_args: &[Type]) -> String {
+    if type_args.is_empty() {
+        return String::new();
+    }
+
+    format!("<{}>", type_args.iter().map(Type::to_string).collect::<Vec<_>>().join(", "))
+}
+
 fn print_stmt(expr_arena: &ExprArena, stmt: &Stmt, indent: usize) {
     let padding = " ".repeat(indent);
     match stmt {
@@ -222,9 +434,79 @@ fn print_stmt(expr_arena: &ExprArena, stmt: &Stmt, indent: usize) {
             print!("{}Println: ", padding);
             print_expr(expr_arena, *arg, indent);
         }
-        Stmt::Unreachable => {
+        Stmt::Unreachable(Some(msg)) => {
+            print!("{}Unreachable: ", padding);
+            print_expr(expr_arena, *msg, indent);
+        }
+        Stmt::Unreachable(None) => {
             println!("{}Unreachable", padding);
         }
+        Stmt::Return(Some(expr_id)) => {
+            print!("{}Return: ", padding);
+            print_expr(expr_arena, *expr_id, indent);
+        }
+        Stmt::Return(None) => {
+            println!("{}Return", padding);
+        }
+        Stmt::Expr(expr_id) => {
+            print!("{}Expr: ", padding);
+            print_expr(expr_arena, *expr_id, indent);
+        }
+        Stmt::Assign(target, op, value) => {
+            match op {
+                Some(op) => println!("{}Assign ({}=):", padding, op),
+                None => println!("{}Assign:", padding),
+            }
+            print_expr(expr_arena, *target, indent + 2);
+            print_expr(expr_arena, *value, indent + 2);
+        }
+        Stmt::If(cond, then, otherwise) => {
+            print!("{}If: ", padding);
+            print_expr(expr_arena, *cond, indent);
+            print_block(expr_arena, then, indent + 2);
+            if let Some(otherwise) = otherwise {
+                println!("{}Else:", padding);
+                print_block(expr_arena, otherwise, indent + 2);
+            }
+        }
+        Stmt::While(cond, body) => {
+            print!("{}While: ", padding);
+            print_expr(expr_arena, *cond, indent);
+            print_block(expr_arena, body, indent + 2);
+        }
+        Stmt::For(ForLoop::Each { index, elem, iter, body }) => {
+            match index {
+                Some(index) => print!("{}For {}, {} in: ", padding, index, elem),
+                None => print!("{}For {} in: ", padding, elem),
+            }
+            print_expr(expr_arena, *iter, indent);
+            print_block(expr_arena, body, indent + 2);
+        }
+        Stmt::For(ForLoop::Counted { init, cond, step, body }) => {
+            println!("{}For:", padding);
+            print_stmt(expr_arena, &init.stmt, indent + 2);
+            print!("{}  Cond: ", padding);
+            print_expr(expr_arena, *cond, indent + 2);
+            print_stmt(expr_arena, &step.stmt, indent + 2);
+            print_block(expr_arena, body, indent + 2);
+        }
+        Stmt::Switch(scrutinee, cases) => {
+            print!("{}Switch: ", padding);
+            print_expr(expr_arena, *scrutinee, indent);
+            for case in cases {
+                println!("{}  {} =>", padding, case.pattern);
+                print_block(expr_arena, &case.body, indent + 4);
+            }
+        }
+        Stmt::Llvm(code) => {
+            println!("{}Llvm {{{}}}", padding, code);
+        }
+    }
+}
+
+fn print_block(expr_arena: &ExprArena, block: &Block, indent: usize) {
+    for node in block.stmts() {
+        print_stmt(expr_arena, &node.stmt, indent);
     }
 }
 
@@ -238,6 +520,7 @@ fn print_ast(program: &Program) {
         match expr {
             Expr::Lit(lit) => println!("{}", lit),
             Expr::Var(name) => println!("Var({})", name),
+            Expr::Const(name) => println!("Const({})", name),
             Expr::Binary (op, left, right) => {
                 println!("Binary {{");
                 println!("    op: {:?}", op);
@@ -257,15 +540,195 @@ fn print_ast(program: &Program) {
                 println!("    to: {}", to);
                 println!("  }}");
             }
+            Expr::Field (expr, name) => {
+                println!("Field {{");
+                println!("    expr: {}", expr);
+                println!("    name: {}", name);
+                println!("  }}");
+            }
+            Expr::Index (expr, index) => {
+                println!("Index {{");
+                println!("    expr: {}", expr);
+                println!("    index: {}", index);
+                println!("  }}");
+            }
+            Expr::Call (name, type_args, args) => {
+                println!("Call {{");
+                println!("    name: {}{}", name, type_args_to_string(type_args));
+                println!("    args: {:?}", args);
+                println!("  }}");
+            }
+            Expr::Construct (name, ctor, args) => {
+                println!("Construct {{");
+                println!("    class: {}", name);
+                println!("    ctor: {}", ctor);
+                println!("    args: {:?}", args);
+                println!("  }}");
+            }
+            Expr::Variant (_enum, name, args) => {
+                println!("Variant {{");
+                println!("    enum: {}", _enum);
+                println!("    variant: {}", name);
+                println!("    args: {:?}", args);
+                println!("  }}");
+            }
+            Expr::Invoke (owner, name, receiver, args) => {
+                println!("Invoke {{");
+                println!("    receiver: {}", receiver);
+                println!("    method: {}.{}", owner, name);
+                println!("    args: {:?}", args);
+                println!("  }}");
+            }
+            Expr::Associated (owner, name, args) => {
+                println!("Associated {{");
+                println!("    method: {}::{}", owner, name);
+                println!("    args: {:?}", args);
+                println!("  }}");
+            }
+            Expr::MethodCall (receiver, name, type_args, args) => {
+                println!("MethodCall {{");
+                println!("    receiver: {}", receiver);
+                println!("    name: {}{}", name, type_args_to_string(type_args));
+                println!("    args: {:?}", args);
+                println!("  }}");
+            }
+            Expr::Switch (scrutinee, arms) => {
+                println!("Switch {{");
+                println!("    scrutinee: {}", scrutinee);
+                for arm in arms {
+                    println!("    {} => {}", arm.pattern, arm.expr);
+                }
+                println!("  }}");
+            }
+            Expr::Null => println!("Null"),
         }
     }
 
     println!("Program:\n");
-    for func in &program.funcs {
-        println!("  FuncDef: {}():", func.name);
-        for stmt in func.body.stmts() {
-            print_stmt(&program.expr_arena, stmt, 4);
-        }
+    print_items(program, &program.consts, &program.funcs, &program.modules, 2);
+}
+
+fn print_items(program: &Program, consts: &[ConstDef], funcs: &[FuncDef], modules: &[ModuleDef], indent: usize) {
+    let padding = " ".repeat(indent);
+
+    for constant in consts {
+        println!("{}ConstDef: {} {} =", padding, constant._type, constant.name);
+        print_expr(&program.expr_arena, constant.expr_id, indent + 2);
         println!();
     }
-}
\ No newline at end of file
+
+    for func in funcs {
+        print_func(program, func, indent);
+    }
+
+    for module in modules {
+        println!("{}ModuleDef: {}", padding, module.name);
+        print_items(program, &module.consts, &module.funcs, &module.modules, indent + 2);
+        print_types(program, &module.classes, &module.enums, &module.interfaces, &module.impls, indent + 2);
+    }
+
+    if indent == 2 {
+        print_types(program, &program.classes, &program.enums, &program.interfaces, &program.impls, indent);
+    }
+}
+
+fn print_types(program: &Program, classes: &[ClassDef], enums: &[EnumDef], interfaces: &[InterfaceDef], impls: &[ImplDef], indent: usize) {
+    let padding = " ".repeat(indent);
+
+    for class in classes {
+        println!("{}ClassDef: {}{}{}:", padding, class.name, generics_to_string(&class.generics), conformance_to_string(&class.interfaces));
+        for field in &class.fields {
+            println!("{}  Field: {} {}", padding, field._type, field.name);
+        }
+        for ctor in &class.ctors {
+            println!("{}  Ctor: {}{}({}):", padding, generics_to_string(&ctor.generics), class.name, params_to_string(&ctor.params));
+            print_block(&program.expr_arena, &ctor.body, indent + 4);
+        }
+        for method in &class.methods {
+            print_func(program, method, indent + 2);
+        }
+    }
+
+    for _enum in enums {
+        println!("{}EnumDef: {}{}{}:", padding, _enum.name, generics_to_string(&_enum.generics), conformance_to_string(&_enum.interfaces));
+        for variant in &_enum.variants {
+            println!("{}  Variant: {}{}", padding, variant.name, type_args_to_string(&variant.fields).replace('<', "(").replace('>', ")"));
+        }
+        for method in &_enum.methods {
+            print_func(program, method, indent + 2);
+        }
+    }
+
+    for interface in interfaces {
+        println!("{}InterfaceDef: {}{}:", padding, interface.name, generics_to_string(&interface.generics));
+        for method in &interface.methods {
+            let receiver = if method.receiver.is_some() { "self" } else { "" };
+            let ret_type = method.ret_type.as_ref().map(|t| format!(": {}", t)).unwrap_or_default();
+            println!("{}  FuncDecl: {}{}({}{}){}", padding, generics_to_string(&method.generics), method.name, receiver, params_to_string(&method.params), ret_type);
+        }
+    }
+
+    for _impl in impls {
+        match &_impl.interface {
+            Some(interface) => println!("{}ImplDef: {}", padding, interface),
+            None => println!("{}ImplDef:", padding),
+        }
+        for block in &_impl.blocks {
+            println!("{}  ${}:", padding, block.target);
+            for method in &block.methods {
+                print_func(program, method, indent + 4);
+            }
+        }
+    }
+}
+
+fn print_func(program: &Program, func: &FuncDef, indent: usize) {
+    let padding = " ".repeat(indent);
+    let mut params = params_to_string(&func.params);
+
+    if func.receiver.is_some() {
+        params = match params.is_empty() {
+            true => "self".to_string(),
+            false => format!("self, {}", params),
+        };
+    }
+
+    let name = format!("{}{}", generics_to_string(&func.generics), func.name);
+
+    match &func.ret_type {
+        Some(ret_type) => println!("{}FuncDef: {}({}): {}:", padding, name, params, ret_type),
+        None => println!("{}FuncDef: {}({}):", padding, name, params),
+    }
+    print_block(&program.expr_arena, &func.body, indent + 2);
+    println!();
+}
+
+fn params_to_string(params: &[Param]) -> String {
+    params.iter()
+        .map(|p| format!("{}{} {}", p._type, if p.variadic { "..." } else { "" }, p.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn generics_to_string(generics: &[GenericParam]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+
+    let params = generics.iter()
+        .map(|g| match g.bounds.is_empty() {
+            true => g.name.clone(),
+            false => format!("{} : {}", g.name, g.bounds.iter().map(Type::to_string).collect::<Vec<_>>().join(" + ")),
+        })
+        .collect::<Vec<_>>();
+
+    format!("<{}>", params.join(", "))
+}
+
+fn conformance_to_string(interfaces: &[Type]) -> String {
+    if interfaces.is_empty() {
+        return String::new();
+    }
+
+    format!(" : {}", interfaces.iter().map(Type::to_string).collect::<Vec<_>>().join(", "))
+}
@@ -2,173 +2,1229 @@ use crate::ast::*;
 use crate::lexer::Token;
 use crate::lexer::TokenKind;
 use crate::lexer::Span;
-use crate::generate_plain_error;
+use crate::lexer::FileId;
+use crate::utils::suggest::closest;
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::codes;
+use crate::diagnostics::codes::ErrorCode;
+
+/// How deep expressions and array types may nest before parsing gives up,
+/// so that hostile input cannot overflow the stack of this or later passes.
+const MAX_NESTING: usize = 128;
+
+/// Anything that may appear at the top of a file or inside a `module`.
+enum Item {
+    Import(Import),
+    Func(FuncDef),
+    Const(ConstDef),
+    Module(ModuleDef),
+    Class(ClassDef),
+    Enum(EnumDef),
+    Interface(InterfaceDef),
+    Impl(ImplDef),
+    Llvm(LlvmBlock),
+}
+
+/// Where to resume after parsing something that may turn out not to be there,
+/// e.g. the type arguments in `a<b>(c)`, as opposed to `a < b`.
+struct Snapshot {
+    pos: usize,
+    depth: usize,
+    splits: usize,
+}
 
 pub struct Parser {
     tokens: Vec<Token>,
     expr_arena: ExprArena,
     pos: usize,
+    depth: usize,
+    // `>>` tokens split into two `>` to close nested type arguments, so that
+    // a rewind can join them again
+    splits: Vec<usize>,
+    file: FileId,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Parser {
-        Parser {
-            tokens,
-            expr_arena: ExprArena::new(),
-            pos: 0,
+// Errors end the compilation, so their size never matters on the hot path.
+#[allow(clippy::result_large_err)]
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Parser {
+        Parser::with_arena(tokens, ExprArena::new())
+    }
+
+    /// Parses into an existing arena, so that several files can share one.
+    pub fn with_arena(mut tokens: Vec<Token>, expr_arena: ExprArena) -> Parser {
+        if !tokens.last().is_some_and(|t| t.kind.is_eof()) {
+            let (file, end) = tokens.last().map_or((FileId::default(), 0), |t| (t.span.file, t.span.end));
+            tokens.push(Token::new(TokenKind::Eof, Span::new(file, end, end)));
+        }
+
+        let file = tokens[tokens.len() - 1].span.file;
+
+        Parser {
+            tokens,
+            expr_arena,
+            pos: 0,
+            depth: 0,
+            splits: Vec::new(),
+            file,
+        }
+    }
+
+    // Reading past the end keeps returning the final `Eof`.
+    fn peek(&self) -> &Token {
+        self.peek_with_offset(0)
+    }
+    
+    fn peek_with_offset(&self, offset: usize) -> &Token {
+        let last = self.tokens.len() - 1;
+        &self.tokens[(self.pos + offset).min(last)]
+    }
+
+    fn previous_end(&self) -> usize {
+        self.pos.checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map_or(self.peek().span.start, |t| t.span.end)
+    }
+
+    fn scroll(&mut self) {
+        self.pos += 1;
+    }
+
+    fn scroll_with_offset(&mut self, offset: usize) {
+        self.pos += offset;
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek().clone();
+        self.pos += 1;
+        token
+    }
+
+    /// Skips the next token if it is `kind`.
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        let found = self.peek().kind == kind;
+
+        if found {
+            self.scroll();
+        }
+
+        found
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot { pos: self.pos, depth: self.depth, splits: self.splits.len() }
+    }
+
+    fn rewind(&mut self, snapshot: Snapshot) {
+        while self.splits.len() > snapshot.splits {
+            let at = self.splits.pop().unwrap();
+            let second = self.tokens.remove(at + 1);
+
+            self.tokens[at].kind = TokenKind::RShift;
+            self.tokens[at].span.end = second.span.end;
+        }
+
+        self.pos = snapshot.pos;
+        self.depth = snapshot.depth;
+    }
+
+    fn error(&self, code: &'static ErrorCode, span: &Span, message: String) -> Diagnostic {
+        Diagnostic::error(message)
+            .with_code(code)
+            .with_span(span)
+    }
+
+    fn enter(&mut self, span: &Span) -> Result<(), Diagnostic> {
+        self.depth += 1;
+
+        if self.depth > MAX_NESTING {
+            return Err(self.error(&codes::E0031, span, format!("Nesting is deeper than the limit of {}", MAX_NESTING)));
+        }
+
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn is_eof(&self) -> bool {
+        self.peek().kind.is_eof()
+    }
+
+    fn expect(&mut self, expected: TokenKind) -> Result<(), Diagnostic> {
+        let got = self.advance();
+
+        if got.kind != expected {
+            let expected_name = expected.to_string();
+            let suggestion = match &got.kind {
+                TokenKind::Ident(name) => closest(name, [expected_name.as_str()]),
+                _ => None,
+            };
+
+            return Err(Diagnostic::error(format!("Parse error: expected `{}`, but got `{}`", expected, got.kind))
+                .with_code(&codes::E0005)
+                .with_span(&got.span)
+                .with_suggestion(&got.span, suggestion));
+        }
+
+        Ok(())
+    }
+
+    pub fn parse(mut self) -> Result<Program, Diagnostic> {
+        let mut program = Program {
+            imports: Vec::new(),
+            funcs: Vec::new(),
+            consts: Vec::new(),
+            modules: Vec::new(),
+            classes: Vec::new(),
+            enums: Vec::new(),
+            interfaces: Vec::new(),
+            impls: Vec::new(),
+            llvm: Vec::new(),
+            no_std: None,
+            templates: Templates::default(),
+            expr_arena: ExprArena::new(),
+        };
+
+        while self.peek().kind == TokenKind::Hash && self.peek_with_offset(1).kind == TokenKind::Bang {
+            program.no_std = Some(self.parse_inner_attribute()?);
+        }
+
+        while !self.is_eof() {
+            match self.parse_item()? {
+                Item::Import(import) => program.imports.push(import),
+                Item::Func(func) => program.funcs.push(func),
+                Item::Const(constant) => program.consts.push(constant),
+                Item::Module(module) => program.modules.push(module),
+                Item::Class(class) => program.classes.push(class),
+                Item::Enum(_enum) => program.enums.push(_enum),
+                Item::Interface(interface) => program.interfaces.push(interface),
+                Item::Impl(_impl) => program.impls.push(_impl),
+                Item::Llvm(block) => program.llvm.push(block),
+            }
+        }
+
+        program.expr_arena = self.expr_arena;
+
+        Ok(program)
+    }
+
+    /// `#![no_std]` at the top of a file, the only inner attribute so far.
+    fn parse_inner_attribute(&mut self) -> Result<Span, Diagnostic> {
+        let start = self.advance().span.start;
+
+        self.expect(TokenKind::Bang)?;
+        self.expect(TokenKind::LBracket)?;
+
+        let name_span = self.peek().span.clone();
+        let name = self.parse_ident("attribute name after `#![`")?;
+
+        if name != "no_std" {
+            return Err(self.error(&codes::E0005, &name_span, format!("Unknown attribute: `{}`", name))
+                .with_suggestion(&name_span, closest(&name, ["no_std"])));
+        }
+
+        self.expect(TokenKind::RBracket)?;
+
+        Ok(Span::new(self.file, start, self.previous_end()))
+    }
+
+    /// `#[inline]` or `#[T : Formatter]` in front of a function.
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>, Diagnostic> {
+        let mut attrs = Vec::new();
+
+        while self.peek().kind == TokenKind::Hash && self.peek_with_offset(1).kind == TokenKind::LBracket {
+            let start = self.peek().span.start;
+            self.scroll_with_offset(2);
+
+            let name = self.parse_ident("attribute after `#[`")?;
+
+            let kind = match self.eat(TokenKind::Colon) {
+                true => AttrKind::Bound(name, self.parse_bounds()?),
+                false => AttrKind::Word(name),
+            };
+
+            self.expect(TokenKind::RBracket)?;
+
+            attrs.push(Attribute { kind, span: Span::new(self.file, start, self.previous_end()) });
+        }
+
+        Ok(attrs)
+    }
+
+    fn parse_item(&mut self) -> Result<Item, Diagnostic> {
+        match self.peek().kind {
+            TokenKind::Import => Ok(Item::Import(self.parse_import()?)),
+            TokenKind::Const => Ok(Item::Const(self.parse_const()?)),
+            TokenKind::Module => Ok(Item::Module(self.parse_module()?)),
+            TokenKind::Class => Ok(Item::Class(self.parse_class()?)),
+            TokenKind::Enum => Ok(Item::Enum(self.parse_enum()?)),
+            TokenKind::Interface => Ok(Item::Interface(self.parse_interface()?)),
+            TokenKind::Impl => Ok(Item::Impl(self.parse_impl()?)),
+            TokenKind::Llvm => Ok(Item::Llvm(self.parse_llvm()?)),
+            TokenKind::Pub if !self.is_func_start(1) => self.parse_pub_item(),
+            _ => Ok(Item::Func(self.parse_func()?)),
+        }
+    }
+
+    /// Whether the token at `offset` is `fun` or `override`. Attributes and
+    /// `pub` in front of a function are read by [`Parser::parse_func`].
+    fn is_func_start(&self, offset: usize) -> bool {
+        matches!(self.peek_with_offset(offset).kind, TokenKind::Fun | TokenKind::Override)
+    }
+
+    /// `pub` exports the item after it from its module.
+    fn parse_pub_item(&mut self) -> Result<Item, Diagnostic> {
+        self.scroll();
+
+        match self.peek().kind {
+            TokenKind::Const => {
+                let mut constant = self.parse_const()?;
+                constant.is_pub = true;
+                Ok(Item::Const(constant))
+            }
+            TokenKind::Module => {
+                let mut module = self.parse_module()?;
+                module.is_pub = true;
+                Ok(Item::Module(module))
+            }
+            TokenKind::Class => {
+                let mut class = self.parse_class()?;
+                class.is_pub = true;
+                Ok(Item::Class(class))
+            }
+            TokenKind::Enum => {
+                let mut _enum = self.parse_enum()?;
+                _enum.is_pub = true;
+                Ok(Item::Enum(_enum))
+            }
+            TokenKind::Interface => {
+                let mut interface = self.parse_interface()?;
+                interface.is_pub = true;
+                Ok(Item::Interface(interface))
+            }
+            _ => {
+                let token = self.peek();
+                Err(self.error(&codes::E0005, &token.span, format!(
+                    "Expected `fun`, `const`, `module`, `class`, `enum` or `interface` after `pub`, but got `{}`",
+                    token.kind,
+                )))
+            }
+        }
+    }
+
+    fn parse_module(&mut self) -> Result<ModuleDef, Diagnostic> {
+        self.scroll();
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("module name after `module`")?;
+
+        self.expect(TokenKind::LBrace)?;
+        self.enter(&span)?;
+
+        let mut module = ModuleDef {
+            name,
+            funcs: Vec::new(),
+            consts: Vec::new(),
+            modules: Vec::new(),
+            classes: Vec::new(),
+            enums: Vec::new(),
+            interfaces: Vec::new(),
+            impls: Vec::new(),
+            llvm: Vec::new(),
+            is_pub: false,
+            span,
+        };
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            match self.parse_item()? {
+                Item::Import(import) => {
+                    return Err(self.error(&codes::E0005, &import.span, "`import` is only allowed outside of modules".to_string()));
+                }
+                Item::Func(func) => module.funcs.push(func),
+                Item::Const(constant) => module.consts.push(constant),
+                Item::Module(inner) => module.modules.push(inner),
+                Item::Class(class) => module.classes.push(class),
+                Item::Enum(_enum) => module.enums.push(_enum),
+                Item::Interface(interface) => module.interfaces.push(interface),
+                Item::Impl(_impl) => module.impls.push(_impl),
+                Item::Llvm(block) => module.llvm.push(block),
+            }
+        }
+
+        self.leave();
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(module)
+    }
+
+    fn parse_const(&mut self) -> Result<ConstDef, Diagnostic> {
+        self.scroll();
+
+        let _type = self.parse_type()?;
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("constant name after type")?;
+
+        self.expect(TokenKind::Assign)?;
+
+        let expr_id = self.parse_expr()?;
+
+        self.expect(TokenKind::Semicolon)?;
+
+        Ok(ConstDef { name, _type, expr_id, is_pub: false, span })
+    }
+
+    /// `class Name<T> : Interface { ... }`, whose body holds fields,
+    /// constructors named after the class and methods.
+    fn parse_class(&mut self) -> Result<ClassDef, Diagnostic> {
+        self.scroll();
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("class name after `class`")?;
+        let generics = self.parse_generics()?;
+        let interfaces = self.parse_conformance()?;
+
+        self.expect(TokenKind::LBrace)?;
+        self.enter(&span)?;
+
+        let mut class = ClassDef {
+            name,
+            generics,
+            interfaces,
+            fields: Vec::new(),
+            ctors: Vec::new(),
+            methods: Vec::new(),
+            is_pub: false,
+            span,
+        };
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            let is_ctor = match &self.peek().kind {
+                TokenKind::Lt => true,
+                TokenKind::Ident(name) => *name == class.name && self.peek_with_offset(1).kind == TokenKind::LParen,
+                _ => false,
+            };
+
+            if is_ctor {
+                class.ctors.push(self.parse_ctor(&class.name)?);
+            } else if self.is_member_func() {
+                class.methods.push(self.parse_func()?);
+            } else {
+                class.fields.push(self.parse_field()?);
+            }
+        }
+
+        self.leave();
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(class)
+    }
+
+    /// Whether a function starts here inside a class, enum or `impl`.
+    fn is_member_func(&self) -> bool {
+        match self.peek().kind {
+            TokenKind::Hash => true,
+            TokenKind::Pub => self.is_func_start(1),
+            _ => self.is_func_start(0),
+        }
+    }
+
+    fn parse_field(&mut self) -> Result<FieldDef, Diagnostic> {
+        let is_pub = self.eat(TokenKind::Pub);
+        let _type = self.parse_type()?;
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("field name after type")?;
+
+        self.expect(TokenKind::Semicolon)?;
+
+        Ok(FieldDef { _type, name, is_pub, span })
+    }
+
+    fn parse_ctor(&mut self, class: &str) -> Result<CtorDef, Diagnostic> {
+        let generics = self.parse_generics()?;
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("constructor name")?;
+
+        if name != class {
+            return Err(self.error(&codes::E0005, &span, format!("Constructor of class `{}` must be named `{}`, but got `{}`", class, class, name)));
+        }
+
+        let (receiver, params) = self.parse_params()?;
+
+        if let Some(receiver) = receiver {
+            return Err(self.error(&codes::E0005, &receiver, "Constructors cannot take `self`".to_string()));
+        }
+
+        let body = self.parse_block()?;
+
+        Ok(CtorDef { generics, params, body, span })
+    }
+
+    /// `enum Name<T> : Interface { Variant, Variant(T), ... methods }`.
+    fn parse_enum(&mut self) -> Result<EnumDef, Diagnostic> {
+        self.scroll();
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("enum name after `enum`")?;
+        let generics = self.parse_generics()?;
+        let interfaces = self.parse_conformance()?;
+
+        self.expect(TokenKind::LBrace)?;
+        self.enter(&span)?;
+
+        let mut _enum = EnumDef {
+            name,
+            generics,
+            interfaces,
+            variants: Vec::new(),
+            methods: Vec::new(),
+            is_pub: false,
+            span,
+        };
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            if self.is_member_func() {
+                _enum.methods.push(self.parse_func()?);
+                continue;
+            }
+
+            let span = self.peek().span.clone();
+            let name = self.parse_ident("variant name")?;
+            let mut fields = Vec::new();
+
+            if self.eat(TokenKind::LParen) {
+                while self.peek().kind != TokenKind::RParen && !self.is_eof() {
+                    fields.push(self.parse_type()?);
+
+                    if self.peek().kind != TokenKind::RParen {
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+
+                self.expect(TokenKind::RParen)?;
+            }
+
+            _enum.variants.push(VariantDef { name, fields, span });
+
+            if self.peek().kind != TokenKind::RBrace {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+
+        self.leave();
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(_enum)
+    }
+
+    /// `interface Name { fun method(self): str; ... }`.
+    fn parse_interface(&mut self) -> Result<InterfaceDef, Diagnostic> {
+        self.scroll();
+
+        let span = self.peek().span.clone();
+        let name = self.parse_ident("interface name after `interface`")?;
+        let generics = self.parse_generics()?;
+
+        self.expect(TokenKind::LBrace)?;
+
+        let mut methods = Vec::new();
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            self.expect(TokenKind::Fun)?;
+
+            let generics = self.parse_generics()?;
+            let span = self.peek().span.clone();
+            let name = self.parse_ident("method name after `fun`")?;
+            let (receiver, params) = self.parse_params()?;
+            let ret_type = self.parse_ret_type()?;
+
+            self.expect(TokenKind::Semicolon)?;
+
+            methods.push(FuncDecl { name, generics, receiver, params, ret_type, span });
+        }
+
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(InterfaceDef { name, generics, methods, is_pub: false, span })
+    }
+
+    /// `: Interface, ...` after the name of a class or enum.
+    fn parse_conformance(&mut self) -> Result<Vec<Type>, Diagnostic> {
+        let mut interfaces = Vec::new();
+
+        if self.eat(TokenKind::Colon) {
+            interfaces.push(self.parse_type()?);
+
+            while self.eat(TokenKind::Comma) {
+                interfaces.push(self.parse_type()?);
+            }
+        }
+
+        Ok(interfaces)
+    }
+
+    /// `impl $str { ... }` or `impl Interface { $unt { ... } ... }`.
+    fn parse_impl(&mut self) -> Result<ImplDef, Diagnostic> {
+        let start = self.advance().span.start;
+
+        if self.peek().kind == TokenKind::Dollar {
+            let block = self.parse_impl_block()?;
+            let span = Span::new(self.file, start, block.span.end);
+
+            return Ok(ImplDef { interface: None, blocks: vec![block], span });
+        }
+
+        let interface = self.parse_type()?;
+        let span = Span::new(self.file, start, self.previous_end());
+
+        self.expect(TokenKind::LBrace)?;
+
+        let mut blocks = Vec::new();
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            blocks.push(self.parse_impl_block()?);
+        }
+
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(ImplDef { interface: Some(interface), blocks, span })
+    }
+
+    /// `$type { methods }`.
+    fn parse_impl_block(&mut self) -> Result<ImplBlock, Diagnostic> {
+        let start = self.peek().span.start;
+
+        self.expect(TokenKind::Dollar)?;
+
+        let target = self.parse_type()?;
+        let span = Span::new(self.file, start, self.previous_end());
+
+        self.expect(TokenKind::LBrace)?;
+
+        let mut methods = Vec::new();
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            methods.push(self.parse_func()?);
+        }
+
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(ImplBlock { target, methods, span })
+    }
+
+    fn parse_llvm(&mut self) -> Result<LlvmBlock, Diagnostic> {
+        let start = self.advance().span.start;
+        let token = self.advance();
+
+        match token.kind {
+            TokenKind::LlvmCode(code) => Ok(LlvmBlock { code, span: Span::new(self.file, start, token.span.end) }),
+
+            other => Err(self.error(&codes::E0005, &token.span, format!("Expected `{{` after `llvm`, but got `{}`", other))),
+        }
+    }
+
+    /// The rest of a `a::b::c` path whose first segment is `first`.
+    fn parse_path(&mut self, first: String, span: &mut Span) -> Result<String, Diagnostic> {
+        let mut path = first;
+
+        while self.peek().kind == TokenKind::ColonColon {
+            self.scroll();
+
+            let segment_span = self.peek().span.clone();
+            let segment = self.parse_ident("name after `::`")?;
+
+            path = format!("{}::{}", path, segment);
+            *span = span.to(&segment_span);
+        }
+
+        Ok(path)
+    }
+
+    fn parse_import(&mut self) -> Result<Import, Diagnostic> {
+        let start = self.advance().span.start;
+
+        // a module may be named after the primitive type it extends, as in `import str::String;`
+        let first = match self.peek().kind.is_primitive_type() {
+            true => self.advance().kind.to_string(),
+            false => self.parse_ident("module name after `import`")?,
+        };
+        let mut path = vec![first];
+
+        while self.peek().kind == TokenKind::ColonColon {
+            self.scroll();
+            path.push(self.parse_ident("name after `::`")?);
+        }
+
+        if path.len() < 2 {
+            let span = self.peek().span.clone();
+            return Err(self.error(&codes::E0005, &span, format!("Expected `::` after module `{}`, but got `{}`", path[0], self.peek().kind)));
+        }
+
+        let item_span = self.tokens[self.pos - 1].span.clone();
+
+        self.expect(TokenKind::Semicolon)?;
+
+        Ok(Import { path, span: Span::new(self.file, start, self.previous_end()), item_span })
+    }
+
+    fn parse_ident(&mut self, what: &str) -> Result<String, Diagnostic> {
+        let token = self.advance();
+
+        match token.kind {
+            TokenKind::Ident(name) => Ok(name),
+
+            other => Err(self.error(&codes::E0005, &token.span, format!("Expected {}, but got `{}`", what, other))),
+        }
+    }
+
+    /// `[#[attributes]] [pub] [override] fun [<generics>] name(params)[: type] { ... }`
+    fn parse_func(&mut self) -> Result<FuncDef, Diagnostic> {
+        let attrs = self.parse_attributes()?;
+        let is_pub = self.eat(TokenKind::Pub);
+        let is_override = self.eat(TokenKind::Override);
+
+        self.expect(TokenKind::Fun)?;
+
+        let generics = self.parse_generics()?;
+
+        let name_token = self.advance();
+        let span = name_token.span.clone();
+
+        let name = match name_token.kind {
+            TokenKind::Ident(n) => n,
+
+            other => return Err(self.error(&codes::E0005, &span, format!("Expected function name after `fun`, but got `{}`", other))),
+        };
+
+        let (receiver, params) = self.parse_params()?;
+        let ret_type = self.parse_ret_type()?;
+        let body = self.parse_block()?;
+
+        Ok(FuncDef { name, generics, attrs, receiver, params, ret_type, body, is_pub, is_override, is_static: false, span })
+    }
+
+    /// `<T, U : Formatter>`, if present.
+    fn parse_generics(&mut self) -> Result<Vec<GenericParam>, Diagnostic> {
+        let mut generics = Vec::new();
+
+        if !self.eat(TokenKind::Lt) {
+            return Ok(generics);
+        }
+
+        while self.peek().kind != TokenKind::Gt && !self.is_eof() {
+            let span = self.peek().span.clone();
+            let name = self.parse_ident("generic parameter name")?;
+
+            let bounds = match self.eat(TokenKind::Colon) {
+                true => self.parse_bounds()?,
+                false => Vec::new(),
+            };
+
+            generics.push(GenericParam { name, bounds, span });
+
+            if self.peek().kind != TokenKind::Gt {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+
+        self.expect(TokenKind::Gt)?;
+
+        Ok(generics)
+    }
+
+    /// `Formatter` or `Formatter + FromStr` after `T :`.
+    fn parse_bounds(&mut self) -> Result<Vec<Type>, Diagnostic> {
+        let mut bounds = vec![self.parse_type()?];
+
+        while self.eat(TokenKind::Plus) {
+            bounds.push(self.parse_type()?);
+        }
+
+        Ok(bounds)
+    }
+
+    /// `(self, T a, U... rest)`, where `self` is only allowed first.
+    fn parse_params(&mut self) -> Result<(Option<Span>, Vec<Param>), Diagnostic> {
+        self.expect(TokenKind::LParen)?;
+
+        let mut receiver = None;
+        let mut params = Vec::new();
+
+        if self.peek().kind == TokenKind::SelfValue {
+            receiver = Some(self.advance().span);
+
+            if self.peek().kind != TokenKind::RParen {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+
+        while self.peek().kind != TokenKind::RParen && !self.is_eof() {
+            params.push(self.parse_param()?);
+
+            if self.peek().kind != TokenKind::RParen {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+
+        self.expect(TokenKind::RParen)?;
+
+        Ok((receiver, params))
+    }
+
+    fn parse_ret_type(&mut self) -> Result<Option<Type>, Diagnostic> {
+        match self.eat(TokenKind::Colon) {
+            true => Ok(Some(self.parse_type()?)),
+            false => Ok(None),
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<Block, Diagnostic> {
+        let span = self.peek().span.clone();
+
+        self.expect(TokenKind::LBrace)?;
+        self.enter(&span)?;
+
+        let mut stmts = Vec::new();
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            stmts.push(self.parse_stmt()?);
+        }
+
+        self.leave();
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(Block::new(stmts))
+    }
+
+    fn parse_param(&mut self) -> Result<Param, Diagnostic> {
+        let _type = self.parse_type()?;
+        let variadic = self.eat(TokenKind::Ellipsis);
+
+        let name_token = self.advance();
+        let span = name_token.span.clone();
+
+        let name = match name_token.kind {
+            TokenKind::Ident(name) => name,
+
+            other => return Err(self.error(&codes::E0005, &span, format!("Expected parameter name after type, but got `{}`", other))),
+        };
+
+        Ok(Param { _type, name, variadic, span })
+    }
+
+    fn parse_type(&mut self) -> Result<Type, Diagnostic> {
+        let span = self.peek().span.clone();
+
+        self.enter(&span)?;
+        let _type = self.parse_type_inner();
+        self.leave();
+
+        _type
+    }
+
+    fn parse_type_inner(&mut self) -> Result<Type, Diagnostic> {
+        let token = self.advance();
+
+        let mut _type = match token.kind {
+            TokenKind::Unt => Type::Unt,
+            TokenKind::Int => Type::Int,
+            TokenKind::Float => Type::Float,
+            TokenKind::Bool => Type::Bool,
+            TokenKind::Char => Type::Char,
+            TokenKind::Str => Type::Str,
+
+            TokenKind::Ident(name) => {
+                let mut span = token.span.clone();
+                let path = self.parse_path(name, &mut span)?;
+
+                let args = match self.peek().kind == TokenKind::Lt {
+                    true => self.parse_type_args()?,
+                    false => Vec::new(),
+                };
+
+                Type::Named(path, args)
+            }
+
+            // `!T`, a `T` or an error
+            TokenKind::Bang => Type::Fallible(Box::new(self.parse_type()?)),
+
+            // `lambda: (params) => ret`
+            TokenKind::Lambda => {
+                self.expect(TokenKind::Colon)?;
+                self.expect(TokenKind::LParen)?;
+
+                let mut params = Vec::new();
+
+                while self.peek().kind != TokenKind::RParen && !self.is_eof() {
+                    params.push(self.parse_type()?);
+
+                    if self.peek().kind != TokenKind::RParen {
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+
+                self.expect(TokenKind::RParen)?;
+                self.expect(TokenKind::FatArrow)?;
+
+                Type::Lambda(params, Box::new(self.parse_type()?))
+            }
+
+            other => return Err(self.error(&codes::E0006, &token.span, format!("Unknown type: `{}`", other))),
+        };
+
+        let mut dimensions = 0;
+
+        loop {
+            match self.peek().kind {
+                TokenKind::LBracket => {
+                    dimensions += 1;
+
+                    if dimensions > MAX_NESTING {
+                        let span = self.peek().span.clone();
+                        return Err(self.error(&codes::E0031, &span, format!("Array type has more than {} dimensions", MAX_NESTING)));
+                    }
+
+                    self.scroll();
+                    self.expect(TokenKind::RBracket)?;
+
+                    _type = Type::Array(Box::new(_type));
+                }
+
+                // only names may be pointers, which keeps `a * b` an expression
+                TokenKind::Star if matches!(_type, Type::Named(..) | Type::Ptr(_)) => {
+                    self.scroll();
+
+                    _type = Type::Ptr(Box::new(_type));
+                }
+
+                _ => break,
+            }
+        }
+
+        Ok(_type)
+    }
+
+    /// `<int, Vec<str>>` after a type or function name.
+    fn parse_type_args(&mut self) -> Result<Vec<Type>, Diagnostic> {
+        self.expect(TokenKind::Lt)?;
+
+        let mut args = Vec::new();
+
+        loop {
+            args.push(self.parse_type()?);
+
+            if !self.eat(TokenKind::Comma) {
+                break;
+            }
+        }
+
+        // the `>>` that closes `Vec<Vec<int>>` is two `>`
+        if self.peek().kind == TokenKind::RShift {
+            let token = self.peek().clone();
+            let mid = token.span.start + 1;
+
+            self.tokens[self.pos] = Token::new(TokenKind::Gt, Span::new(self.file, token.span.start, mid));
+            self.tokens.insert(self.pos + 1, Token::new(TokenKind::Gt, Span::new(self.file, mid, token.span.end)));
+            self.splits.push(self.pos);
         }
-    }
 
-    fn peek(&self) -> &Token {
-        self.tokens.get(self.pos).unwrap()
-    }
-    
-    #[allow(dead_code)]
-    fn peek_with_offset(&self, offset: usize) -> &Token {
-        self.tokens.get(self.pos + offset).unwrap()
-    }
+        self.expect(TokenKind::Gt)?;
 
-    fn scroll(&mut self) {
-        self.pos += 1;
+        Ok(args)
     }
 
-    #[allow(dead_code)]
-    fn scroll_with_offset(&mut self, offset: usize) {
-        self.pos += offset;
+    /// Type arguments followed by `(`, as in `parse<int>(s)`. Anything else
+    /// after the name, e.g. `a < b`, is left for the expression parser.
+    fn try_call_type_args(&mut self) -> Option<Vec<Type>> {
+        if self.peek().kind != TokenKind::Lt {
+            return None;
+        }
+
+        let snapshot = self.snapshot();
+
+        match self.parse_type_args() {
+            Ok(args) if self.peek().kind == TokenKind::LParen => Some(args),
+            _ => {
+                self.rewind(snapshot);
+                None
+            }
+        }
     }
 
-    fn advance(&mut self) -> Token {
-        let token = self.tokens.get(self.pos).unwrap().clone();
-        self.pos += 1;
-        token
+    /// Whether a variable declaration starts here, i.e. a type followed by
+    /// a name, as in `Vec<int> v` or `T[] buf`.
+    fn is_vardecl(&mut self) -> bool {
+        let snapshot = self.snapshot();
+        let is_vardecl = self.parse_type().is_ok() && matches!(self.peek().kind, TokenKind::Ident(_));
+
+        self.rewind(snapshot);
+
+        is_vardecl
     }
 
-    fn is_eof(&self) -> bool {
-        self.peek().kind.is_eof()
+    fn parse_stmt(&mut self) -> Result<StmtNode, Diagnostic> {
+        let start = self.peek().span.start;
+
+        let stmt = match self.peek().clone().kind {
+            t if t.is_primitive_type() => {
+                Stmt::VarDecl(self.parse_vardecl()?)
+            }
+
+            TokenKind::Unreachable => {
+                Stmt::Unreachable(self.parse_unreachable()?)
+            },
+
+            TokenKind::Return => {
+                Stmt::Return(self.parse_return()?)
+            },
+
+            TokenKind::If => self.parse_if()?,
+
+            TokenKind::While => {
+                self.scroll();
+
+                let cond = self.parse_expr()?;
+                let body = self.parse_block()?;
+
+                Stmt::While(cond, body)
+            }
+
+            TokenKind::For => Stmt::For(self.parse_for()?),
+
+            TokenKind::Switch => self.parse_switch_stmt()?,
+
+            TokenKind::Llvm => Stmt::Llvm(self.parse_llvm()?.code),
+
+            TokenKind::Ident(name) if name == "println" => {
+                Stmt::Println(self.parse_println()?)
+            },
+
+            TokenKind::Ident(_) if self.is_vardecl() => {
+                Stmt::VarDecl(self.parse_vardecl()?)
+            }
+
+            TokenKind::Ident(_) | TokenKind::SelfValue => {
+                let stmt = self.parse_simple_stmt()?;
+
+                self.expect(TokenKind::Semicolon)?;
+
+                stmt
+            },
+
+            other => {
+                let span = self.peek().span.clone();
+                return Err(self.error(&codes::E0007, &span, format!("Parse error: unknown statement starting with `{}`", other)));
+            }
+        };
+
+        let end = self.previous_end();
+
+        Ok(StmtNode::new(stmt, Span::new(self.file, start, end)))
     }
 
-    fn expect(&mut self, expected: TokenKind) {
-        let got = self.advance().kind;
+    /// An expression, an assignment such as `a.b += 1` or `i++`, without `;`.
+    fn parse_simple_stmt(&mut self) -> Result<Stmt, Diagnostic> {
+        let target = self.parse_expr()?;
+
+        let op = match self.peek().kind {
+            TokenKind::Assign => None,
+            TokenKind::PlusAssign => Some(BinaryOp::Add),
+            TokenKind::MinusAssign => Some(BinaryOp::Sub),
+            TokenKind::StarAssign => Some(BinaryOp::Mul),
+            TokenKind::SlashAssign => Some(BinaryOp::Div),
+            TokenKind::PercentAssign => Some(BinaryOp::Mod),
+
+            TokenKind::PlusPlus | TokenKind::MinusMinus => {
+                let token = self.advance();
+                let op = match token.kind {
+                    TokenKind::PlusPlus => BinaryOp::Add,
+                    _ => BinaryOp::Sub,
+                };
+                let one = self.expr_arena.add(ExprNode::new(Expr::Lit(Lit::Unt(1)), token.span));
 
-        if got != expected {
-            generate_plain_error!("Parse error: expected `{}`, but got `{}`", expected, got);
-        }
+                return Ok(Stmt::Assign(target, Some(op), one));
+            }
+
+            _ => return Ok(Stmt::Expr(target)),
+        };
+
+        self.scroll();
+
+        let value = self.parse_expr()?;
+
+        Ok(Stmt::Assign(target, op, value))
     }
 
-    pub fn parse(mut self) -> Program {
-        let mut funcs = Vec::new();
+    fn parse_if(&mut self) -> Result<Stmt, Diagnostic> {
+        self.scroll();
+
+        let cond = self.parse_expr()?;
+        let then = self.parse_block()?;
 
-        while !self.is_eof() {
-            funcs.push(self.parse_func());
+        if !self.eat(TokenKind::Else) {
+            return Ok(Stmt::If(cond, then, None));
         }
 
-        Program {
-            funcs,
-            expr_arena: self.expr_arena,
+        if self.peek().kind != TokenKind::If {
+            return Ok(Stmt::If(cond, then, Some(self.parse_block()?)));
         }
+
+        let span = self.peek().span.clone();
+
+        self.enter(&span)?;
+        let else_if = self.parse_stmt()?;
+        self.leave();
+
+        Ok(Stmt::If(cond, then, Some(Block::new(vec![else_if]))))
     }
 
-    fn parse_func(&mut self) -> FuncDef {
-        self.expect(TokenKind::Fun);
+    /// `for el in arr { }`, `for i, el in arr { }` or
+    /// `for unt i = 0, i < n, i++ { }`.
+    fn parse_for(&mut self) -> Result<ForLoop, Diagnostic> {
+        self.scroll();
+
+        let is_each = matches!(self.peek().kind, TokenKind::Ident(_))
+            && matches!(self.peek_with_offset(1).kind, TokenKind::Comma | TokenKind::In)
+            && !matches!(self.peek_with_offset(2).kind, TokenKind::Assign);
 
-        let name = match self.advance().kind {
-            TokenKind::Ident(n) => n,
+        if is_each {
+            let first = self.parse_ident("loop variable after `for`")?;
 
-            other => generate_plain_error!("Expected function name after `fun`, but got `{}`", other),
-        };
+            let (index, elem) = match self.eat(TokenKind::Comma) {
+                true => (Some(first), self.parse_ident("element name after `,`")?),
+                false => (None, first),
+            };
 
-        self.expect(TokenKind::LParen);
-        self.expect(TokenKind::RParen);
-        self.expect(TokenKind::LBrace);
+            self.expect(TokenKind::In)?;
 
-        let mut stmts = Vec::new();
+            let iter = self.parse_expr()?;
+            let body = self.parse_block()?;
 
-        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
-            stmts.push(self.parse_stmt());
+            return Ok(ForLoop::Each { index, elem, iter, body });
         }
 
-        self.expect(TokenKind::RBrace);
+        let start = self.peek().span.start;
+        let init = match self.peek().kind.is_primitive_type() || self.is_vardecl() {
+            true => Stmt::VarDecl(self.parse_vardecl_head()?),
+            false => self.parse_simple_stmt()?,
+        };
+        let init = StmtNode::new(init, Span::new(self.file, start, self.previous_end()));
+
+        self.expect(TokenKind::Comma)?;
+
+        let cond = self.parse_expr()?;
+
+        self.expect(TokenKind::Comma)?;
+
+        let start = self.peek().span.start;
+        let step = self.parse_simple_stmt()?;
+        let step = StmtNode::new(step, Span::new(self.file, start, self.previous_end()));
 
-        let body = Block::new(stmts);
+        let body = self.parse_block()?;
 
-        FuncDef { name, body }
+        Ok(ForLoop::Counted { init: Box::new(init), cond, step: Box::new(step), body })
     }
 
-    fn parse_stmt(&mut self) -> Stmt {
-        let stmt = match self.peek().clone().kind {
-            t if t.is_primitive_type() => {
-                Stmt::VarDecl(self.parse_vardecl())
-            }
+    fn parse_unreachable(&mut self) -> Result<Option<ExprId>, Diagnostic> {
+        self.scroll();
 
-            TokenKind::Unreachable => {
-                self.scroll();
-                Stmt::Unreachable
-            },
+        let msg = if self.peek().kind == TokenKind::LParen {
+            self.scroll();
 
-            TokenKind::Ident(name) if name == "println" => {
-                Stmt::Println(self.parse_println())
-            },
+            let expr_id = self.parse_expr()?;
+
+            self.expect(TokenKind::RParen)?;
 
-            other => generate_plain_error!("Parse error: unknown statement starting with `{}`", other),
+            Some(expr_id)
+        } else {
+            None
         };
 
-        stmt
+        if self.peek().kind == TokenKind::Semicolon {
+            self.scroll();
+        }
+
+        Ok(msg)
     }
 
-    fn parse_println(&mut self) -> ExprId {
+    fn parse_println(&mut self) -> Result<ExprId, Diagnostic> {
         self.scroll();
-        self.expect(TokenKind::LParen);
+        self.expect(TokenKind::LParen)?;
 
         if self.peek().kind == TokenKind::RParen {
             return self.parse_println_no_value();
         }
 
-        let expr_id = self.parse_expr();
+        let expr_id = self.parse_expr()?;
 
-        self.expect(TokenKind::RParen);
-        self.expect(TokenKind::Semicolon);
+        self.expect(TokenKind::RParen)?;
+        self.expect(TokenKind::Semicolon)?;
 
-        expr_id
+        Ok(expr_id)
     }
 
-    fn parse_println_no_value(&mut self) -> ExprId {
+    fn parse_println_no_value(&mut self) -> Result<ExprId, Diagnostic> {
         let start = self.peek().span.start;
         let end = self.peek().span.end;
 
         self.scroll();
-        self.expect(TokenKind::Semicolon);
+        self.expect(TokenKind::Semicolon)?;
 
-        self.expr_arena.add(ExprNode::new(
+        Ok(self.expr_arena.add(ExprNode::new(
             Expr::Lit(Lit::Str(String::new())),
-            Span::new(start, end),
-        ))
+            Span::new(self.file, start, end),
+        )))
     }
 
-    fn parse_vardecl(&mut self) -> VarDecl {
-        let _type = match self.advance().kind {
-            TokenKind::Unt => Type::Unt,
-            TokenKind::Int => Type::Int,
-            TokenKind::Float => Type::Float,
-            TokenKind::Bool => Type::Bool,
-            TokenKind::Char => Type::Char,
-            TokenKind::Str => Type::Str,
+    fn parse_return(&mut self) -> Result<Option<ExprId>, Diagnostic> {
+        self.scroll();
 
-            other => generate_plain_error!("Unknown type: `{}`", other),
-        };
+        if self.peek().kind == TokenKind::Semicolon {
+            self.scroll();
+            return Ok(None);
+        }
+
+        let expr_id = self.parse_expr()?;
+
+        // like a block, a `switch` ends at its `}`
+        if let Expr::Switch(..) = self.expr_arena.get(expr_id).expr {
+            self.eat(TokenKind::Semicolon);
+        } else {
+            self.expect(TokenKind::Semicolon)?;
+        }
+
+        Ok(Some(expr_id))
+    }
+
+    fn parse_vardecl(&mut self) -> Result<VarDecl, Diagnostic> {
+        let decl = self.parse_vardecl_head()?;
+
+        self.expect(TokenKind::Semicolon)?;
+
+        Ok(decl)
+    }
+
+    /// `<type> <name> = <expression>` without `;`.
+    fn parse_vardecl_head(&mut self) -> Result<VarDecl, Diagnostic> {
+        let _type = self.parse_type()?;
+
+        let name_token = self.advance();
 
-        let name = match self.advance().kind {
+        let name = match name_token.kind {
             TokenKind::Ident(name) => name,
 
-            other => generate_plain_error!("Expected variable name after type, but got `{}`", other),
+            other => return Err(self.error(&codes::E0005, &name_token.span, format!("Expected variable name after type, but got `{}`", other))),
         };
 
-        self.expect(TokenKind::Assign);
+        self.expect(TokenKind::Assign)?;
 
-        let expr_id = self.parse_expr();
+        let expr_id = self.parse_expr()?;
 
-        self.expect(TokenKind::Semicolon);
-
-        VarDecl { _type, name, expr_id }
+        Ok(VarDecl { _type, name, expr_id })
     }
 
     fn make_binary(&mut self, op: BinaryOp, left: ExprId, right: ExprId) -> ExprId {
@@ -178,17 +1234,23 @@ impl Parser {
         self.expr_arena.add(
             ExprNode::new(
                 Expr::Binary(op, left, right),
-                Span::new(start, end),
+                Span::new(self.file, start, end),
             )
         )
     }
 
-    fn parse_expr(&mut self) -> ExprId {
-        self.parse_comparison()
+    fn parse_expr(&mut self) -> Result<ExprId, Diagnostic> {
+        let span = self.peek().span.clone();
+
+        self.enter(&span)?;
+        let expr = self.parse_comparison();
+        self.leave();
+
+        expr
     }
 
-    fn parse_comparison(&mut self) -> ExprId {
-        let mut expr = self.parse_or();
+    fn parse_comparison(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_or()?;
 
         loop {
             let op = match self.peek().kind {
@@ -203,16 +1265,16 @@ impl Parser {
 
             self.scroll();
 
-            let right = self.parse_or();
+            let right = self.parse_or()?;
 
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_or(&mut self) -> ExprId {
-        let mut expr = self.parse_xor();
+    fn parse_or(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_xor()?;
 
         loop {
             let op = match self.peek().kind {
@@ -222,16 +1284,16 @@ impl Parser {
 
             self.scroll();
 
-            let right = self.parse_xor();
+            let right = self.parse_xor()?;
 
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_xor(&mut self) -> ExprId {
-        let mut expr = self.parse_and();
+    fn parse_xor(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_and()?;
 
         loop {
             let op = match self.peek().kind {
@@ -241,16 +1303,16 @@ impl Parser {
 
             self.scroll();
 
-            let right = self.parse_and();
+            let right = self.parse_and()?;
 
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_and(&mut self) -> ExprId {
-        let mut expr = self.parse_bitwise_shift();
+    fn parse_and(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_bitwise_shift()?;
 
         loop {
             let op = match self.peek().kind {
@@ -260,16 +1322,16 @@ impl Parser {
 
             self.scroll();
 
-            let right = self.parse_bitwise_shift();
+            let right = self.parse_bitwise_shift()?;
 
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_bitwise_shift(&mut self) -> ExprId {
-        let mut expr = self.parse_additive();
+    fn parse_bitwise_shift(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_additive()?;
 
         loop {
             let op = match self.peek().kind {
@@ -280,16 +1342,16 @@ impl Parser {
 
             self.scroll();
 
-            let right = self.parse_additive();
+            let right = self.parse_additive()?;
 
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_additive(&mut self) -> ExprId {
-        let mut expr = self.parse_term();
+    fn parse_additive(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_term()?;
 
         loop {
             let op = match self.peek().kind {
@@ -300,16 +1362,16 @@ impl Parser {
             
             self.scroll();
             
-            let right = self.parse_term();
+            let right = self.parse_term()?;
             
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_term(&mut self) -> ExprId {
-        let mut expr = self.parse_cast();
+    fn parse_term(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_cast()?;
 
         loop {
             let op = match self.peek().kind {
@@ -321,49 +1383,45 @@ impl Parser {
             
             self.scroll();
             
-            let right = self.parse_cast();
+            let right = self.parse_cast()?;
             
             expr = self.make_binary(op, expr, right);
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_cast(&mut self) -> ExprId {
-        let mut expr = self.parse_unary();
+    fn parse_cast(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_unary()?;
 
         loop {
             if let TokenKind::As = self.peek().kind {
                 self.scroll();
 
-                let type_token = self.advance();
-                let to_type = match type_token.kind {
-                    TokenKind::Unt => Type::Unt,
-                    TokenKind::Int => Type::Int,
-                    TokenKind::Float => Type::Float,
-                    TokenKind::Bool => Type::Bool,
-                    TokenKind::Char => Type::Char,
-                    TokenKind::Str => Type::Str,
+                let type_token = self.peek().clone();
 
-                    other => generate_plain_error!("Expected type after keyword `as`, but got: `{}`", other),
-                };
+                if !type_token.kind.is_primitive_type() && !matches!(type_token.kind, TokenKind::Ident(_)) {
+                    return Err(self.error(&codes::E0005, &type_token.span, format!("Expected type after keyword `as`, but got: `{}`", type_token.kind)));
+                }
+
+                let to_type = self.parse_type()?;
 
                 let start = self.expr_arena.get(expr).span.start;
-                let end = type_token.span.end;
+                let end = self.previous_end();
 
                 expr = self.expr_arena.add(ExprNode::new(
                     Expr::Cast(to_type, expr),
-                    Span::new(start, end),
+                    Span::new(self.file, start, end),
                 ));
             } else {
                 break;
             }
         }
 
-        expr
+        Ok(expr)
     }
 
-    fn parse_unary(&mut self) -> ExprId {
+    fn parse_unary(&mut self) -> Result<ExprId, Diagnostic> {
         match self.peek().kind.clone() {
             TokenKind::Minus => {
                 let start = self.peek().span.start;
@@ -373,13 +1431,19 @@ impl Parser {
                 match self.peek().kind {
                     TokenKind::NumLit(n) => {
                         let end = self.peek().span.end;
+                        let span = Span::new(self.file, start, end);
 
                         self.scroll();
 
-                        self.expr_arena.add(ExprNode::new(
-                            Expr::Lit(Lit::Int(-(n as i64))),
-                            Span::new(start, end),
-                        ))
+                        // `i64::MIN` has no positive counterpart, so it is negated in place
+                        if n > i64::MIN.unsigned_abs() {
+                            return Err(self.error(&codes::E0030, &span, format!("Number literal `-{}` is too small for `int`", n)));
+                        }
+
+                        Ok(self.expr_arena.add(ExprNode::new(
+                            Expr::Lit(Lit::Int((n as i64).wrapping_neg())),
+                            span,
+                        )))
                     }
 
                     TokenKind::FloatLit(f) => {
@@ -387,20 +1451,20 @@ impl Parser {
 
                         self.scroll();
 
-                        self.expr_arena.add(ExprNode::new(
+                        Ok(self.expr_arena.add(ExprNode::new(
                             Expr::Lit(Lit::Float(-f)),
-                            Span::new(start, end),
-                        ))
+                            Span::new(self.file, start, end),
+                        )))
                     }
 
                     _ => {
-                        let expr = self.parse_primary();
+                        let expr = self.parse_postfix()?;
                         let end = self.expr_arena.get(expr).span.end;
 
-                        self.expr_arena.add(ExprNode::new(
+                        Ok(self.expr_arena.add(ExprNode::new(
                             Expr::Unary(UnaryOp::Minus, expr),
-                            Span::new(start, end),
-                        ))
+                            Span::new(self.file, start, end),
+                        )))
                     }
                 }
             }
@@ -410,21 +1474,80 @@ impl Parser {
                 
                 self.scroll();
                 
-                let expr = self.parse_primary();
+                let expr = self.parse_postfix()?;
                 
                 let end = self.expr_arena.get(expr).span.end;
 
-                self.expr_arena.add(ExprNode::new(
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Unary(UnaryOp::Not, expr),
-                    Span::new(start, end),
-                ))
+                    Span::new(self.file, start, end),
+                )))
             }
 
-            _ => self.parse_primary(),
+            _ => self.parse_postfix(),
+        }
+    }
+
+    fn parse_postfix(&mut self) -> Result<ExprId, Diagnostic> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            match self.peek().kind {
+                TokenKind::Dot => {
+                    self.scroll();
+
+                    let name_token = self.advance();
+                    let name = match name_token.kind {
+                        TokenKind::Ident(name) => name,
+
+                        other => return Err(self.error(&codes::E0005, &name_token.span, format!("Expected field name after `.`, but got `{}`", other))),
+                    };
+
+                    let start = self.expr_arena.get(expr).span.start;
+                    let type_args = self.try_call_type_args();
+
+                    if type_args.is_some() || self.peek().kind == TokenKind::LParen {
+                        let (args, end) = self.parse_args()?;
+
+                        expr = self.expr_arena.add(ExprNode::new(
+                            Expr::MethodCall(expr, name, type_args.unwrap_or_default(), args),
+                            Span::new(self.file, start, end),
+                        ));
+                        continue;
+                    }
+
+                    let end = name_token.span.end;
+
+                    expr = self.expr_arena.add(ExprNode::new(
+                        Expr::Field(expr, name),
+                        Span::new(self.file, start, end),
+                    ));
+                }
+
+                TokenKind::LBracket => {
+                    self.scroll();
+
+                    let index = self.parse_expr()?;
+
+                    let start = self.expr_arena.get(expr).span.start;
+                    let end = self.peek().span.end;
+
+                    self.expect(TokenKind::RBracket)?;
+
+                    expr = self.expr_arena.add(ExprNode::new(
+                        Expr::Index(expr, index),
+                        Span::new(self.file, start, end),
+                    ));
+                }
+
+                _ => break,
+            }
         }
+
+        Ok(expr)
     }
 
-    fn parse_primary(&mut self) -> ExprId {
+    fn parse_primary(&mut self) -> Result<ExprId, Diagnostic> {
         match self.peek().kind.clone() {
             TokenKind::NumLit(n) => {
                 let start = self.peek().span.start;
@@ -432,10 +1555,10 @@ impl Parser {
 
                 self.scroll();
 
-                self.expr_arena.add(ExprNode::new(
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Lit(Lit::Unt(n)),
-                    Span::new(start, end),
-                ))
+                    Span::new(self.file, start, end),
+                )))
             }
 
             TokenKind::FloatLit(f) => {
@@ -444,10 +1567,10 @@ impl Parser {
 
                 self.scroll();
 
-                self.expr_arena.add(ExprNode::new(
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Lit(Lit::Float(f)),
-                    Span::new(start, end),
-                ))
+                    Span::new(self.file, start, end),
+                )))
             }
 
             TokenKind::BoolLit(b) => {
@@ -456,10 +1579,10 @@ impl Parser {
 
                 self.scroll();
 
-                self.expr_arena.add(ExprNode::new(
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Lit(Lit::Bool(b)),
-                    Span::new(start, end),
-                ))
+                    Span::new(self.file, start, end),
+                )))
             }
             
             TokenKind::CharLit(c) => {
@@ -468,10 +1591,10 @@ impl Parser {
                 
                 self.scroll();
                 
-                self.expr_arena.add(ExprNode::new(
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Lit(Lit::Char(c)),
-                    Span::new(start, end),
-                ))
+                    Span::new(self.file, start, end),
+                )))
             }
 
             TokenKind::StringLit(s) => {
@@ -480,34 +1603,214 @@ impl Parser {
 
                 self.scroll();
 
-                self.expr_arena.add(ExprNode::new(
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Lit(Lit::Str(s)),
-                    Span::new(start, end),
-                ))
+                    Span::new(self.file, start, end),
+                )))
             }
 
             TokenKind::Ident(name) => {
-                let span = self.peek().span.clone();
+                let mut span = self.peek().span.clone();
 
                 self.scroll();
 
-                self.expr_arena.add(ExprNode::new(
+                let name = self.parse_path(name, &mut span)?;
+                let type_args = self.try_call_type_args();
+
+                if type_args.is_some() || self.peek().kind == TokenKind::LParen {
+                    return self.parse_call(name, type_args.unwrap_or_default(), span);
+                }
+
+                Ok(self.expr_arena.add(ExprNode::new(
                     Expr::Var(name),
                     span,
-                ))
+                )))
+            }
+
+            TokenKind::SelfValue => {
+                let span = self.advance().span;
+
+                Ok(self.expr_arena.add(ExprNode::new(Expr::Var("self".to_string()), span)))
+            }
+
+            TokenKind::Null => {
+                let span = self.advance().span;
+
+                Ok(self.expr_arena.add(ExprNode::new(Expr::Null, span)))
             }
 
+            TokenKind::Switch => self.parse_switch(),
+
             TokenKind::LParen => {
                 self.scroll();
 
-                let expr = self.parse_expr();
+                let expr = self.parse_expr()?;
+
+                self.expect(TokenKind::RParen)?;
+
+                Ok(expr)
+            }
+
+            other => {
+                let span = self.peek().span.clone();
+                Err(self.error(&codes::E0007, &span, format!("Invalid expression: `{}`", other)))
+            }
+        }
+    }
+
+    fn parse_call(&mut self, name: String, type_args: Vec<Type>, name_span: Span) -> Result<ExprId, Diagnostic> {
+        let (args, end) = self.parse_args()?;
+
+        Ok(self.expr_arena.add(ExprNode::new(
+            Expr::Call(name, type_args, args),
+            Span::new(self.file, name_span.start, end),
+        )))
+    }
+
+    /// `(a, b)`, returning the arguments and the end of `)`.
+    fn parse_args(&mut self) -> Result<(Vec<ExprId>, usize), Diagnostic> {
+        self.expect(TokenKind::LParen)?;
+
+        let mut args = Vec::new();
 
-                self.expect(TokenKind::RParen);
+        while self.peek().kind != TokenKind::RParen && !self.is_eof() {
+            args.push(self.parse_expr()?);
 
-                expr
+            if self.peek().kind != TokenKind::RParen {
+                self.expect(TokenKind::Comma)?;
             }
+        }
+
+        let end = self.peek().span.end;
+
+        self.expect(TokenKind::RParen)?;
+
+        Ok((args, end))
+    }
+
+    /// `switch <expression> { <pattern> => <expression>, ... }`
+    fn parse_switch(&mut self) -> Result<ExprId, Diagnostic> {
+        let start = self.advance().span.start;
+        let scrutinee = self.parse_expr()?;
+
+        self.expect(TokenKind::LBrace)?;
+
+        let mut arms = Vec::new();
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            let pattern = self.parse_pattern()?;
+
+            self.expect(TokenKind::FatArrow)?;
+
+            let expr = self.parse_expr()?;
+
+            arms.push(SwitchArm { pattern, expr });
+
+            if self.peek().kind != TokenKind::RBrace {
+                self.expect(TokenKind::Comma)?;
+            }
+        }
+
+        let end = self.peek().span.end;
+
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(self.expr_arena.add(ExprNode::new(
+            Expr::Switch(scrutinee, arms),
+            Span::new(self.file, start, end),
+        )))
+    }
+
+    /// `switch <expression> { <pattern> => { <statements> } ... }`, where a
+    /// `,` may follow each block.
+    fn parse_switch_stmt(&mut self) -> Result<Stmt, Diagnostic> {
+        self.scroll();
+
+        let scrutinee = self.parse_expr()?;
+
+        self.expect(TokenKind::LBrace)?;
+
+        let mut cases = Vec::new();
+
+        while self.peek().kind != TokenKind::RBrace && !self.is_eof() {
+            let pattern = self.parse_pattern()?;
+
+            self.expect(TokenKind::FatArrow)?;
+
+            let body = self.parse_block()?;
+
+            cases.push(SwitchCase { pattern, body });
 
-            other => generate_plain_error!("Invalid expression: `{}`", other),
+            self.eat(TokenKind::Comma);
         }
+
+        self.expect(TokenKind::RBrace)?;
+
+        Ok(Stmt::Switch(scrutinee, cases))
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, Diagnostic> {
+        let span = self.peek().span.clone();
+
+        self.enter(&span)?;
+        let pattern = self.parse_pattern_inner();
+        self.leave();
+
+        pattern
+    }
+
+    fn parse_pattern_inner(&mut self) -> Result<Pattern, Diagnostic> {
+        let token = self.advance();
+
+        let lit = match token.kind {
+            TokenKind::NumLit(n) => Lit::Unt(n),
+            TokenKind::FloatLit(f) => Lit::Float(f),
+            TokenKind::BoolLit(b) => Lit::Bool(b),
+            TokenKind::CharLit(c) => Lit::Char(c),
+            TokenKind::StringLit(s) => Lit::Str(s),
+
+            TokenKind::Minus => {
+                let number = self.advance();
+                let span = token.span.to(&number.span);
+
+                match number.kind {
+                    TokenKind::NumLit(n) if n <= i64::MIN.unsigned_abs() => return Ok(Pattern::Lit(Lit::Int((n as i64).wrapping_neg()), span)),
+                    TokenKind::NumLit(n) => return Err(self.error(&codes::E0030, &span, format!("Number literal `-{}` is too small for `int`", n))),
+                    TokenKind::FloatLit(f) => return Ok(Pattern::Lit(Lit::Float(-f), span)),
+                    other => return Err(self.error(&codes::E0005, &number.span, format!("Expected number after `-` in pattern, but got `{}`", other))),
+                }
+            }
+
+            TokenKind::Ident(name) if name == "_" => return Ok(Pattern::Wildcard(token.span)),
+
+            TokenKind::Ident(name) => {
+                let mut span = token.span.clone();
+                let name = self.parse_path(name, &mut span)?;
+
+                if !self.eat(TokenKind::LParen) {
+                    return Ok(Pattern::Name(name, span));
+                }
+
+                let mut fields = Vec::new();
+
+                while self.peek().kind != TokenKind::RParen && !self.is_eof() {
+                    fields.push(self.parse_pattern()?);
+
+                    if self.peek().kind != TokenKind::RParen {
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+
+                let end = self.peek().span.end;
+
+                self.expect(TokenKind::RParen)?;
+
+                return Ok(Pattern::Variant(name, fields, Span::new(self.file, span.start, end)));
+            }
+
+            other => return Err(self.error(&codes::E0005, &token.span, format!("Expected pattern, but got `{}`", other))),
+        };
+
+        Ok(Pattern::Lit(lit, token.span))
     }
-}
\ No newline at end of file
+}
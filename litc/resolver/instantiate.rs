@@ -0,0 +1,191 @@
+// Copies of generic items for their instances.
+//
+// An instance starts as a copy of its template in which every generic
+// parameter is replaced with its type argument. Expressions are added to the
+// arena anew, since the resolver rewrites nodes in place and the same
+// template body resolves differently for `int` than for `str`.
+
+use std::collections::HashMap;
+
+use crate::ast::*;
+
+pub struct Copier<'a> {
+    pub arena: &'a mut ExprArena,
+    // generic parameter names to their type arguments
+    pub subst: &'a HashMap<String, Type>,
+}
+
+impl Copier<'_> {
+    /// Copies `func` without its generic parameters, which `subst` must
+    /// cover.
+    pub fn func(&mut self, func: &FuncDef) -> FuncDef {
+        FuncDef {
+            name: func.name.clone(),
+            generics: Vec::new(),
+            attrs: func.attrs.clone(),
+            receiver: func.receiver.clone(),
+            params: self.params(&func.params),
+            ret_type: func.ret_type.as_ref().map(|t| t.substitute(self.subst)),
+            body: self.block(&func.body),
+            is_pub: func.is_pub,
+            is_override: func.is_override,
+            is_static: func.is_static,
+            span: func.span.clone(),
+        }
+    }
+
+    /// Copies `class` as the class called `name`. Its generic methods stay
+    /// generic in their own parameters.
+    pub fn class(&mut self, class: &ClassDef, name: &str) -> ClassDef {
+        ClassDef {
+            name: name.to_string(),
+            generics: Vec::new(),
+            interfaces: class.interfaces.iter().map(|i| i.substitute(self.subst)).collect(),
+            fields: class.fields.iter()
+                .map(|f| FieldDef { _type: f._type.substitute(self.subst), ..f.clone() })
+                .collect(),
+            ctors: class.ctors.iter()
+                .map(|c| CtorDef {
+                    generics: c.generics.clone(),
+                    params: self.params(&c.params),
+                    body: self.block(&c.body),
+                    span: c.span.clone(),
+                })
+                .collect(),
+            methods: class.methods.iter().map(|m| self.method(m)).collect(),
+            is_pub: class.is_pub,
+            span: class.span.clone(),
+        }
+    }
+
+    /// Copies `_enum` as the enum called `name`.
+    pub fn _enum(&mut self, _enum: &EnumDef, name: &str) -> EnumDef {
+        EnumDef {
+            name: name.to_string(),
+            generics: Vec::new(),
+            interfaces: _enum.interfaces.iter().map(|i| i.substitute(self.subst)).collect(),
+            variants: _enum.variants.iter()
+                .map(|v| VariantDef { fields: v.fields.iter().map(|f| f.substitute(self.subst)).collect(), ..v.clone() })
+                .collect(),
+            methods: _enum.methods.iter().map(|m| self.method(m)).collect(),
+            is_pub: _enum.is_pub,
+            span: _enum.span.clone(),
+        }
+    }
+
+    /// Copies a method of a generic type, keeping its own generic
+    /// parameters.
+    fn method(&mut self, method: &FuncDef) -> FuncDef {
+        FuncDef { generics: method.generics.clone(), ..self.func(method) }
+    }
+
+    fn params(&self, params: &[Param]) -> Vec<Param> {
+        params.iter()
+            .map(|p| Param { _type: p._type.substitute(self.subst), ..p.clone() })
+            .collect()
+    }
+
+    fn block(&mut self, block: &Block) -> Block {
+        Block::new(block.stmts().iter().map(|node| self.stmt(node)).collect())
+    }
+
+    fn stmt(&mut self, node: &StmtNode) -> StmtNode {
+        let stmt = match &node.stmt {
+            Stmt::VarDecl(v) => Stmt::VarDecl(VarDecl {
+                _type: v._type.substitute(self.subst),
+                name: v.name.clone(),
+                expr_id: self.expr(v.expr_id),
+            }),
+            Stmt::Println(id) => Stmt::Println(self.expr(*id)),
+            Stmt::Unreachable(id) => Stmt::Unreachable(id.map(|id| self.expr(id))),
+            Stmt::Return(id) => Stmt::Return(id.map(|id| self.expr(id))),
+            Stmt::Expr(id) => Stmt::Expr(self.expr(*id)),
+            Stmt::Assign(target, op, value) => Stmt::Assign(self.expr(*target), op.clone(), self.expr(*value)),
+            Stmt::If(cond, then, otherwise) => {
+                Stmt::If(self.expr(*cond), self.block(then), otherwise.as_ref().map(|b| self.block(b)))
+            }
+            Stmt::While(cond, body) => Stmt::While(self.expr(*cond), self.block(body)),
+            Stmt::For(ForLoop::Each { index, elem, iter, body }) => Stmt::For(ForLoop::Each {
+                index: index.clone(),
+                elem: elem.clone(),
+                iter: self.expr(*iter),
+                body: self.block(body),
+            }),
+            Stmt::For(ForLoop::Counted { init, cond, step, body }) => Stmt::For(ForLoop::Counted {
+                init: Box::new(self.stmt(init)),
+                cond: self.expr(*cond),
+                step: Box::new(self.stmt(step)),
+                body: self.block(body),
+            }),
+            Stmt::Switch(scrutinee, cases) => Stmt::Switch(
+                self.expr(*scrutinee),
+                cases.iter()
+                    .map(|case| SwitchCase { pattern: self.pattern(&case.pattern), body: self.block(&case.body) })
+                    .collect(),
+            ),
+            Stmt::Llvm(ir) => Stmt::Llvm(ir.clone()),
+        };
+
+        StmtNode::new(stmt, node.span.clone())
+    }
+
+    fn expr(&mut self, id: ExprId) -> ExprId {
+        let node = self.arena.get(id).clone();
+        let types = |types: &[Type]| types.iter().map(|t| t.substitute(self.subst)).collect::<Vec<_>>();
+
+        let expr = match node.expr {
+            Expr::Lit(_) | Expr::Var(_) | Expr::Const(_) | Expr::Null => node.expr,
+            Expr::Binary(op, left, right) => Expr::Binary(op, self.expr(left), self.expr(right)),
+            Expr::Unary(op, expr) => Expr::Unary(op, self.expr(expr)),
+            Expr::Cast(to, expr) => Expr::Cast(to.substitute(self.subst), self.expr(expr)),
+            Expr::Field(expr, name) => Expr::Field(self.expr(expr), name),
+            Expr::Index(expr, index) => Expr::Index(self.expr(expr), self.expr(index)),
+            // `T::parse(s)` calls the method of the type argument for `T`
+            Expr::Call(name, type_args, args) => match self.associated(&name) {
+                Some((owner, method)) => Expr::Associated(owner, method, self.exprs(&args)),
+                None => {
+                    let type_args = types(&type_args);
+                    Expr::Call(name, type_args, self.exprs(&args))
+                }
+            },
+            Expr::MethodCall(receiver, name, type_args, args) => {
+                let type_args = types(&type_args);
+                Expr::MethodCall(self.expr(receiver), name, type_args, self.exprs(&args))
+            }
+            Expr::Construct(name, ctor, args) => Expr::Construct(name, ctor, self.exprs(&args)),
+            Expr::Invoke(owner, name, receiver, args) => Expr::Invoke(owner, name, self.expr(receiver), self.exprs(&args)),
+            Expr::Associated(owner, name, args) => Expr::Associated(owner, name, self.exprs(&args)),
+            Expr::Variant(_enum, name, args) => Expr::Variant(_enum, name, self.exprs(&args)),
+            Expr::Switch(scrutinee, arms) => Expr::Switch(
+                self.expr(scrutinee),
+                arms.iter()
+                    .map(|arm| SwitchArm { pattern: self.pattern(&arm.pattern), expr: self.expr(arm.expr) })
+                    .collect(),
+            ),
+        };
+
+        self.arena.add(ExprNode::new(expr, node.span))
+    }
+
+    /// The type and method that call path `T::name` names, if `T` is a
+    /// generic parameter.
+    fn associated(&self, path: &str) -> Option<(String, String)> {
+        let (param, method) = path.split_once("::").filter(|(_, method)| !method.contains("::"))?;
+
+        Some((self.subst.get(param)?.to_string(), method.to_string()))
+    }
+
+    fn exprs(&mut self, ids: &[ExprId]) -> Vec<ExprId> {
+        ids.iter().map(|id| self.expr(*id)).collect()
+    }
+
+    fn pattern(&self, pattern: &Pattern) -> Pattern {
+        match pattern {
+            Pattern::Variant(name, fields, span) => {
+                Pattern::Variant(name.clone(), fields.iter().map(|f| self.pattern(f)).collect(), span.clone())
+            }
+            Pattern::Binding(name, _type, span) => Pattern::Binding(name.clone(), _type.substitute(self.subst), span.clone()),
+            _ => pattern.clone(),
+        }
+    }
+}
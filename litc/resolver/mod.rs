@@ -1,3 +1,4 @@
 mod resolver;
+mod instantiate;
 
-pub use resolver::resolve;
\ No newline at end of file
+pub use resolver::resolve;
@@ -1,55 +1,906 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::mem;
 
 use crate::ast::*;
+use crate::diagnostics::Diagnostic;
+use crate::diagnostics::codes;
+
+use super::instantiate::Copier;
 
 pub fn resolve(program: &mut Program) {
-    let mut resolver = Resolver::new(&mut program.expr_arena);
+    add_default_ctors(program);
+    mark_static_methods(program);
+    set_aside_templates(program);
+
+    let implementations = program.implementations();
+    let consts = program.consts.iter().map(|c| (c.name.clone(), c._type.clone())).collect();
+    let templates = mem::take(&mut program.templates);
+    let mut arena = mem::take(&mut program.expr_arena);
+    let mut resolver = Resolver::new(&mut arena, templates, implementations, consts);
+    resolver.interfaces.extend(program.interface_sigs());
+
+    // uses of generic types in signatures are instantiated first, so that
+    // the signatures the bodies are resolved against name the instances
+    for func in &mut program.funcs {
+        resolver.instantiate_sig(&mut func.params, &mut func.ret_type);
+    }
+
+    for class in &mut program.classes {
+        resolver.instantiate_class_sigs(class);
+    }
+
+    for _enum in &mut program.enums {
+        resolver.instantiate_enum_sigs(_enum);
+    }
+
+    for block in program.impls.iter_mut().flat_map(|i| &mut i.blocks) {
+        block.target = resolver.instantiate_type(&block.target);
+
+        for method in &mut block.methods {
+            resolver.instantiate_sig(&mut method.params, &mut method.ret_type);
+        }
+    }
+
+    resolver.funcs.extend(program.signatures());
+    resolver.classes.extend(program.class_sigs());
+    resolver.enums.extend(program.enum_sigs());
+
+    for (owner, methods) in program.method_sigs() {
+        resolver.methods.entry(owner).or_default().extend(methods);
+    }
+
+    for constant in &program.consts {
+        resolver.resolve_const(constant);
+    }
 
     for func in &mut program.funcs {
         resolver.resolve_func(func);
     }
+
+    for class in &mut program.classes {
+        resolver.resolve_class(class);
+    }
+
+    for _enum in &mut program.enums {
+        resolver.resolve_enum(_enum);
+    }
+
+    for block in program.impls.iter_mut().flat_map(|i| &mut i.blocks) {
+        for method in &mut block.methods {
+            resolver.resolve_method(&block.target, method);
+        }
+    }
+
+    resolver.resolve_instances();
+
+    let probes = resolver.probe_templates();
+    let (mut templates, instances) = resolver.finish();
+
+    program.funcs.extend(instances.funcs);
+    program.classes.extend(instances.classes);
+    program.enums.extend(instances.enums);
+    program.impls.extend(impl_of(instances.methods));
+
+    templates.probes = probes;
+    program.templates = templates;
+    program.expr_arena = arena;
+}
+
+/// An `impl` block that holds instances of generic methods, if there are
+/// any.
+fn impl_of(methods: Vec<(Type, FuncDef)>) -> Option<ImplDef> {
+    let span = methods.first()?.1.span.clone();
+
+    Some(ImplDef {
+        interface: None,
+        blocks: methods.into_iter()
+            .map(|(target, method)| ImplBlock { target, span: method.span.clone(), methods: vec![method] })
+            .collect(),
+        span,
+    })
 }
 
+/// Moves the generic functions, classes, enums and methods of `program` to
+/// its templates. What remains has no generic parameters.
+fn set_aside_templates(program: &mut Program) {
+    let templates = &mut program.templates;
+
+    let (generic, funcs) = mem::take(&mut program.funcs).into_iter().partition(|f| !f.generics.is_empty());
+    templates.funcs = generic;
+    program.funcs = funcs;
+
+    let (generic, classes) = mem::take(&mut program.classes).into_iter().partition(|c| !c.generics.is_empty());
+    templates.classes = generic;
+    program.classes = classes;
+
+    let (generic, enums) = mem::take(&mut program.enums).into_iter().partition(|e| !e.generics.is_empty());
+    templates.enums = generic;
+    program.enums = enums;
+
+    for class in &mut program.classes {
+        let owner = Type::Named(class.name.clone(), Vec::new());
+        templates.methods.extend(take_generic_methods(&mut class.methods).into_iter().map(|m| (owner.clone(), m)));
+    }
+
+    for _enum in &mut program.enums {
+        let owner = Type::Named(_enum.name.clone(), Vec::new());
+        templates.methods.extend(take_generic_methods(&mut _enum.methods).into_iter().map(|m| (owner.clone(), m)));
+    }
+
+    for block in program.impls.iter_mut().flat_map(|i| &mut i.blocks) {
+        templates.methods.extend(take_generic_methods(&mut block.methods).into_iter().map(|m| (block.target.clone(), m)));
+    }
+}
+
+fn take_generic_methods(methods: &mut Vec<FuncDef>) -> Vec<FuncDef> {
+    let (generic, rest) = mem::take(methods).into_iter().partition(|m| !m.generics.is_empty());
+    *methods = rest;
+
+    generic
+}
+
+/// Marks the methods that implement an interface method declared without
+/// `self` as static, e.g. `parse` of `interface FromStr`.
+fn mark_static_methods(program: &mut Program) {
+    let interfaces = program.interface_sigs();
+    let is_static = |implemented: &[Type], method: &FuncDef| method.is_override && implemented.iter()
+        .filter_map(|interface| interfaces.get(&interface.to_string())?.method(&method.name))
+        .any(|decl| decl.receiver.is_none());
+
+    for class in &mut program.classes {
+        for method in &mut class.methods {
+            method.is_static = is_static(&class.interfaces, method);
+        }
+    }
+
+    for _enum in &mut program.enums {
+        for method in &mut _enum.methods {
+            method.is_static = is_static(&_enum.interfaces, method);
+        }
+    }
+
+    for _impl in &mut program.impls {
+        for method in _impl.blocks.iter_mut().flat_map(|b| &mut b.methods) {
+            method.is_static = is_static(_impl.interface.as_slice(), method);
+        }
+    }
+}
+
+/// Whether `method` of a generic type needs a probe of its own, being
+/// generic or conditional on a `#[T : Interface]` bound.
+fn needs_probe(method: &FuncDef) -> bool {
+    !method.generics.is_empty() || method.attrs.iter().any(|a| matches!(a.kind, AttrKind::Bound(..)))
+}
+
+/// Gives every class without constructors one that takes its fields in
+/// order, so `Point(1, 2)` works for `class Point { int x; int y; }`.
+fn add_default_ctors(program: &mut Program) {
+    for class in program.classes.iter_mut().filter(|c| c.ctors.is_empty()) {
+        let span = &class.span;
+        let mut stmts = Vec::new();
+
+        for field in &class.fields {
+            let receiver = program.expr_arena.add(ExprNode::new(Expr::Var("self".to_string()), span.clone()));
+            let target = program.expr_arena.add(ExprNode::new(Expr::Field(receiver, field.name.clone()), span.clone()));
+            let value = program.expr_arena.add(ExprNode::new(Expr::Var(field.name.clone()), span.clone()));
+
+            stmts.push(StmtNode::new(Stmt::Assign(target, None, value), span.clone()));
+        }
+
+        class.ctors.push(CtorDef {
+            generics: Vec::new(),
+            params: class.fields.iter()
+                .map(|f| Param { _type: f._type.clone(), name: f.name.clone(), variadic: false, span: f.span.clone() })
+                .collect(),
+            body: Block::new(stmts),
+            span: span.clone(),
+        });
+    }
+}
+
+/// The instances of templates made while resolving, and those of them
+/// whose bodies are not resolved yet.
+#[derive(Default)]
+struct Instances {
+    funcs: Vec<FuncDef>,
+    classes: Vec<ClassDef>,
+    enums: Vec<EnumDef>,
+    // instances of generic methods, with the type they belong to
+    methods: Vec<(Type, FuncDef)>,
+    pending: Vec<Pending>,
+    // each instance of a generic type, by name, with the template it is
+    // made from and its type arguments
+    types: HashMap<String, (String, Vec<Type>)>,
+}
+
+enum Pending {
+    Func(FuncDef),
+    Class(ClassDef),
+    Enum(EnumDef),
+    Method(Type, FuncDef),
+}
+
+/// How deeply type arguments may nest before an instance is taken to
+/// instantiate itself without end, as `fun <T> f(T x) { f(Box(x)); }` does.
+const MAX_TYPE_DEPTH: usize = 32;
+
+/// How long a type argument may be written out. `f(Pair(x, x))` doubles it
+/// with each instance, long before it nests too deeply.
+const MAX_TYPE_LENGTH: usize = 1 << 16;
+
 struct Resolver<'a> {
     arena: &'a mut ExprArena,
     declared: HashMap<String, Type>,
+    funcs: HashMap<String, FuncSig>,
+    classes: HashMap<String, ClassSig>,
+    enums: HashMap<String, EnumSig>,
+    methods: HashMap<String, HashMap<String, MethodSig>>,
+    consts: HashMap<String, Type>,
+    templates: Templates,
+    instances: Instances,
+    // pairs of a type and an interface it implements
+    implementations: HashSet<(String, String)>,
+    interfaces: HashMap<String, InterfaceSig>,
+    ret_type: Option<Type>,
+    // the type whose constructor or method is resolved
+    owner: Option<Type>,
+    // the type that the context expects of the expression resolved next
+    expected: Option<Type>,
 }
 
 impl<'a> Resolver<'a> {
-    fn new(expr_arena: &'a mut ExprArena) -> Resolver<'a> {
+    fn new(
+        expr_arena: &'a mut ExprArena,
+        templates: Templates,
+        implementations: HashSet<(String, String)>,
+        consts: HashMap<String, Type>,
+    ) -> Resolver<'a> {
         Resolver {
             arena: expr_arena,
             declared: HashMap::new(),
+            funcs: HashMap::new(),
+            classes: HashMap::new(),
+            enums: HashMap::new(),
+            methods: HashMap::new(),
+            consts,
+            templates,
+            instances: Instances::default(),
+            implementations,
+            interfaces: HashMap::new(),
+            ret_type: None,
+            owner: None,
+            expected: None,
         }
     }
 
+    fn finish(self) -> (Templates, Instances) {
+        (self.templates, self.instances)
+    }
+
+    fn resolve_const(&mut self, constant: &ConstDef) {
+        self.declared.clear();
+
+        let expr_type = self.resolve_expected(constant.expr_id, &constant._type);
+        self.coerce(constant.expr_id, &expr_type, &constant._type);
+    }
+
     fn resolve_func(&mut self, func: &mut FuncDef) {
         self.declared.clear();
+        self.ret_type = func.ret_type.clone();
+        self.owner = None;
+
+        for param in &func.params {
+            self.declared.insert(param.name.clone(), param._type.clone());
+        }
 
         self.resolve_block(&mut func.body)
     }
+
+    fn resolve_ctor(&mut self, class: &str, ctor: &mut CtorDef) {
+        self.declared.clear();
+        self.ret_type = None;
+        self.owner = Some(Type::Named(class.to_string(), Vec::new()));
+
+        self.declared.insert("self".to_string(), Type::Named(class.to_string(), Vec::new()));
+
+        for param in &ctor.params {
+            self.declared.insert(param.name.clone(), param._type.clone());
+        }
+
+        self.resolve_block(&mut ctor.body)
+    }
+
+    fn resolve_method(&mut self, owner: &Type, method: &mut FuncDef) {
+        self.declared.clear();
+        self.ret_type = method.ret_type.clone();
+        self.owner = Some(owner.clone());
+
+        if !method.is_static {
+            self.declared.insert("self".to_string(), owner.clone());
+        }
+
+        for param in &method.params {
+            self.declared.insert(param.name.clone(), param._type.clone());
+        }
+
+        self.resolve_block(&mut method.body)
+    }
+
+    fn resolve_class(&mut self, class: &mut ClassDef) {
+        for ctor in &mut class.ctors {
+            self.resolve_ctor(&class.name, ctor);
+        }
+
+        for method in &mut class.methods {
+            self.resolve_method(&Type::Named(class.name.clone(), Vec::new()), method);
+        }
+    }
+
+    fn resolve_enum(&mut self, _enum: &mut EnumDef) {
+        for method in &mut _enum.methods {
+            self.resolve_method(&Type::Named(_enum.name.clone(), Vec::new()), method);
+        }
+    }
+
+    /// Resolves the bodies of instances, which may need more instances in
+    /// turn, until none is left.
+    fn resolve_instances(&mut self) {
+        while let Some(pending) = self.instances.pending.pop() {
+            match pending {
+                Pending::Func(mut func) => {
+                    self.resolve_func(&mut func);
+                    self.instances.funcs.push(func);
+                }
+                Pending::Class(mut class) => {
+                    self.resolve_class(&mut class);
+                    self.instances.classes.push(class);
+                }
+                Pending::Enum(mut _enum) => {
+                    self.resolve_enum(&mut _enum);
+                    self.instances.enums.push(_enum);
+                }
+                Pending::Method(owner, mut method) => {
+                    self.resolve_method(&owner, &mut method);
+                    self.instances.methods.push((owner, method));
+                }
+            }
+        }
+    }
+
+    /// Makes the probes of the templates: one for each generic function,
+    /// class and enum, and one for each generic or conditional method, in
+    /// which the parameters of the method's type also have the bounds that
+    /// the method is conditional on.
+    fn probe_templates(&mut self) -> Vec<Probe> {
+        let mut probes = Vec::new();
+
+        // the generic methods of types that are not instances, taken before
+        // the probes below add those of the instances that they make
+        let methods = self.templates.methods.iter()
+            .filter(|(owner, _)| !self.instances.types.contains_key(&owner.to_string()))
+            .map(|(owner, method)| (owner.clone(), method.name.clone(), method.generics.clone()))
+            .collect::<Vec<_>>();
+
+        let funcs = self.templates.funcs.iter().map(|f| (f.name.clone(), f.generics.clone())).collect::<Vec<_>>();
+
+        for (name, generics) in funcs {
+            probes.push(self.probe(&generics, |resolver, args| {
+                resolver.instantiate_func(&name, args);
+            }));
+        }
+
+        let types = self.templates.classes.iter().map(|c| (&c.name, &c.generics, &c.methods))
+            .chain(self.templates.enums.iter().map(|e| (&e.name, &e.generics, &e.methods)))
+            .map(|(name, generics, methods)| (name.clone(), generics.clone(), methods.iter()
+                .filter(|m| needs_probe(m))
+                .map(|m| (m.name.clone(), m.generics.clone(), m.attrs.clone()))
+                .collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        for (name, generics, methods) in types {
+            probes.push(self.probe(&generics, |resolver, args| {
+                resolver.instantiate_generic_type(&name, args);
+            }));
+
+            for (method, method_generics, attrs) in methods {
+                let mut params = generics.clone();
+
+                for attr in attrs {
+                    if let AttrKind::Bound(name, bounds) = attr.kind && let Some(param) = params.iter_mut().find(|p| p.name == name) {
+                        param.bounds.extend(bounds);
+                    }
+                }
+
+                params.extend(method_generics);
+
+                probes.push(self.probe(&params, |resolver, args| {
+                    let (type_args, method_args) = args.split_at(generics.len());
+                    let instance = resolver.instantiate_generic_type(&name, type_args);
+
+                    if let Some(instance) = instance && !method_args.is_empty() {
+                        resolver.instantiate_method(&Type::Named(instance, Vec::new()), &method, method_args);
+                    }
+                }));
+            }
+        }
+
+        for (owner, name, generics) in methods {
+            probes.push(self.probe(&generics, |resolver, args| {
+                resolver.instantiate_method(&owner, &name, args);
+            }));
+        }
+
+        probes
+    }
+
+    /// Instantiates a template through `instantiate`, which is given a
+    /// placeholder class for each of `generics`, and resolves the result.
+    /// The signatures and instances made for the probe are taken out of the
+    /// resolver again.
+    fn probe(&mut self, generics: &[GenericParam], instantiate: impl FnOnce(&mut Self, &[Type])) -> Probe {
+        let funcs = self.funcs.clone();
+        let classes = self.classes.clone();
+        let enums = self.enums.clone();
+        let methods = self.methods.clone();
+        let implementations = self.implementations.clone();
+        let types = self.instances.types.clone();
+        let instances = mem::replace(&mut self.instances, Instances { types, ..Instances::default() });
+
+        let placeholders = generics.iter().map(|g| self.placeholder(g)).collect::<Vec<_>>();
+
+        for class in &placeholders {
+            for interface in &class.interfaces {
+                self.implementations.insert((class.name.clone(), interface.to_string()));
+            }
+
+            self.classes.insert(class.name.clone(), class.sig());
+            self.methods.remove(&class.name);
+            self.add_method_sigs(&class.name, &class.methods);
+        }
+
+        let args = generics.iter().map(|g| Type::Named(g.name.clone(), Vec::new())).collect::<Vec<_>>();
+        instantiate(self, &args);
+        self.resolve_instances();
+
+        let made = mem::replace(&mut self.instances, instances);
+        self.funcs = funcs;
+        self.classes = classes;
+        self.enums = enums;
+        self.methods = methods;
+        self.implementations = implementations;
+
+        Probe {
+            funcs: made.funcs,
+            classes: placeholders.into_iter().chain(made.classes).collect(),
+            enums: made.enums,
+            impls: impl_of(made.methods).into_iter().collect(),
+        }
+    }
+
+    /// The class that generic parameter `generic` stands for in a probe. It
+    /// has no fields or constructors, and implements the bounds of
+    /// `generic` with methods whose bodies are `unreachable`.
+    fn placeholder(&self, generic: &GenericParam) -> ClassDef {
+        let owner = Type::Named(generic.name.clone(), Vec::new());
+        let mut methods = Vec::new();
+
+        for bound in &generic.bounds {
+            let Some(sig) = self.interfaces.get(&bound.to_string()) else {
+                continue;
+            };
+
+            let subst = HashMap::from([(bound.to_string(), owner.clone())]);
+
+            methods.extend(sig.methods.iter().map(|decl| FuncDef {
+                name: decl.name.clone(),
+                generics: decl.generics.clone(),
+                attrs: Vec::new(),
+                receiver: decl.receiver.clone(),
+                params: decl.params.iter().map(|p| Param { _type: p._type.substitute(&subst), ..p.clone() }).collect(),
+                ret_type: decl.ret_type.as_ref().map(|t| t.substitute(&subst)),
+                body: Block::new(vec![StmtNode::new(Stmt::Unreachable(None), decl.span.clone())]),
+                is_pub: true,
+                is_override: true,
+                is_static: decl.receiver.is_none(),
+                span: decl.span.clone(),
+            }));
+        }
+
+        ClassDef {
+            name: generic.name.clone(),
+            generics: Vec::new(),
+            interfaces: generic.bounds.clone(),
+            fields: Vec::new(),
+            ctors: Vec::new(),
+            methods,
+            is_pub: true,
+            span: generic.span.clone(),
+        }
+    }
+
+    /// `_type` with each use of a generic class or enum replaced by its
+    /// instance, e.g. `Vec<int>` by the class called `Vec<int>`. Uses that
+    /// cannot be instantiated are left for the analyzer to report.
+    fn instantiate_type(&mut self, _type: &Type) -> Type {
+        match _type {
+            Type::Named(name, args) if !args.is_empty() => {
+                let args = args.iter().map(|arg| self.instantiate_type(arg)).collect::<Vec<_>>();
+
+                match self.instantiate_generic_type(name, &args) {
+                    Some(instance) => Type::Named(instance, Vec::new()),
+                    None => Type::Named(name.clone(), args),
+                }
+            }
+
+            Type::Array(inner) => Type::Array(Box::new(self.instantiate_type(inner))),
+            Type::Ptr(inner) => Type::Ptr(Box::new(self.instantiate_type(inner))),
+            Type::Fallible(inner) => Type::Fallible(Box::new(self.instantiate_type(inner))),
+
+            Type::Lambda(params, ret) => Type::Lambda(
+                params.iter().map(|param| self.instantiate_type(param)).collect(),
+                Box::new(self.instantiate_type(ret)),
+            ),
+
+            _ => _type.clone(),
+        }
+    }
+
+    fn instantiate_sig(&mut self, params: &mut [Param], ret_type: &mut Option<Type>) {
+        for param in params {
+            param._type = self.instantiate_type(&param._type);
+        }
+
+        if let Some(ret_type) = ret_type {
+            *ret_type = self.instantiate_type(ret_type);
+        }
+    }
+
+    fn instantiate_class_sigs(&mut self, class: &mut ClassDef) {
+        for field in &mut class.fields {
+            field._type = self.instantiate_type(&field._type);
+        }
+
+        for ctor in &mut class.ctors {
+            self.instantiate_sig(&mut ctor.params, &mut None);
+        }
+
+        for method in &mut class.methods {
+            self.instantiate_sig(&mut method.params, &mut method.ret_type);
+        }
+    }
+
+    fn instantiate_enum_sigs(&mut self, _enum: &mut EnumDef) {
+        for variant in &mut _enum.variants {
+            for field in &mut variant.fields {
+                *field = self.instantiate_type(field);
+            }
+        }
+
+        for method in &mut _enum.methods {
+            self.instantiate_sig(&mut method.params, &mut method.ret_type);
+        }
+    }
+
+    /// Returns the name of the instance of generic class or enum `name` for
+    /// `args`, and makes it if it is new. Returns `None` if `name` is not
+    /// generic or `args` do not fit its generic parameters.
+    fn instantiate_generic_type(&mut self, name: &str, args: &[Type]) -> Option<String> {
+        let instance = instance_name(name, args);
+
+        if self.instances.types.contains_key(&instance) {
+            return Some(instance);
+        }
+
+        let generics = match (self.templates.class(name), self.templates._enum(name)) {
+            (Some(class), _) => &class.generics,
+            (_, Some(_enum)) => &_enum.generics,
+            _ => return None,
+        };
+
+        let subst = self.check_type_args(name, generics, args)?;
+
+        // registered before its signatures are instantiated, which may
+        // name the instance itself
+        self.instances.types.insert(instance.clone(), (name.to_string(), args.to_vec()));
+
+        let owner = Type::Named(instance.clone(), Vec::new());
+        let mut copier = Copier { arena: self.arena, subst: &subst };
+
+        if let Some(template) = self.templates.class(name) {
+            let mut class = copier.class(template, &instance);
+
+            self.drop_unmet_methods(&owner, &mut class.methods, &mut class.interfaces, &subst);
+            self.instantiate_class_sigs(&mut class);
+            self.add_method_templates(&owner, &mut class.methods);
+
+            for interface in &class.interfaces {
+                self.implementations.insert((instance.clone(), interface.to_string()));
+            }
+
+            self.classes.insert(instance.clone(), class.sig());
+            self.add_method_sigs(&instance, &class.methods);
+            self.instances.pending.push(Pending::Class(class));
+        } else {
+            let mut _enum = copier._enum(self.templates._enum(name).unwrap(), &instance);
+
+            self.drop_unmet_methods(&owner, &mut _enum.methods, &mut _enum.interfaces, &subst);
+            self.instantiate_enum_sigs(&mut _enum);
+            self.add_method_templates(&owner, &mut _enum.methods);
+
+            for interface in &_enum.interfaces {
+                self.implementations.insert((instance.clone(), interface.to_string()));
+            }
+
+            self.enums.insert(instance.clone(), _enum.sig());
+            self.add_method_sigs(&instance, &_enum.methods);
+            self.instances.pending.push(Pending::Enum(_enum));
+        }
+
+        Some(instance)
+    }
+
+    /// Leaves out the methods of instance `owner` declared with a
+    /// `#[T : Interface]` bound that its type arguments `subst` do not meet,
+    /// together with the interfaces those methods implement, so that
+    /// `Vec<T> : Formatter` holds only where `T` is a `Formatter`. The
+    /// methods are recorded for the analyzer to report if they are called.
+    /// Bounds that name no parameter of the type are left to the analyzer.
+    fn drop_unmet_methods(&mut self, owner: &Type, methods: &mut Vec<FuncDef>, interfaces: &mut Vec<Type>, subst: &HashMap<String, Type>) {
+        for method in mem::take(methods) {
+            let unmet = method.attrs.iter().find_map(|attr| {
+                let AttrKind::Bound(param, bounds) = &attr.kind else {
+                    return None;
+                };
+
+                let arg = subst.get(param)?;
+                let bound = bounds.iter().find(|b| !self.implementations.contains(&(arg.to_string(), b.to_string())))?;
+
+                Some(UnmetBound {
+                    owner: owner.clone(),
+                    method: method.name.clone(),
+                    param: param.clone(),
+                    arg: arg.clone(),
+                    bound: bound.clone(),
+                    span: attr.span.clone(),
+                })
+            });
+
+            let Some(unmet) = unmet else {
+                let attrs = method.attrs.into_iter().filter(|a| !matches!(a.kind, AttrKind::Bound(..))).collect();
+                methods.push(FuncDef { attrs, ..method });
+                continue;
+            };
+
+            if method.is_override {
+                interfaces.retain(|i| self.interfaces.get(&i.to_string()).is_none_or(|sig| sig.method(&method.name).is_none()));
+            }
+
+            // probes may make the same instance more than once
+            if self.templates.unmet(owner, &unmet.method).is_none() {
+                self.templates.unmet.push(unmet);
+            }
+        }
+    }
+
+    /// Moves the generic methods of instance `owner` to the templates,
+    /// unless a probe has put them there before.
+    fn add_method_templates(&mut self, owner: &Type, methods: &mut Vec<FuncDef>) {
+        let (generic, rest) = mem::take(methods).into_iter().partition(|m: &FuncDef| !m.generics.is_empty());
+        *methods = rest;
+
+        for method in generic {
+            if self.templates.method(owner, &method.name).is_none() {
+                self.templates.methods.push((owner.clone(), method));
+            }
+        }
+    }
+
+    fn add_method_sigs(&mut self, owner: &str, methods: &[FuncDef]) {
+        let sigs = self.methods.entry(owner.to_string()).or_default();
+
+        for method in methods {
+            sigs.entry(method.name.clone()).or_insert_with(|| method.method_sig());
+        }
+    }
+
+    /// Returns the name of the instance of generic function `name` for
+    /// `args`, and makes it if it is new.
+    fn instantiate_func(&mut self, name: &str, args: &[Type]) -> Option<String> {
+        let template = self.templates.func(name)?;
+        let subst = self.check_type_args(name, &template.generics, args)?;
+        let instance = instance_name(name, args);
+
+        if !self.funcs.contains_key(&instance) {
+            let mut func = Copier { arena: self.arena, subst: &subst }.func(template);
+            func.name = instance.clone();
+
+            self.instantiate_sig(&mut func.params, &mut func.ret_type);
+            self.funcs.insert(instance.clone(), func.sig());
+            self.instances.pending.push(Pending::Func(func));
+        }
+
+        Some(instance)
+    }
+
+    /// Returns the name of the instance of generic method `name` of `owner`
+    /// for `args`, e.g. `parse<int>`, and makes it if it is new.
+    fn instantiate_method(&mut self, owner: &Type, name: &str, args: &[Type]) -> Option<String> {
+        let template = self.templates.method(owner, name)?;
+        let subst = self.check_type_args(name, &template.generics, args)?;
+        let instance = instance_name(name, args);
+        let sigs = self.methods.entry(owner.to_string()).or_default();
+
+        if !sigs.contains_key(&instance) {
+            let mut method = Copier { arena: self.arena, subst: &subst }.func(template);
+            method.name = instance.clone();
+
+            self.instantiate_sig(&mut method.params, &mut method.ret_type);
+            self.add_method_sigs(&owner.to_string(), std::slice::from_ref(&method));
+            self.instances.pending.push(Pending::Method(owner.clone(), method));
+        }
+
+        Some(instance)
+    }
+
+    /// Maps `generics` to `args` if there is an argument for each parameter
+    /// and each argument implements the bounds of its parameter.
+    fn check_type_args(&self, name: &str, generics: &[GenericParam], args: &[Type]) -> Option<HashMap<String, Type>> {
+        if generics.len() != args.len() {
+            return None;
+        }
+
+        for (generic, arg) in generics.iter().zip(args) {
+            if generic.bounds.iter().any(|bound| !self.implementations.contains(&(arg.to_string(), bound.to_string()))) {
+                return None;
+            }
+
+            if self.type_depth(arg) > MAX_TYPE_DEPTH {
+                Diagnostic::error(format!("Instantiating `{}` nests type arguments deeper than the limit of {}", name, MAX_TYPE_DEPTH))
+                    .with_code(&codes::E0059)
+                    .with_span(&generic.span)
+                    .with_note(format!("`{}` is instantiated with `{}`", generic.name, arg))
+                    .abort();
+            }
+
+            let length = arg.to_string().len();
+
+            if length > MAX_TYPE_LENGTH {
+                Diagnostic::error(format!("Instantiating `{}` makes a type argument longer than the limit of {} characters", name, MAX_TYPE_LENGTH))
+                    .with_code(&codes::E0059)
+                    .with_span(&generic.span)
+                    .with_note(format!("`{}` is instantiated with a type {} characters long", generic.name, length))
+                    .abort();
+            }
+        }
+
+        Some(generics.iter().map(|g| g.name.clone()).zip(args.iter().cloned()).collect())
+    }
+
+    /// Infers the type arguments for `generics` from a call whose
+    /// parameters, which may name them, are given `arg_types`. Returns
+    /// `None` unless every parameter is determined; where two arguments
+    /// disagree, the first one wins and the call fails to check later.
+    /// `result` pairs the type of the call's value with the type that its
+    /// context expects, which is matched first, so `Box<int> b = Box(3)`
+    /// takes `int` rather than `unt` from the literal.
+    fn infer(&self, generics: &[GenericParam], result: Option<(&Type, &Type)>, params: &[Type], arg_types: &[Type]) -> Option<Vec<Type>> {
+        let mut inferred = HashMap::new();
+
+        if let Some((result, expected)) = result {
+            self.unify(result, expected, generics, &mut inferred);
+        }
+
+        for (param, arg) in params.iter().zip(arg_types) {
+            self.unify(param, arg, generics, &mut inferred);
+        }
+
+        generics.iter().map(|g| inferred.get(&g.name).cloned()).collect()
+    }
+
+    fn unify(&self, param: &Type, arg: &Type, generics: &[GenericParam], inferred: &mut HashMap<String, Type>) {
+        match (param, arg) {
+            (_, Type::Void) => {}
+
+            (Type::Named(name, args), _) if args.is_empty() && generics.iter().any(|g| g.name == *name) => {
+                inferred.entry(name.clone()).or_insert_with(|| arg.clone());
+            }
+
+            (Type::Array(param), Type::Array(arg))
+            | (Type::Ptr(param), Type::Ptr(arg))
+            | (Type::Fallible(param), Type::Fallible(arg)) => self.unify(param, arg, generics, inferred),
+
+            // `Vec<T>` against the instance `Vec<int>`
+            (Type::Named(name, params), Type::Named(instance, _)) => {
+                if let Some((template, args)) = self.instances.types.get(instance) && template == name {
+                    for (param, arg) in params.iter().zip(args) {
+                        self.unify(param, arg, generics, inferred);
+                    }
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// How many levels of type arguments and array elements `_type` has,
+    /// counting those of the instances it names, e.g. 3 for `Box<Box<int>>`
+    /// however it is written.
+    fn type_depth(&self, _type: &Type) -> usize {
+        match _type {
+            Type::Named(name, args) if args.is_empty() => match self.instances.types.get(name) {
+                Some((_, args)) => 1 + args.iter().map(|arg| self.type_depth(arg)).max().unwrap_or(0),
+                None => 1,
+            },
+            Type::Named(_, args) => 1 + args.iter().map(|arg| self.type_depth(arg)).max().unwrap_or(0),
+            Type::Array(inner) | Type::Ptr(inner) | Type::Fallible(inner) => 1 + self.type_depth(inner),
+            _ => 1,
+        }
+    }
+
+    /// Whether `_type` is the instance of generic class or enum `template`.
+    fn is_instance_of(&self, _type: &Type, template: &str) -> bool {
+        matches!(_type, Type::Named(name, _) if self.instances.types.get(name).is_some_and(|(t, _)| t == template))
+    }
     
     fn resolve_block(&mut self, block: &mut Block) {
-        for stmt in block.mut_stmts() {
-            match stmt {
+        for node in block.mut_stmts() {
+            match &mut node.stmt {
                 Stmt::VarDecl(v) => {
-                    let (_, expr_type) = self.resolve_expr(v.expr_id);
+                    v._type = self.instantiate_type(&v._type);
 
-                    if expr_type == Type::Unt && v._type != Type::Unt {
-                        self.coerce_node_to(v.expr_id, &v._type);
-                    }
+                    let expr_type = self.resolve_expected(v.expr_id, &v._type);
+                    self.coerce(v.expr_id, &expr_type, &v._type);
 
                     self.declared.insert(v.name.clone(), v._type.clone());
                 }
-                Stmt::Println(expr_id) => {
+                Stmt::Println(expr_id) | Stmt::Unreachable(Some(expr_id)) | Stmt::Expr(expr_id) => {
                     self.resolve_expr(*expr_id);
                 }
+                Stmt::Return(Some(expr_id)) => {
+                    match self.ret_type.clone() {
+                        Some(ret_type) => {
+                            let expr_type = self.resolve_expected(*expr_id, &ret_type);
+                            self.coerce(*expr_id, &expr_type, &ret_type);
+                        }
+                        None => {
+                            self.resolve_expr(*expr_id);
+                        }
+                    }
+                }
+                Stmt::Assign(target, _, value) => {
+                    let (_, target_type) = self.resolve_expr(*target);
+                    let value_type = self.resolve_expected(*value, &target_type);
+                    self.coerce(*value, &value_type, &target_type);
+                }
+                // the names bound by a pattern are in scope in its block only
+                Stmt::Switch(scrutinee, cases) => {
+                    let (_, scrutinee_ty) = self.resolve_expr(*scrutinee);
+
+                    for case in cases {
+                        self.resolve_pattern(&mut case.pattern, &scrutinee_ty);
+
+                        let outer = self.declare_bindings(&case.pattern);
+                        self.resolve_block(&mut case.body);
+                        self.declared = outer;
+                    }
+                }
                 _ => {}
             }
         }
     }
 
+    /// Resolves expression `id` where a value of type `expected` is
+    /// needed, which the type arguments of a generic call or variant are
+    /// inferred from first.
+    fn resolve_expected(&mut self, id: ExprId, expected: &Type) -> Type {
+        self.expected = Some(expected.clone());
+        self.resolve_expr(id).1
+    }
+
     fn resolve_expr(&mut self, id: ExprId) -> (Expr, Type) {
+        // only the expression itself is expected to be of that type
+        let expected = self.expected.take();
         let expr_node = self.arena.get(id);
         let expr = expr_node.expr.clone();
 
@@ -67,6 +918,11 @@ impl<'a> Resolver<'a> {
                 (Expr::Var(name.clone()), ty)
             }
 
+            Expr::Const(ref name) => {
+                let ty = self.consts.get(name).cloned().unwrap_or(Type::Unt);
+                (Expr::Const(name.clone()), ty)
+            }
+
             Expr::Binary (op, left, right) => {
                 let (_, left_ty) = self.resolve_expr(left);
                 let (_, right_ty) = self.resolve_expr(right);
@@ -109,6 +965,10 @@ impl<'a> Resolver<'a> {
             
             Expr::Cast (to, expr) => {
                 self.resolve_expr(expr);
+
+                let to = self.instantiate_type(&to);
+                let span = self.arena.get(id).span.clone();
+                self.arena.set(id, ExprNode::new(Expr::Cast(to.clone(), expr), span));
                 
                 // TODO: Хуйня, переделывай!
                 
@@ -141,11 +1001,452 @@ impl<'a> Resolver<'a> {
 
                 (Expr::Cast(to.clone(), expr), to)
             }
+
+            Expr::Field (expr, name) => {
+                let (_, expr_ty) = self.resolve_expr(expr);
+
+                let field_ty = match &expr_ty {
+                    Type::Named(class, _) => self.classes.get(class)
+                        .and_then(|c| c.field(&name))
+                        .map(|(_, f)| f._type.clone()),
+                    _ => None,
+                };
+
+                (Expr::Field(expr, name), field_ty.unwrap_or(Type::Unt))
+            }
+
+            Expr::Index (expr, index) => {
+                let (_, expr_ty) = self.resolve_expr(expr);
+                self.resolve_expr(index);
+
+                let elem_ty = match expr_ty {
+                    Type::Array(elem) => *elem,
+                    _ => Type::Char,
+                };
+
+                (Expr::Index(expr, index), elem_ty)
+            }
+
+            Expr::Call (name, type_args, args)
+                if args.len() == 2 && Builtin::from_name(&name).is_some_and(|b| b.arith_op().is_some()) =>
+            {
+                let (_, left_ty) = self.resolve_expr(args[0]);
+                let (_, right_ty) = self.resolve_expr(args[1]);
+
+                let target_ty = Resolver::numeric_tower(&left_ty, &right_ty).unwrap_or(left_ty.clone());
+
+                if left_ty != target_ty { self.coerce_node_to(args[0], &target_ty); }
+                if right_ty != target_ty { self.coerce_node_to(args[1], &target_ty); }
+
+                (Expr::Call(name, type_args, args), target_ty)
+            }
+
+            Expr::Call (name, type_args, args) if self.templates.class(&name).is_some() => {
+                let arg_types = args.iter().map(|arg| self.resolve_expr(*arg).1).collect::<Vec<_>>();
+                let type_args = match type_args.is_empty() {
+                    true => {
+                        let template = self.templates.class(&name).unwrap();
+                        let result = Type::Named(name.clone(), template.generics.iter().map(|g| Type::Named(g.name.clone(), Vec::new())).collect());
+
+                        template.ctors.iter()
+                            .filter(|ctor| ctor.params.len() == args.len())
+                            .find_map(|ctor| {
+                                let params = ctor.params.iter().map(|p| p._type.clone()).collect::<Vec<_>>();
+                                self.infer(&template.generics, expected.as_ref().map(|e| (&result, e)), &params, &arg_types)
+                            })
+                            .unwrap_or_default()
+                    }
+                    false => type_args.iter().map(|arg| self.instantiate_type(arg)).collect(),
+                };
+
+                match self.instantiate_generic_type(&name, &type_args) {
+                    Some(instance) => self.construct(id, instance, &args, &arg_types),
+                    None => self.leave(id, Expr::Call(name.clone(), type_args, args), Type::Named(name, Vec::new())),
+                }
+            }
+
+            Expr::Call (name, type_args, args) if self.classes.contains_key(&name) => {
+                let arg_types: Vec<Type> = args.iter().map(|arg| self.resolve_expr(*arg).1).collect();
+
+                match type_args.is_empty() {
+                    true => self.construct(id, name, &args, &arg_types),
+                    false => (Expr::Call(name.clone(), type_args, args), Type::Named(name, Vec::new())),
+                }
+            }
+
+            Expr::Call (name, type_args, args) if self.templates.func(&name).is_some() => {
+                let arg_types = args.iter().map(|arg| self.resolve_expr(*arg).1).collect::<Vec<_>>();
+                let template = self.templates.func(&name).unwrap();
+                let sig = template.sig();
+                let type_args = match type_args.is_empty() {
+                    true => {
+                        let result = sig.ret_type.as_ref().zip(expected.as_ref());
+                        self.infer(&template.generics, result, &sig.params, &arg_types).unwrap_or_default()
+                    }
+                    false => type_args.iter().map(|arg| self.instantiate_type(arg)).collect(),
+                };
+
+                let Some(instance) = self.instantiate_func(&name, &type_args) else {
+                    return self.leave(id, Expr::Call(name, type_args, args), Type::Void);
+                };
+
+                let sig = self.funcs[&instance].clone();
+                self.coerce_args(&args, &arg_types, &sig.params);
+
+                self.leave(id, Expr::Call(instance, Vec::new(), args), sig.ret_type.unwrap_or(Type::Void))
+            }
+
+            Expr::Call (name, type_args, args) => {
+                let sig = self.funcs.get(&name).cloned();
+                self.resolve_args(&args, sig.as_ref());
+
+                let ret_ty = sig.and_then(|s| s.ret_type).unwrap_or(Type::Void);
+
+                (Expr::Call(name, type_args, args), ret_ty)
+            }
+
+            // a generic enum is instantiated for the types of the fields, or
+            // else for the type that the context expects
+            Expr::Variant (_enum, name, args) if self.templates._enum(&_enum).is_some() => {
+                let arg_types = args.iter().map(|arg| self.resolve_expr(*arg).1).collect::<Vec<_>>();
+                let template = self.templates._enum(&_enum).unwrap();
+                let result = Type::Named(_enum.clone(), template.generics.iter().map(|g| Type::Named(g.name.clone(), Vec::new())).collect());
+
+                let inferred = template.variants.iter()
+                    .find(|v| v.name == name)
+                    .and_then(|v| self.infer(&template.generics, expected.as_ref().map(|e| (&result, e)), &v.fields, &arg_types))
+                    .or_else(|| match &self.owner {
+                        Some(Type::Named(owner, _)) if self.is_instance_of(self.owner.as_ref().unwrap(), &_enum) => {
+                            Some(self.instances.types[owner].1.clone())
+                        }
+                        _ => None,
+                    });
+
+                match inferred.and_then(|args| self.instantiate_generic_type(&_enum, &args)) {
+                    Some(instance) => {
+                        let expr = Expr::Variant(instance.clone(), name, args.clone());
+                        self.coerce_variant_args(&instance, &expr, &arg_types);
+
+                        self.leave(id, expr, Type::Named(instance, Vec::new()))
+                    }
+                    None => (Expr::Variant(_enum.clone(), name, args), Type::Named(_enum, Vec::new())),
+                }
+            }
+
+            // `Point::parse(s)` names a method of the type, not a variant
+            Expr::Variant (owner, name, args) if self.is_static_method(&owner, &name) => {
+                self.leave(id, Expr::Associated(owner, name, args), Type::Void);
+                self.resolve_expr(id)
+            }
+
+            Expr::Variant (_enum, name, args) => {
+                let sig = self.enums.get(&_enum)
+                    .and_then(|e| e.variant(&name))
+                    .map(|(_, v)| FuncSig { params: v.fields.clone(), ret_type: None });
+
+                self.resolve_args(&args, sig.as_ref());
+
+                (Expr::Variant(_enum.clone(), name, args), Type::Named(_enum, Vec::new()))
+            }
+
+            Expr::MethodCall (receiver, name, type_args, args) => {
+                let (_, receiver_ty) = self.resolve_expr(receiver);
+
+                if self.templates.method(&receiver_ty, &name).is_some() {
+                    let (expr, _type) = self.call_generic_method(receiver, receiver_ty, name, type_args, args, expected);
+                    return self.leave(id, expr, _type);
+                }
+
+                let owner = receiver_ty.to_string();
+
+                let sig = self.methods.get(&owner)
+                    .and_then(|methods| methods.get(&name))
+                    .filter(|m| !m.is_static)
+                    .map(|m| m.sig.clone());
+
+                self.resolve_args(&args, sig.as_ref());
+
+                // left for the analyzer to report
+                let Some(sig) = sig.filter(|_| type_args.is_empty()) else {
+                    return (Expr::MethodCall(receiver, name, type_args, args), Type::Void);
+                };
+
+                let new_expr = Expr::Invoke(owner, name, receiver, args);
+                let span = self.arena.get(id).span.clone();
+                self.arena.set(id, ExprNode::new(new_expr.clone(), span));
+
+                (new_expr, sig.ret_type.unwrap_or(Type::Void))
+            }
+
+            Expr::Invoke (owner, name, receiver, args) => {
+                self.resolve_expr(receiver);
+
+                let sig = self.methods[&owner][&name].sig.clone();
+                self.resolve_args(&args, Some(&sig));
+
+                (Expr::Invoke(owner, name, receiver, args), sig.ret_type.unwrap_or(Type::Void))
+            }
+
+            Expr::Associated (owner, name, args) => {
+                let sig = self.methods.get(&owner)
+                    .and_then(|methods| methods.get(&name))
+                    .filter(|m| m.is_static)
+                    .map(|m| m.sig.clone());
+
+                self.resolve_args(&args, sig.as_ref());
+
+                let ret_ty = sig.and_then(|s| s.ret_type).unwrap_or(Type::Void);
+
+                (Expr::Associated(owner, name, args), ret_ty)
+            }
+
+            Expr::Construct (name, ctor, args) => {
+                for arg in &args {
+                    self.resolve_expr(*arg);
+                }
+
+                (Expr::Construct(name.clone(), ctor, args), Type::Named(name, Vec::new()))
+            }
+
+            // the type of a switch is the type of its arms, where `panic(..)`
+            // arms have none and untyped integers take the type of the rest
+            Expr::Switch (scrutinee, mut arms) => {
+                let (_, scrutinee_ty) = self.resolve_expr(scrutinee);
+                let mut arm_types = Vec::new();
+
+                for arm in &mut arms {
+                    self.resolve_pattern(&mut arm.pattern, &scrutinee_ty);
+
+                    let outer = self.declare_bindings(&arm.pattern);
+                    arm_types.push(self.resolve_expr(arm.expr).1);
+                    self.declared = outer;
+                }
+
+                let switch_ty = arm_types.iter().find(|t| **t != Type::Void && **t != Type::Unt && !self.is_template_type(t))
+                    .or_else(|| arm_types.iter().find(|t| **t != Type::Void))
+                    .cloned()
+                    .unwrap_or(Type::Void);
+
+                for (arm, arm_ty) in arms.iter().zip(&arm_types) {
+                    self.coerce(arm.expr, arm_ty, &switch_ty);
+                }
+
+                let new_expr = Expr::Switch(scrutinee, arms);
+                let span = self.arena.get(id).span.clone();
+                self.arena.set(id, ExprNode::new(new_expr.clone(), span));
+
+                (new_expr, switch_ty)
+            }
+
+            // left for the analyzer to reject
+            expr @ Expr::Null => (expr, Type::Void),
         };
 
         (new_expr, current_type)
     }
 
+    /// Resolves `pattern` against a value of `_type`: plain names become the
+    /// unit variants of the enum they name, or else bindings of the value,
+    /// variant paths are checked against the enum, and untyped integers take
+    /// the type of the value. Patterns that cannot match are left for the
+    /// analyzer to report.
+    fn resolve_pattern(&self, pattern: &mut Pattern, _type: &Type) {
+        match pattern {
+            Pattern::Lit(lit, _) => {
+                *lit = match (&*lit, _type) {
+                    (Lit::Unt(u), Type::Int) => Lit::Int(*u as i64),
+                    (Lit::Unt(u), Type::Float) => Lit::Float(*u as f64),
+                    (Lit::Int(i), Type::Float) => Lit::Float(*i as f64),
+                    (lit, _) => lit.clone(),
+                };
+            }
+
+            Pattern::Name(name, span) => match self.pattern_variant(name, _type) {
+                Some((qualified, _)) => *pattern = Pattern::Variant(qualified, Vec::new(), span.clone()),
+                None if !name.contains("::") => *pattern = Pattern::Binding(name.clone(), _type.clone(), span.clone()),
+                None => {}
+            },
+
+            Pattern::Variant(name, fields, _) => {
+                if let Some((qualified, field_types)) = self.pattern_variant(name, _type) {
+                    *name = qualified;
+
+                    for (field, field_ty) in fields.iter_mut().zip(&field_types) {
+                        self.resolve_pattern(field, field_ty);
+                    }
+                }
+            }
+
+            Pattern::Wildcard(_) | Pattern::Binding(..) => {}
+        }
+    }
+
+    /// The variant of enum `_type` that pattern name `name` refers to, as
+    /// `Enum::Variant`, with the types of its fields.
+    fn pattern_variant(&self, name: &str, _type: &Type) -> Option<(String, Vec<Type>)> {
+        let Type::Named(_enum, _) = _type else {
+            return None;
+        };
+
+        let variant = match name.rsplit_once("::") {
+            Some((path, variant)) if path == _enum || self.is_instance_of(_type, path) => variant,
+            Some(_) => return None,
+            None => name,
+        };
+
+        let (_, def) = self.enums.get(_enum)?.variant(variant)?;
+
+        Some((format!("{}::{}", _enum, variant), def.fields.clone()))
+    }
+
+    /// Declares the names bound by `pattern` and returns the variables that
+    /// were declared before, to restore once the arm is resolved.
+    fn declare_bindings(&mut self, pattern: &Pattern) -> HashMap<String, Type> {
+        let outer = self.declared.clone();
+
+        for (name, _type, _) in pattern.bindings() {
+            self.declared.insert(name.to_string(), _type.clone());
+        }
+
+        outer
+    }
+
+    /// Resolves the arguments of a call and coerces untyped integers to the
+    /// parameter types of `sig`, when the callee is known.
+    fn resolve_args(&mut self, args: &[ExprId], sig: Option<&FuncSig>) {
+        let params = sig.map(|s| s.params.as_slice()).unwrap_or_default();
+
+        for (i, arg) in args.iter().enumerate() {
+            let Some(param_ty) = params.get(i) else {
+                self.resolve_expr(*arg);
+                continue;
+            };
+
+            let arg_ty = self.resolve_expected(*arg, param_ty);
+            self.coerce(*arg, &arg_ty, param_ty);
+        }
+    }
+
+    fn coerce_args(&mut self, args: &[ExprId], arg_types: &[Type], params: &[Type]) {
+        for ((arg, arg_ty), param_ty) in args.iter().zip(arg_types).zip(params) {
+            self.coerce(*arg, arg_ty, param_ty);
+        }
+    }
+
+    /// Coerces the fields of `variant`, a variant of enum instance
+    /// `instance`, to the types of the instance.
+    fn coerce_variant_args(&mut self, instance: &str, variant: &Expr, arg_types: &[Type]) {
+        let Expr::Variant(_, name, args) = variant else {
+            return;
+        };
+
+        if let Some((_, def)) = self.enums[instance].variant(name) {
+            let fields = def.fields.clone();
+            self.coerce_args(args, arg_types, &fields);
+        }
+    }
+
+    /// Resolves a call of generic method `name` to its instance for the
+    /// explicit or inferred type arguments.
+    fn call_generic_method(
+        &mut self,
+        receiver: ExprId,
+        receiver_ty: Type,
+        name: String,
+        type_args: Vec<Type>,
+        args: Vec<ExprId>,
+        expected: Option<Type>,
+    ) -> (Expr, Type) {
+        let arg_types = args.iter().map(|arg| self.resolve_expr(*arg).1).collect::<Vec<_>>();
+        let template = self.templates.method(&receiver_ty, &name).unwrap();
+        let sig = template.sig();
+        let type_args = match type_args.is_empty() {
+            true => {
+                let result = sig.ret_type.as_ref().zip(expected.as_ref());
+                self.infer(&template.generics, result, &sig.params, &arg_types).unwrap_or_default()
+            }
+            false => type_args.iter().map(|arg| self.instantiate_type(arg)).collect(),
+        };
+
+        let Some(instance) = self.instantiate_method(&receiver_ty, &name, &type_args) else {
+            return (Expr::MethodCall(receiver, name, type_args, args), Type::Void);
+        };
+
+        let owner = receiver_ty.to_string();
+        let sig = self.methods[&owner][&instance].sig.clone();
+        self.coerce_args(&args, &arg_types, &sig.params);
+
+        (Expr::Invoke(owner, instance, receiver, args), sig.ret_type.unwrap_or(Type::Void))
+    }
+
+    /// Sets node `id` to `expr` and returns it with `_type`.
+    fn leave(&mut self, id: ExprId, expr: Expr, _type: Type) -> (Expr, Type) {
+        let span = self.arena.get(id).span.clone();
+        self.arena.set(id, ExprNode::new(expr.clone(), span));
+
+        (expr, _type)
+    }
+
+    /// Resolves a call of class `name` with arguments of `arg_types` to one
+    /// of its constructors. Calls that no constructor fits are left for the
+    /// analyzer to report.
+    fn construct(&mut self, id: ExprId, name: String, args: &[ExprId], arg_types: &[Type]) -> (Expr, Type) {
+        let class_ty = Type::Named(name.clone(), Vec::new());
+
+        let Some(ctor) = self.choose_ctor(&self.classes[&name], arg_types) else {
+            return self.leave(id, Expr::Call(name, Vec::new(), args.to_vec()), class_ty);
+        };
+
+        let params = self.classes[&name].ctors[ctor].params.clone();
+        self.coerce_args(args, arg_types, &params);
+
+        self.leave(id, Expr::Construct(name, ctor, args.to_vec()), class_ty)
+    }
+
+    /// Fits expression `id`, of type `from`, to the type `to` that its
+    /// context expects: untyped integers take the expected numeric type, and
+    /// a variant of a generic enum whose fields do not determine its type
+    /// arguments, such as `Option::Null`, takes the expected instance.
+    fn coerce(&mut self, id: ExprId, from: &Type, to: &Type) {
+        let is_fitting_variant = self.is_template_type(from)
+            && matches!(from, Type::Named(template, _) if self.is_instance_of(to, template));
+
+        if (*from == Type::Unt && *to != Type::Unt) || is_fitting_variant {
+            self.coerce_node_to(id, to);
+        }
+    }
+
+    /// Whether `name` is a static method of `owner` and no variant of it.
+    fn is_static_method(&self, owner: &str, name: &str) -> bool {
+        self.enums.get(owner).is_none_or(|e| e.variant(name).is_none())
+            && self.methods.get(owner).and_then(|methods| methods.get(name)).is_some_and(|m| m.is_static)
+    }
+
+    /// Whether `_type` is a generic enum without type arguments, which is
+    /// the type of its variants until they are instantiated.
+    fn is_template_type(&self, _type: &Type) -> bool {
+        matches!(_type, Type::Named(name, args) if args.is_empty() && self.templates._enum(name).is_some())
+    }
+
+    /// Picks the constructor of `class` that a call with `arg_types` goes to:
+    /// one whose parameters match exactly, or else one that the arguments
+    /// can be coerced to. A class with a single constructor always gets it,
+    /// so mismatches are reported against that one.
+    fn choose_ctor(&self, class: &ClassSig, arg_types: &[Type]) -> Option<usize> {
+        if class.ctors.len() == 1 {
+            return Some(0);
+        }
+
+        let coercible = |arg: &Type, param: &Type| arg == param || (*arg == Type::Unt && param.is_num_type());
+
+        let matches = |exact: bool| class.ctors.iter().position(|ctor| {
+            ctor.params.len() == arg_types.len()
+                && ctor.params.iter().zip(arg_types).all(|(param, arg)| if exact { arg == param } else { coercible(arg, param) })
+        });
+
+        matches(true).or_else(|| matches(false))
+    }
+
     fn coerce_node_to(&mut self, id: ExprId, target: &Type) {
         let mut node = self.arena.get(id).clone();
 
@@ -170,6 +1471,21 @@ impl<'a> Resolver<'a> {
             Expr::Unary (_, expr) => {
                 self.coerce_node_to(*expr, target);
             }
+            Expr::Switch (_, arms) => {
+                for arm in arms {
+                    self.coerce_node_to(arm.expr, target);
+                }
+            }
+            Expr::Variant (_enum, _, args) if self.is_instance_of(target, _enum) => {
+                let Type::Named(instance, _) = target else {
+                    unreachable!()
+                };
+
+                let arg_types = args.clone().iter().map(|arg| self.resolve_expr(*arg).1).collect::<Vec<_>>();
+
+                *_enum = instance.clone();
+                self.coerce_variant_args(instance, &node.expr, &arg_types);
+            }
             _ => {}
         }
 
@@ -192,4 +1508,4 @@ impl<'a> Resolver<'a> {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
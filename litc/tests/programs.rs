@@ -0,0 +1,459 @@
+//! Runs `litc` on the programs in `tests/programs` and checks what it
+//! reports.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+
+/// The path of `name` in `tests`.
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join(name)
+}
+
+fn litc(args: &[&str], program: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_litc"))
+        .args(args)
+        .arg("--error-format=json")
+        .arg(fixture("programs").join(program))
+        .output()
+        .expect("failed to run litc")
+}
+
+/// Compiles `program` with `args` and returns the path of its IR.
+fn compile(args: &[&str], program: &str) -> PathBuf {
+    let ir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(program).with_extension("ll");
+    let compiled = litc(&[args, &["-o", ir.to_str().unwrap()]].concat(), program);
+    assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stderr));
+
+    ir
+}
+
+/// Compiles `program` with `args` and runs it with `lli`.
+fn run(args: &[&str], program: &str) -> Output {
+    Command::new("lli").arg(compile(args, program)).output().expect("failed to run lli")
+}
+
+/// The codes of the diagnostics that `litc --check` reports for `program`.
+fn check(program: &str) -> Vec<String> {
+    codes(&litc(&["--check"], program))
+}
+
+/// The codes of the diagnostics in `output`.
+fn codes(output: &Output) -> Vec<String> {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    stderr.lines()
+        .filter_map(|line| line.split("\"code\":\"").nth(1))
+        .filter_map(|rest| rest.split('"').next())
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn polymorphic_recursion_hits_the_depth_limit() {
+    assert_eq!(check("polymorphic_recursion.lit"), ["E0059"]);
+}
+
+#[test]
+fn doubling_recursion_hits_the_length_limit() {
+    assert_eq!(check("doubling_recursion.lit"), ["E0059"]);
+}
+
+#[test]
+fn static_methods_are_called_through_types() {
+    assert_eq!(check("static_methods.lit"), Vec::<String>::new());
+
+    let output = litc(&["-o", "/dev/stdout"], "static_methods.lit");
+    assert!(output.status.success());
+}
+
+#[test]
+fn static_method_cannot_be_called_on_a_value() {
+    assert_eq!(check("static_method_with_receiver.lit"), ["E0046"]);
+}
+
+#[test]
+fn uncalled_template_is_checked() {
+    assert_eq!(check("uncalled_template.lit"), ["E0014"]);
+}
+
+#[test]
+fn unbounded_parameter_has_no_fields() {
+    assert_eq!(check("unbounded_field.lit"), ["E0023"]);
+}
+
+#[test]
+fn method_needs_its_condition_to_use_a_bound() {
+    assert_eq!(check("missing_bound.lit"), ["E0046"]);
+}
+
+#[test]
+fn generic_and_conditional_methods_check() {
+    assert_eq!(check("generic_methods.lit"), Vec::<String>::new());
+}
+
+#[test]
+fn generic_class_gets_a_default_constructor() {
+    assert_eq!(check("generic_default_ctor.lit"), Vec::<String>::new());
+}
+
+#[test]
+fn prelude_needs_no_imports() {
+    let output = run(&[], "prelude.lit");
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("panicked: done"));
+}
+
+#[test]
+fn no_std_leaves_the_prelude_out() {
+    assert_eq!(check("no_std.lit"), ["E0026"]);
+}
+
+#[test]
+fn variable_is_copied_into_a_new_one() {
+    let output = run(&[], "copy.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1\n2\n");
+}
+
+#[test]
+fn import_cycle_is_reported() {
+    assert_eq!(check("import_cycle.lit"), ["E0033"]);
+}
+
+#[test]
+fn explain_is_plain_when_piped() {
+    let output = Command::new(env!("CARGO_BIN_EXE_litc")).args(["--explain", "E0001"]).output().expect("failed to run litc");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.starts_with("E0001: Unterminated string literal\n\n"), "{}", stdout);
+    assert!(!stdout.contains('\x1B'));
+}
+
+#[test]
+fn parse_only_is_plain_when_piped() {
+    let output = litc(&["--parse-only"], "copy.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[Parse complete]: No syntax errors found\n");
+}
+
+#[test]
+fn strings_concatenate_measure_index_and_compare() {
+    let output = run(&[], "strings.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello, world\n12\no\ntrue\nfalse\nfalse\n");
+}
+
+#[test]
+fn negative_string_index_aborts_with_checks() {
+    let output = run(&["--checks"], "string_index_negative.lit");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "c\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("string_index_negative.lit:6:13: index out of bounds"));
+}
+
+#[test]
+fn string_index_past_the_end_aborts_with_checks() {
+    let output = run(&["--checks"], "string_index_past_end.lit");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("string_index_past_end.lit:5:13: index out of bounds"));
+}
+
+#[test]
+fn equal_string_literals_share_one_constant() {
+    let ir = fs::read_to_string(compile(&[], "string_pool.lit")).unwrap();
+    assert_eq!(ir.matches("c\"hi\\00\"").count(), 1);
+
+    let output = run(&[], "string_pool.lit");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "true\ntrue\nhi, hi\n");
+}
+
+#[test]
+fn user_names_do_not_clash_with_generated_ones() {
+    // `r0` looks like a temporary, `puts` and `exit` are C functions the
+    // runtime declares, and `été` is not a plain LLVM identifier
+    let output = run(&[], "mangling.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "été\n2\n7\n");
+}
+
+#[test]
+fn main_returns_the_exit_code_and_gets_the_arguments() {
+    let output = Command::new("lli").arg(compile(&[], "exit_code.lit")).args(["a", "b"]).output().expect("failed to run lli");
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+}
+
+#[test]
+fn function_defined_twice_is_reported() {
+    assert_eq!(check("duplicate_function.lit"), ["E0009"]);
+}
+
+#[test]
+fn program_without_main_is_reported() {
+    assert_eq!(check("missing_main.lit"), ["E0010"]);
+}
+
+#[test]
+fn code_after_unreachable_is_reported_and_left_out() {
+    assert_eq!(check("dead_code.lit"), ["W0007"]);
+
+    let ir = fs::read_to_string(compile(&[], "dead_code.lit")).unwrap();
+    assert!(!ir.contains("424242"));
+
+    let output = run(&[], "dead_code.lit");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "before\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("dead_code.lit:3:5: entered unreachable code: stop"));
+}
+
+#[test]
+fn panic_aborts_with_its_location() {
+    assert_eq!(check("panic.lit"), ["W0007"]);
+
+    let output = run(&[], "panic.lit");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("panic.lit:2:5: panicked: failed: no reason"));
+}
+
+#[test]
+fn failed_assert_aborts_with_its_location() {
+    let output = run(&[], "assert.lit");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "checked\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("assert.lit:5:5: assertion failed: x is too small"));
+
+    let output = run(&[], "assert_without_message.lit");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("assert_without_message.lit:3:5: assertion failed\n"));
+}
+
+#[test]
+fn unt_underflow_wraps_without_checks_and_aborts_with_them() {
+    let output = run(&[], "unt_underflow.lit");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "18446744073709551615\n");
+
+    let output = run(&["--checks"], "unt_underflow.lit");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unt_underflow.lit:4:13: attempt to subtract with overflow"));
+}
+
+#[test]
+fn division_by_zero_aborts_with_checks() {
+    let output = run(&["--checks"], "division_by_zero.lit");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "5\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("division_by_zero.lit:5:13: attempt to divide by zero"));
+}
+
+#[test]
+fn lints_warn_by_default() {
+    let mut codes = check("lints.lit");
+    codes.sort();
+
+    assert_eq!(codes, ["W0001", "W0002", "W0003", "W0004", "W0005", "W0006"]);
+    assert!(litc(&["--check"], "lints.lit").status.success());
+}
+
+#[test]
+fn lint_levels_follow_the_flags() {
+    let output = litc(&["--check", "-A", "unused_variables", "-D", "shadowing"], "lints.lit");
+    let mut codes = codes(&output);
+    codes.sort();
+
+    assert!(!output.status.success());
+    assert_eq!(codes, ["W0002", "W0003", "W0004", "W0005", "W0006"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains(r#""severity":"error","code":"W0003""#));
+
+    assert!(!litc(&["--check", "-Werror"], "lints.lit").status.success());
+}
+
+#[test]
+fn misspelt_names_get_suggestions() {
+    let stderr = String::from_utf8_lossy(&litc(&["--check"], "typos.lit").stderr).to_string();
+    assert!(stderr.contains(r#""suggestions":[{"message":"did you mean `println`?","replacement":"println""#), "{}", stderr);
+
+    let stderr = String::from_utf8_lossy(&litc(&["--check"], "variable_typo.lit").stderr).to_string();
+    assert!(stderr.contains(r#""replacement":"count""#), "{}", stderr);
+    assert!(stderr.contains(r#""range":{"start":{"line":3,"column":13},"end":{"line":3,"column":18}}}]"#), "{}", stderr);
+
+    // a type has no span of its own to replace, so its suggestion is a note
+    let stderr = String::from_utf8_lossy(&litc(&["--check"], "type_typo.lit").stderr).to_string();
+    assert!(stderr.contains(r#""notes":["did you mean `float`?"],"suggestions":[]"#), "{}", stderr);
+}
+
+#[test]
+fn json_diagnostics_go_to_stderr() {
+    let output = litc(&["--check"], "mismatched_types.lit");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let file = fixture("programs").join("mismatched_types.lit");
+
+    assert!(output.stdout.is_empty());
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(stderr.starts_with(r#"{"severity":"error","code":"E0014","message":"Cannot assign str value to variable `x` of type `int`""#), "{}", stderr);
+    assert!(stderr.contains(&format!(r#""file":"{}""#, file.display())), "{}", stderr);
+    assert!(stderr.contains(r#""span":{"start":25,"end":31},"range":{"start":{"line":2,"column":13},"end":{"line":2,"column":19}}"#), "{}", stderr);
+}
+
+#[test]
+fn human_output_is_plain_when_piped() {
+    let output = Command::new(env!("CARGO_BIN_EXE_litc"))
+        .arg("--check")
+        .arg(fixture("programs").join("mismatched_types.lit"))
+        .output()
+        .expect("failed to run litc");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(output.stdout.is_empty());
+    assert!(stderr.starts_with("[Error E0014]: Cannot assign str value"), "{}", stderr);
+    assert!(!stderr.contains('\x1B'));
+
+    let output = litc(&["--check"], "copy.lit");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[Analysis complete]: No Semantic errors found\n");
+}
+
+#[test]
+fn malformed_input_is_reported_without_a_panic() {
+    let cases = [
+        ("unterminated_char.lit", "E0002"),
+        ("huge_number.lit", "E0030"),
+        ("deep_nesting.lit", "E0031"),
+        ("unexpected_end.lit", "E0005"),
+    ];
+
+    for (program, code) in cases {
+        let output = litc(&["--parse-only"], program);
+
+        assert_eq!(output.status.code(), Some(1), "{}", program);
+        assert_eq!(codes(&output), [code], "{}", program);
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"), "{}", program);
+    }
+}
+
+#[test]
+fn errors_point_into_the_file_they_are_in() {
+    let output = litc(&["--check"], "error_in_import.lit");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let file = fixture("programs").join("remote").join("faulty.lit");
+
+    assert_eq!(codes(&output), ["E0020"]);
+    assert!(stderr.contains(&format!(r#""file":"{}""#, file.display())), "{}", stderr);
+    assert!(stderr.contains(r#""range":{"start":{"line":3,"column":12},"end":{"line":3,"column":19}}"#), "{}", stderr);
+}
+
+#[test]
+fn imported_items_are_found_in_nested_modules() {
+    let output = run(&[], "imports.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "9\n");
+}
+
+#[test]
+fn unresolved_import_is_reported() {
+    assert_eq!(check("unresolved_import.lit"), ["E0032"]);
+}
+
+#[test]
+fn module_items_are_reached_through_paths() {
+    let ir = fs::read_to_string(compile(&[], "modules.lit")).unwrap();
+    assert!(ir.contains("define i64 @lit.fn.geometry.units.scale("));
+
+    let output = run(&[], "modules.lit");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "4\n80\n10\n");
+}
+
+#[test]
+fn private_item_cannot_be_used_outside_its_module() {
+    assert_eq!(check("private_item.lit"), ["E0037"]);
+}
+
+#[test]
+fn standard_library_parses() {
+    let std_lib = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..").join("std_lib");
+    let files: Vec<PathBuf> = fs::read_dir(std_lib).unwrap().map(|entry| entry.unwrap().path()).collect();
+
+    assert!(!files.is_empty());
+
+    for file in files {
+        let output = Command::new(env!("CARGO_BIN_EXE_litc")).arg("--parse-only").arg(&file).output().expect("failed to run litc");
+        assert!(output.status.success(), "{}: {}", file.display(), String::from_utf8_lossy(&output.stderr));
+    }
+}
+
+#[test]
+fn methods_are_called_on_objects_and_primitives() {
+    let output = run(&[], "methods.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "6\n8\n");
+}
+
+#[test]
+fn enum_variants_carry_their_payloads() {
+    let output = run(&[], "enums.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "12\n15\n0\n");
+}
+
+#[test]
+fn switch_matches_literals_bindings_and_wildcards() {
+    let output = run(&[], "switch.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "one\nmany\nhello, world\nhi you\nno\n");
+}
+
+#[test]
+fn switch_on_integers_is_a_jump_table_and_on_strings_a_compare_chain() {
+    let ir = fs::read_to_string(compile(&[], "switch.lit")).unwrap();
+    let body = |func: &str| {
+        let start = ir.find(&format!("@lit.fn.{}(", func)).unwrap();
+        ir[start..].split("\n}\n").next().unwrap().to_string()
+    };
+
+    assert!(body("digit").contains("switch i64"));
+    assert!(!body("greet").contains("switch i64"));
+    assert!(body("greet").contains("call i32 @strcmp"));
+}
+
+#[test]
+fn switch_must_cover_every_variant() {
+    assert_eq!(check("non_exhaustive_switch.lit"), ["E0052"]);
+}
+
+#[test]
+fn arm_after_a_catch_all_is_unreachable() {
+    assert_eq!(check("unreachable_arm.lit"), ["W0008"]);
+}
+
+#[test]
+fn interfaces_are_implemented_by_classes_and_primitives() {
+    let output = run(&[], "interfaces.lit");
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "a number\nyes\na point\n");
+}
+
+#[test]
+fn missing_interface_method_is_reported() {
+    assert_eq!(check("missing_interface_method.lit"), ["E0053"]);
+}
+
+#[test]
+fn override_must_match_its_interface() {
+    assert_eq!(check("mismatched_override.lit"), ["E0054"]);
+}
@@ -3,13 +3,23 @@ static mut error_quantity: u32 = 0;
 
 #[macro_export]
 macro_rules! generate_plain_error {
+    ($code:ident, $($arg:tt)*) => {{
+        $crate::diagnostics::Diagnostic::error(format!($($arg)*))
+            .with_code(&$crate::diagnostics::codes::$code)
+            .abort()
+    }};
     ($($arg:tt)*) => {{
-        let msg = format!($($arg)*);
+        $crate::diagnostics::Diagnostic::error(format!($($arg)*)).abort()
+    }};
+}
 
-        println!(
-            "\x1B[1;31m[Error]:\x1B[0m {}",
-            msg
-        );
-        std::process::exit(1);
+/// Like [`generate_plain_error!`], for an error located at `span`.
+#[macro_export]
+macro_rules! generate_error {
+    ($span:expr, $code:ident, $($arg:tt)*) => {{
+        $crate::diagnostics::Diagnostic::error(format!($($arg)*))
+            .with_code(&$crate::diagnostics::codes::$code)
+            .with_span($span)
+            .abort()
     }};
-}
\ No newline at end of file
+}
@@ -8,6 +8,8 @@ use std::path::Path;
 use std::process::Command;
 use std::time::Instant;
 
+use toml::Toml;
+
 const VERSION: &str = "v0.1.0";
 const HELP_TEXT: &str = "lit - Lit package manager\n\
 \n\
@@ -24,7 +26,10 @@ const HELP_TEXT: &str = "lit - Lit package manager\n\
   -h, --help             Show this help message\n  \
   -v, --version          Show version\n  \
   -T                     Show how much time it took to compile the project into a binary\n  \
-  --litc-args \"args\"   Add compiler arguments if you build or run\
+  --litc-args \"args\"   Add compiler arguments if you build or run\n\
+\n\
+\x1B[1mEnvironment:\x1B[0m\n  \
+  LIT_HOME     Lit installation whose standard library `litc` uses\n               (default: the one `litc` is installed in)\
 ";
 
 enum Cmd {
@@ -150,10 +155,10 @@ impl Options {
     }
 
     fn build(litc_args: Vec<String>, run_after: bool) {
-        let toml = fs::read_to_string("build.toml").unwrap_or_else(|e| {
-            generate_error!("Cannot read `build.toml` due to: {}", e);
+        let toml = Options::read_toml();
+        let project_name = toml.get("project", "name").unwrap_or_else(|| {
+            generate_error!("Cannot find attribute: `name` in build.toml")
         });
-        let project_name = Options::parse_toml_name(&toml);
 
         let src_path = "src/main.lit";
         let ll_path = &format!("out/ir/{}.ll", project_name);
@@ -161,6 +166,7 @@ impl Options {
 
         println!("\x1B[1;32mCompiling\x1B[0m `{}`...", src_path);
         let litc = Command::new("litc")
+            .args(Options::lint_args(&toml))
             .args(litc_args)
             .args([src_path, "-o", ll_path])
             .status()
@@ -196,25 +202,45 @@ impl Options {
         }
     }
 
-    fn parse_toml_name(toml: &str) -> String {
-        for line in toml.lines() {
-            let line = line.trim();
+    fn read_toml() -> Toml {
+        let text = fs::read_to_string("build.toml").unwrap_or_else(|e| {
+            generate_error!("Cannot read `build.toml` due to: {}", e);
+        });
+
+        Toml::parse(&text)
+    }
 
-            if line.starts_with("name") {
-                if let Some(val) = line.split('=').nth(1) {
-                    return val.trim().trim_matches('"').to_string();
-                }
-            }
+    /// Turns the `[lints]` table into `-A`/`-W`/`-D` flags for litc, e.g.
+    /// `unused_variables = "allow"` becomes `-A unused_variables`.
+    fn lint_args(toml: &Toml) -> Vec<String> {
+        let mut args = Vec::new();
+
+        for (lint, level) in toml.section("lints") {
+            let flag = match level.as_str() {
+                "allow" => "-A",
+                "warn" => "-W",
+                "deny" => "-D",
+                other => generate_error!(
+                    "Unknown level `{}` for lint `{}` in build.toml (expected `allow`, `warn` or `deny`)",
+                    other, lint
+                ),
+            };
+
+            args.push(flag.to_string());
+            args.push(lint.clone());
         }
 
-        generate_error!("Cannot find attribute: `name` in build.toml")
+        args
     }
 
     fn check() {
         let src_path = "src/main.lit";
 
+        let toml = Options::read_toml();
+
         println!("\x1B[1;32mChecking\x1B[0m `{}`...", src_path);
         Command::new("litc")
+            .args(Options::lint_args(&toml))
             .args(["-S", src_path])
             .status()
             .unwrap_or_else(|_| generate_error!("Cannot run `litc`. Is it installed?"));
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+/// Minimal reader for `build.toml`.
+///
+/// Only `[section]` headers and `key = value` lines are understood. Quotes
+/// around values are stripped and `#` starts a comment. Keys that appear
+/// before any header belong to the section named `""`.
+pub struct Toml {
+    sections: HashMap<String, Vec<(String, String)>>,
+}
+
+impl Toml {
+    pub fn parse(text: &str) -> Toml {
+        let mut sections: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        let mut current = String::new();
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = header.trim().to_string();
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let key = key.trim().to_string();
+                let value = value.trim().trim_matches('"').to_string();
+
+                sections.entry(current.clone()).or_default().push((key, value));
+            }
+        }
+
+        Toml { sections }
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section).iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Every `key = value` pair of `section`, in file order.
+    pub fn section(&self, section: &str) -> &[(String, String)] {
+        self.sections.get(section).map(|s| s.as_slice()).unwrap_or(&[])
+    }
+}